@@ -0,0 +1,187 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+use crate::ai::tool_outcome::ToolOutcome;
+use crate::{memory_graph_repository, memory_store, memory_tag_service, reminder_store, scheduler};
+
+/// How long an action can be undone. Matches the soft-delete retention so a
+/// journaled delete can always be restored.
+pub const UNDO_WINDOW_SECONDS: i64 = memory_store::DELETED_MEMORY_RETENTION_SECONDS;
+
+/// What it takes to revert one change made by a tool.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum InverseOperation {
+    /// Reverts a save by soft-deleting the memory it created
+    DeleteMemory { memory_id: String },
+    /// Reverts a delete
+    RestoreMemory { memory_id: String },
+    /// Reverts an update
+    RestoreContent { memory_id: String, content: String },
+    /// Reverts tagging; only lists tags the memory did not have before
+    DetachTags { memory_id: String, tags: Vec<String> },
+    /// Reverts scheduling a reminder
+    CancelReminder { reminder_id: String },
+}
+
+impl InverseOperation {
+    /// Applies the inverse and describes what it did, e.g. `restored "..."`.
+    fn apply(&self, conn: &Connection, user_id: &str) -> Result<String, String> {
+        match self {
+            InverseOperation::DeleteMemory { memory_id } => {
+                let memory = memory_graph_repository::get_memory(conn, user_id, memory_id)?;
+                memory_store::delete_memory(conn, user_id, memory_id)?;
+                Ok(format!("removed \"{}\"", memory.content))
+            }
+            InverseOperation::RestoreMemory { memory_id } => {
+                memory_store::restore_memory(conn, user_id, memory_id)?;
+                let memory = memory_graph_repository::get_memory(conn, user_id, memory_id)?;
+                Ok(format!("restored \"{}\"", memory.content))
+            }
+            InverseOperation::RestoreContent { memory_id, content } => {
+                memory_store::update_memory_content(conn, user_id, memory_id, content)?;
+                Ok(format!("changed a memory back to \"{}\"", content))
+            }
+            InverseOperation::DetachTags { memory_id, tags } => {
+                for tag in memory_tag_service::get_memory_tags(conn, user_id, memory_id)?
+                    .iter()
+                    .filter(|tag| tags.contains(&tag.tag))
+                {
+                    memory_tag_service::detach_tag_from_memory(conn, user_id, memory_id, &tag.id)?;
+                }
+                let labels = tags.iter().map(|tag| format!("#{}", tag)).collect::<Vec<_>>();
+                Ok(format!("removed {}", labels.join(", ")))
+            }
+            InverseOperation::CancelReminder { reminder_id } => {
+                let reminder = reminder_store::get_reminder(conn, user_id, reminder_id)?;
+                // The worker fires from the task payload, so the task must go too
+                scheduler::cancel_reminder_task(conn, reminder_id)?;
+                reminder_store::delete_reminder(conn, user_id, reminder_id)?;
+                Ok(format!("cancelled the reminder \"{}\"", reminder.content))
+            }
+        }
+    }
+}
+
+/// What `undo_last_action` reverted.
+#[derive(Debug, Clone, Serialize)]
+pub struct UndoReport {
+    pub plan_id: String,
+    /// One description per reverted change, most recent first.
+    pub undone: Vec<String>,
+}
+
+impl UndoReport {
+    pub fn summary(&self) -> String {
+        format!("Undone: {}.", self.undone.join("; "))
+    }
+}
+
+/// Journals the inverses a tool reported, keyed by the plan that ran it.
+pub fn record_outcome(
+    conn: &Connection,
+    user_id: &str,
+    plan_id: &str,
+    intent: &str,
+    outcome: &ToolOutcome,
+) -> Result<(), String> {
+    if outcome.inverses.is_empty() {
+        return Ok(());
+    }
+    purge_expired_entries(conn)?;
+
+    let now = current_timestamp();
+    for inverse in &outcome.inverses {
+        let inverse_json =
+            serde_json::to_string(inverse).map_err(|e| format!("Failed to serialize inverse operation: {}", e))?;
+        conn.execute(
+            "INSERT INTO action_journal (id, user_id, plan_id, intent, inverse_json, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![Uuid::new_v4().to_string(), user_id, plan_id, intent, inverse_json, now],
+        )
+        .map_err(|e| format!("Failed to record action journal entry: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Reverts every change of the most recent plan that has not been undone yet,
+/// newest first. All inverses apply or none do. `None` when there is nothing
+/// left to undo inside the undo window.
+pub fn undo_last_action(conn: &Connection, user_id: &str) -> Result<Option<UndoReport>, String> {
+    let cutoff = current_timestamp() - UNDO_WINDOW_SECONDS;
+    let plan_id: Option<String> = conn
+        .query_row(
+            "SELECT plan_id FROM action_journal
+             WHERE user_id = ?1 AND undone_at IS NULL AND created_at >= ?2
+             ORDER BY created_at DESC, rowid DESC
+             LIMIT 1",
+            params![user_id, cutoff],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to query action journal: {}", e))?;
+
+    let Some(plan_id) = plan_id else {
+        return Ok(None);
+    };
+
+    let entries = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, inverse_json FROM action_journal
+                 WHERE user_id = ?1 AND plan_id = ?2 AND undone_at IS NULL
+                 ORDER BY created_at DESC, rowid DESC",
+            )
+            .map_err(|e| format!("Failed to prepare action journal query: {}", e))?;
+        let rows = stmt
+            .query_map(params![user_id, plan_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(|e| format!("Failed to query action journal: {}", e))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to collect action journal entries: {}", e))?
+    };
+
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Failed to start undo transaction: {}", e))?;
+    let now = current_timestamp();
+    let mut undone = Vec::new();
+    for (entry_id, inverse_json) in entries {
+        let inverse: InverseOperation = serde_json::from_str(&inverse_json)
+            .map_err(|e| format!("Failed to parse inverse operation: {}", e))?;
+        undone.push(
+            inverse
+                .apply(&tx, user_id)
+                .map_err(|e| format!("Could not undo the last action: {}", e))?,
+        );
+        tx.execute(
+            "UPDATE action_journal SET undone_at = ?1 WHERE id = ?2",
+            params![now, entry_id],
+        )
+        .map_err(|e| format!("Failed to mark journal entry undone: {}", e))?;
+    }
+    tx.commit()
+        .map_err(|e| format!("Failed to commit undo: {}", e))?;
+
+    Ok(Some(UndoReport { plan_id, undone }))
+}
+
+fn purge_expired_entries(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM action_journal WHERE created_at < ?1",
+        params![current_timestamp() - UNDO_WINDOW_SECONDS],
+    )
+    .map_err(|e| format!("Failed to purge action journal: {}", e))?;
+    Ok(())
+}
+
+fn current_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0) as i64
+}
@@ -0,0 +1,23 @@
+use serde_json::Value;
+use tauri::Emitter;
+
+/// The parts of the Tauri app the AI pipeline talks to. Lets the orchestrator
+/// run against a recording host in tests instead of a live `AppHandle`.
+pub trait AppHost: Send + Sync {
+    /// Sends an event to the frontend. Delivery failures are ignored.
+    fn emit_event(&self, event: &str, payload: Value);
+    fn open_url(&self, url: &str) -> Result<(), String>;
+    /// Partial text of the assistant reply being generated. Ignored unless the
+    /// host is streaming a chat turn.
+    fn stream_token(&self, _token: &str) {}
+}
+
+impl AppHost for tauri::AppHandle {
+    fn emit_event(&self, event: &str, payload: Value) {
+        let _ = self.emit(event, payload);
+    }
+
+    fn open_url(&self, url: &str) -> Result<(), String> {
+        crate::open_url_internal(url, self)
+    }
+}
@@ -1,10 +1,40 @@
+use super::app_host::AppHost;
 use super::orchestrator;
+use serde_json::{json, Value};
 
-/// Handles a user message through the AI orchestrator.
+/// Tags streamed text with the id of the assistant message it belongs to.
+struct ChatStreamHost<'a> {
+    inner: &'a dyn AppHost,
+    message_id: &'a str,
+}
+
+impl AppHost for ChatStreamHost<'_> {
+    fn emit_event(&self, event: &str, payload: Value) {
+        self.inner.emit_event(event, payload);
+    }
+
+    fn open_url(&self, url: &str) -> Result<(), String> {
+        self.inner.open_url(url)
+    }
+
+    fn stream_token(&self, token: &str) {
+        self.inner.emit_event(
+            "chat_token",
+            json!({
+                "message_id": self.message_id,
+                "token": token,
+            }),
+        );
+    }
+}
+
+/// Handles a user message through the AI orchestrator. Streamed answer text is
+/// emitted as `chat_token` events carrying `message_id`.
 pub async fn handle_chat(
     message: String,
+    message_id: &str,
     user_id: &str,
-    app_handle: &tauri::AppHandle,
+    app_handle: &dyn AppHost,
     registry: &crate::AppRegistry,
     memory_store: &crate::MemoryStore,
     plugin_registry: &crate::plugin_registry::PluginRegistry,
@@ -15,10 +45,15 @@ pub async fn handle_chat(
         return Err("Message cannot be empty".to_string());
     }
 
+    let host = ChatStreamHost {
+        inner: app_handle,
+        message_id,
+    };
+
     orchestrator::process_user_command(
         message,
         user_id,
-        app_handle,
+        &host,
         registry,
         memory_store,
         plugin_registry,
@@ -0,0 +1,319 @@
+use serde::Serialize;
+
+use crate::chat_history_store::ChatMessageRecord;
+
+/// Tokens a whole prompt may use, system instructions and user request included.
+pub const PROMPT_TOKEN_BUDGET: usize = 3000;
+pub const HISTORY_TOKEN_BUDGET: usize = 600;
+pub const RUNTIME_CONTEXT_TOKEN_BUDGET: usize = 300;
+pub const MEMORY_CONTEXT_TOKEN_BUDGET: usize = 800;
+/// Chat turns loaded for history; whatever does not fit is summarized.
+pub const HISTORY_TURNS: i32 = 20;
+
+/// Words kept per turn in the summary of older turns.
+const SUMMARY_WORDS_PER_TURN: usize = 12;
+/// Below this a section is dropped rather than cut to a useless stub.
+const MIN_SECTION_TOKENS: usize = 16;
+
+/// Rough token count for budgeting: about four characters per token, and at
+/// least one per word so short-word text is not undercounted.
+pub fn estimate_tokens(text: &str) -> usize {
+    let by_chars = text.chars().count().div_ceil(4);
+    let by_words = text.split_whitespace().count();
+    by_chars.max(by_words)
+}
+
+/// Which end of a section survives truncation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Keep {
+    /// Lists ordered by relevance
+    Head,
+    /// Conversation history, where the latest lines matter most
+    Tail,
+}
+
+#[derive(Debug, Clone)]
+struct PromptSection {
+    name: &'static str,
+    content: String,
+    budget: usize,
+    priority: u8,
+    keep: Keep,
+}
+
+/// How one section fared, for the prompt debug view.
+#[derive(Debug, Clone, Serialize)]
+pub struct SectionUsage {
+    pub name: String,
+    pub budget: usize,
+    pub original_tokens: usize,
+    pub tokens: usize,
+    pub truncated: bool,
+    pub dropped: bool,
+}
+
+/// Section contents after budgeting, in the order they were added.
+#[derive(Debug, Clone)]
+pub struct AssembledContext {
+    sections: Vec<(&'static str, String)>,
+    pub usage: Vec<SectionUsage>,
+}
+
+impl AssembledContext {
+    pub fn get(&self, name: &str) -> &str {
+        self.sections
+            .iter()
+            .find(|(section, _)| *section == name)
+            .map(|(_, content)| content.as_str())
+            .unwrap_or("")
+    }
+}
+
+/// Fits optional prompt sections into what is left of a token budget once the
+/// fixed text (system prompt, user request) is reserved. Every section is first
+/// cut to its own budget; if the total still overflows, the lowest-priority
+/// sections shrink first and are dropped once they get too small.
+#[derive(Debug, Clone)]
+pub struct ContextAssembler {
+    available: usize,
+    sections: Vec<PromptSection>,
+}
+
+impl ContextAssembler {
+    pub fn new(total_budget: usize) -> Self {
+        Self {
+            available: total_budget,
+            sections: Vec::new(),
+        }
+    }
+
+    /// Reserves room for text that is always sent in full.
+    pub fn reserve(mut self, fixed_text: &str) -> Self {
+        self.available = self.available.saturating_sub(estimate_tokens(fixed_text));
+        self
+    }
+
+    /// Adds a section; a higher `priority` is kept longer when space runs out.
+    pub fn section(mut self, name: &'static str, content: &str, budget: usize, priority: u8, keep: Keep) -> Self {
+        self.sections.push(PromptSection {
+            name,
+            content: content.trim().to_string(),
+            budget,
+            priority,
+            keep,
+        });
+        self
+    }
+
+    pub fn assemble(self) -> AssembledContext {
+        let original = self
+            .sections
+            .iter()
+            .map(|section| estimate_tokens(&section.content))
+            .collect::<Vec<_>>();
+        let mut contents = self
+            .sections
+            .iter()
+            .map(|section| truncate_to_tokens(&section.content, section.budget, section.keep))
+            .collect::<Vec<_>>();
+
+        let mut order = (0..self.sections.len()).collect::<Vec<_>>();
+        // Lowest priority first; among equals, the section added last gives way first.
+        order.sort_by_key(|&idx| (self.sections[idx].priority, std::cmp::Reverse(idx)));
+        for idx in order {
+            let used = contents.iter().map(|content| estimate_tokens(content)).sum::<usize>();
+            if used <= self.available {
+                break;
+            }
+            let current = estimate_tokens(&contents[idx]);
+            let allowed = current.saturating_sub(used - self.available);
+            contents[idx] = if allowed < MIN_SECTION_TOKENS {
+                String::new()
+            } else {
+                truncate_to_tokens(&contents[idx], allowed, self.sections[idx].keep)
+            };
+        }
+
+        let usage = self
+            .sections
+            .iter()
+            .zip(&contents)
+            .zip(original)
+            .map(|((section, content), original_tokens)| {
+                let tokens = estimate_tokens(content);
+                SectionUsage {
+                    name: section.name.to_string(),
+                    budget: section.budget,
+                    original_tokens,
+                    tokens,
+                    truncated: tokens < original_tokens,
+                    dropped: content.is_empty() && original_tokens > 0,
+                }
+            })
+            .collect();
+
+        AssembledContext {
+            sections: self.sections.iter().map(|section| section.name).zip(contents).collect(),
+            usage,
+        }
+    }
+}
+
+/// Cuts `text` to about `budget` tokens on line boundaries, keeping whole lines
+/// from the `keep` end. A single line that is too long is cut on a word boundary.
+pub fn truncate_to_tokens(text: &str, budget: usize, keep: Keep) -> String {
+    if estimate_tokens(text) <= budget {
+        return text.to_string();
+    }
+
+    let mut lines = text.lines().collect::<Vec<_>>();
+    if keep == Keep::Tail {
+        lines.reverse();
+    }
+    let mut kept = Vec::new();
+    let mut used = 0;
+    for line in &lines {
+        let cost = estimate_tokens(line) + 1;
+        if used + cost > budget {
+            break;
+        }
+        used += cost;
+        kept.push(*line);
+    }
+
+    if kept.is_empty() {
+        if let Some(line) = lines.first() {
+            return clip_words(line, budget, keep);
+        }
+    }
+    if keep == Keep::Tail {
+        kept.reverse();
+    }
+    kept.join("\n")
+}
+
+fn clip_words(line: &str, budget: usize, keep: Keep) -> String {
+    if estimate_tokens(line) <= budget {
+        return line.to_string();
+    }
+    let mut words = line.split_whitespace().collect::<Vec<_>>();
+    if keep == Keep::Tail {
+        words.reverse();
+    }
+    let mut kept = Vec::new();
+    for word in words {
+        kept.push(word);
+        if estimate_tokens(&kept.join(" ")) + 1 > budget {
+            kept.pop();
+            break;
+        }
+    }
+    if kept.is_empty() {
+        return String::new();
+    }
+    if keep == Keep::Tail {
+        kept.reverse();
+        format!("…{}", kept.join(" "))
+    } else {
+        format!("{}…", kept.join(" "))
+    }
+}
+
+/// Formats chat history (oldest first) within `budget`. The newest turns are
+/// kept verbatim; once they no longer fit, the older turns collapse into one
+/// summary line of their opening words.
+pub fn fit_history(messages: &[ChatMessageRecord], budget: usize) -> String {
+    let lines = messages
+        .iter()
+        .map(|message| format!("{}: {}", message.role, message.content))
+        .collect::<Vec<_>>();
+    let full = lines.join("\n");
+    if estimate_tokens(&full) <= budget {
+        return full;
+    }
+
+    // A quarter of the budget goes to the summary, the rest to recent turns.
+    let recent_budget = budget - budget / 4;
+    let mut recent = Vec::new();
+    let mut used = 0;
+    for line in lines.iter().rev() {
+        let cost = estimate_tokens(line) + 1;
+        if used + cost > recent_budget {
+            break;
+        }
+        used += cost;
+        recent.push(line.clone());
+    }
+    recent.reverse();
+
+    const SUMMARY_PREFIX: &str = "Earlier in this conversation (summarized):";
+    let older = &messages[..messages.len() - recent.len()];
+    let turns = older
+        .iter()
+        .map(|message| format!("{}: {}", message.role, opening_words(&message.content)))
+        .collect::<Vec<_>>()
+        .join(" | ");
+    let summary_budget = budget.saturating_sub(used + estimate_tokens(SUMMARY_PREFIX) + 1);
+    let summary = clip_words(&turns, summary_budget, Keep::Tail);
+
+    std::iter::once(summary)
+        .filter(|summary| !summary.is_empty())
+        .map(|summary| format!("{} {}", SUMMARY_PREFIX, summary))
+        .chain(recent)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn opening_words(content: &str) -> String {
+    let words = content.split_whitespace().collect::<Vec<_>>();
+    if words.len() <= SUMMARY_WORDS_PER_TURN {
+        words.join(" ")
+    } else {
+        format!("{}…", words[..SUMMARY_WORDS_PER_TURN].join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(role: &str, content: &str) -> ChatMessageRecord {
+        ChatMessageRecord {
+            id: String::new(),
+            role: role.to_string(),
+            content: content.to_string(),
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn low_priority_sections_give_way_first() {
+        let memories = (1..=40).map(|i| format!("{}. memory number {}", i, i)).collect::<Vec<_>>().join("\n");
+        let assembled = ContextAssembler::new(200)
+            .reserve(&"word ".repeat(60))
+            .section("runtime", &"runtime detail ".repeat(20), 100, 2, Keep::Head)
+            .section("memories", &memories, 400, 1, Keep::Head)
+            .assemble();
+
+        let used = assembled.usage.iter().map(|usage| usage.tokens).sum::<usize>();
+        assert!(used <= 140);
+        assert!(!assembled.usage[0].truncated);
+        assert!(assembled.usage[1].truncated);
+        assert!(assembled.get("memories").starts_with("1. memory number 1\n"));
+    }
+
+    #[test]
+    fn older_turns_are_summarized_when_history_overflows() {
+        let mut messages = (0..10)
+            .map(|i| turn("user", &format!("message {} {}", i, "filler words here ".repeat(10))))
+            .collect::<Vec<_>>();
+        messages.push(turn("assistant", "Latest answer"));
+
+        let history = fit_history(&messages, 150);
+
+        assert!(estimate_tokens(&history) <= 150);
+        assert!(history.starts_with("Earlier in this conversation (summarized): "));
+        assert!(history.ends_with("assistant: Latest answer"));
+        assert_eq!(fit_history(&messages[10..], 150), "assistant: Latest answer");
+    }
+}
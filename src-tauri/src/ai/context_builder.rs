@@ -16,9 +16,10 @@ pub fn build_current_datetime_context() -> String {
 pub fn build_runtime_context(
     memory_store: &crate::MemoryStore,
     user_id: &str,
+    permissions: &crate::PermissionManager,
 ) -> String {
     let datetime = build_current_datetime_context();
-    let system = crate::system::system_context_service::get_system_context();
+    let system = crate::system::system_context_service::get_system_context(permissions);
 
     let recent_commands = {
         let conn = match memory_store.conn.lock() {
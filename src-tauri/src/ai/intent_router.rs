@@ -1,28 +1,34 @@
-use super::{orchestrator::StructuredIntent, tool_executor};
+use super::app_host::AppHost;
+use super::tool_outcome::ToolOutcome;
+use super::{orchestrator::StructuredIntent, tool_executor, tool_registry};
 use serde_json::json;
 
 pub async fn route_intent(
     user_message: &str,
     structured_intent: StructuredIntent,
     user_id: &str,
-    app_handle: &tauri::AppHandle,
+    app_handle: &dyn AppHost,
     registry: &crate::AppRegistry,
     memory_store: &crate::MemoryStore,
     plugin_registry: &crate::plugin_registry::PluginRegistry,
     event_bus: &crate::EventBus,
     permissions: &crate::PermissionManager,
-) -> Result<String, String> {
+) -> Result<ToolOutcome, String> {
     if structured_intent.confidence <= 0.0 {
-        return Ok("I'm not sure what you meant. Could you rephrase that?".to_string());
+        return Ok(ToolOutcome::needs_input("I'm not sure what you meant. Could you rephrase that?"));
     }
 
     if structured_intent.confidence < 0.35 {
-        return Ok(format!(
+        return Ok(ToolOutcome::needs_input(format!(
             "I'm not entirely sure what you'd like me to do. Could you be more specific?",
-        ));
+        )));
     }
 
-    match structured_intent.intent.as_str() {
+    let tool = tool_registry::find_tool(&structured_intent.intent)
+        .ok_or_else(|| format!("Unsupported intent returned by LLM: {}", structured_intent.intent))?;
+    tool_registry::validate_arguments(tool, &structured_intent.parameters)?;
+
+    match tool.name {
         "set_reminder" => tool_executor::execute_set_reminder(
             &structured_intent.parameters,
             user_message,
@@ -49,13 +55,14 @@ pub async fn route_intent(
             event_bus,
             permissions,
         ),
-        "delete_memory" | "forget_memory" => tool_executor::execute_delete_memory(
+        "delete_memory" => tool_executor::execute_delete_memory(
             &structured_intent.parameters,
             user_id,
             memory_store,
             event_bus,
             permissions,
         ),
+        "undo" => tool_executor::execute_undo(user_id, memory_store, event_bus, permissions),
         "search_memory" => tool_executor::execute_search_memory(
             &structured_intent.parameters,
             user_id,
@@ -63,6 +70,20 @@ pub async fn route_intent(
             event_bus,
             permissions,
         ),
+        "tag_memory" => tool_executor::execute_tag_memory(
+            &structured_intent.parameters,
+            user_id,
+            memory_store,
+            event_bus,
+            permissions,
+        ),
+        "list_by_tag" => tool_executor::execute_list_by_tag(
+            &structured_intent.parameters,
+            user_id,
+            memory_store,
+            event_bus,
+            permissions,
+        ),
         "query_timetable" => {
             // Backward compatibility: legacy timetable intent is now handled via memory search.
             let query = extract_subject_query(user_message)
@@ -83,6 +104,11 @@ pub async fn route_intent(
             event_bus,
             permissions,
         ),
+        "kill_process" => tool_executor::execute_kill_process(
+            &structured_intent.parameters,
+            event_bus,
+            permissions,
+        ),
         "search_web" => tool_executor::execute_search_web(
             &structured_intent.parameters,
             app_handle,
@@ -94,18 +120,21 @@ pub async fn route_intent(
             memory_store,
             plugin_registry,
             event_bus,
+            permissions,
         ),
         "ai_query" => {
             tool_executor::execute_ai_query(
                 &structured_intent.parameters,
                 user_message,
                 user_id,
+                app_handle,
                 memory_store,
+                permissions,
             )
             .await
         }
-        "unknown" => Ok("No actionable system command detected.".to_string()),
-        other => Err(format!("Unsupported intent returned by LLM: {}", other)),
+        "unknown" => Ok(ToolOutcome::needs_input("No actionable system command detected.")),
+        other => Err(format!("No route for intent: {}", other)),
     }
 }
 
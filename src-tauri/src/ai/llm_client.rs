@@ -1,54 +1,20 @@
-use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
-const GEMINI_API_ENDPOINT: &str = "https://generativelanguage.googleapis.com/v1beta/models/gemini-3-flash-preview:generateContent";
+use super::llm_provider::{TokenSink, ToolCall};
 
-#[derive(Debug, Serialize)]
-struct GeminiRequest {
-    contents: Vec<Content>,
-}
-
-#[derive(Debug, Serialize)]
-struct Content {
-    parts: Vec<Part>,
-}
-
-#[derive(Debug, Serialize)]
-struct Part {
-    text: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct GeminiResponse {
-    candidates: Vec<Candidate>,
-}
-
-#[derive(Debug, Deserialize)]
-struct Candidate {
-    content: ResponseContent,
-}
+const RETRY_DELAYS_MS: [u64; 3] = [250, 500, 1000];
 
-#[derive(Debug, Deserialize)]
-struct ResponseContent {
-    parts: Vec<ResponsePart>,
-}
-
-#[derive(Debug, Deserialize)]
-struct ResponsePart {
-    text: String,
-}
-
-/// Sends a prompt to Gemini and returns the response text.
+/// Sends a prompt to the active LLM provider and returns the response text.
 pub async fn generate_structured_response(prompt: String) -> Result<String, String> {
     let mut last_error = String::new();
-    let retry_delays_ms = [250_u64, 500_u64, 1000_u64];
 
-    for (idx, delay) in retry_delays_ms.iter().enumerate() {
+    for (idx, delay) in RETRY_DELAYS_MS.iter().enumerate() {
         match generate_structured_response_once(prompt.clone()).await {
             Ok(value) => return Ok(value),
             Err(err) => {
                 last_error = err;
-                if idx + 1 < retry_delays_ms.len() {
+                if idx + 1 < RETRY_DELAYS_MS.len() {
                     tokio::time::sleep(Duration::from_millis(*delay)).await;
                 }
             }
@@ -79,64 +45,53 @@ pub async fn generate_structured_response(prompt: String) -> Result<String, Stri
     Err(last_error)
 }
 
-pub async fn request_action_plan(prompt: String) -> Result<String, String> {
-    generate_structured_response(prompt).await
-}
+/// Like `generate_structured_response`, but hands text to `on_token` as it arrives.
+/// Failed attempts are retried only while nothing has been streamed yet, so the
+/// user never sees a reply restart. There is no rule-based fallback.
+pub async fn generate_streaming_response(prompt: String, on_token: TokenSink<'_>) -> Result<String, String> {
+    let provider = super::llm_provider::active_provider();
+    let streamed = AtomicBool::new(false);
+    let forward = |token: &str| {
+        streamed.store(true, Ordering::Relaxed);
+        on_token(token);
+    };
 
-async fn generate_structured_response_once(prompt: String) -> Result<String, String> {
-    let api_key = std::env::var("GEMINI_API_KEY")
-        .map_err(|_| "GEMINI_API_KEY environment variable not set".to_string())?;
+    let mut last_error = String::new();
+    for (idx, delay) in RETRY_DELAYS_MS.iter().enumerate() {
+        match provider.generate_stream(&prompt, &forward).await {
+            Ok(value) => return Ok(value),
+            Err(err) if streamed.load(Ordering::Relaxed) => return Err(err),
+            Err(err) => {
+                last_error = err;
+                if idx + 1 < RETRY_DELAYS_MS.len() {
+                    tokio::time::sleep(Duration::from_millis(*delay)).await;
+                }
+            }
+        }
+    }
 
-    let url = format!("{}?key={}", GEMINI_API_ENDPOINT, api_key);
+    Err(last_error)
+}
 
-    let request_body = GeminiRequest {
-        contents: vec![Content {
-            parts: vec![Part {
-                text: prompt,
-            }],
-        }],
-    };
+pub async fn request_action_plan(prompt: String) -> Result<String, String> {
+    generate_structured_response(prompt).await
+}
 
-    let client = reqwest::Client::new();
-    let response = client
-        .post(&url)
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send request to Gemini: {}", e))?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!(
-            "Gemini API error (status {}): {}",
-            status, error_text
-        ));
+/// Plans through native function calling. Returns None when the active provider
+/// has no function calling, so callers can use the JSON prompt instead.
+pub async fn request_tool_calls(prompt: String) -> Option<Result<Vec<ToolCall>, String>> {
+    let provider = super::llm_provider::active_provider();
+    if !provider.supports_tools() {
+        return None;
     }
 
-    let gemini_response: GeminiResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Gemini response: {}", e))?;
-
-    let response_text = gemini_response
-        .candidates
-        .first()
-        .map(|candidate| {
-            candidate
-                .content
-                .parts
-                .iter()
-                .map(|part| part.text.as_str())
-                .collect::<Vec<_>>()
-                .join("\n")
-        })
-        .ok_or_else(|| "No response text from Gemini".to_string())?;
-
-    Ok(response_text)
+    let tools = super::tool_registry::exposed_tools();
+    Some(provider.generate_tool_calls(&prompt, &tools).await)
+}
+
+async fn generate_structured_response_once(prompt: String) -> Result<String, String> {
+    let provider = super::llm_provider::active_provider();
+    provider.generate(&prompt).await
 }
 
 fn fallback_rule_based_intent(prompt: &str) -> String {
@@ -176,8 +131,14 @@ fn fallback_rule_based_intent(prompt: &str) -> String {
 fn extract_user_request(prompt: &str) -> String {
     let marker = "User request:";
     if let Some(idx) = prompt.rfind(marker) {
+        // Templates put the message on the line after the marker.
         let tail = &prompt[idx + marker.len()..];
-        return tail.lines().next().unwrap_or("unknown").trim().to_string();
+        return tail
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or("unknown")
+            .to_string();
     }
     "unknown".to_string()
 }
@@ -0,0 +1,805 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use super::tool_registry::ToolSpec;
+use crate::settings_service::{AppSettings, LlmProviderKind};
+
+pub type LlmFuture<'a> = Pin<Box<dyn Future<Output = Result<String, String>> + Send + 'a>>;
+
+/// A function call the model chose, with its arguments as a JSON object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolCall {
+    pub name: String,
+    pub arguments: Value,
+}
+
+pub type ToolCallFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<ToolCall>, String>> + Send + 'a>>;
+
+/// Receives each text fragment of a streamed completion as it arrives.
+pub type TokenSink<'a> = &'a (dyn Fn(&str) + Send + Sync);
+
+/// A text-in, text-out completion backend.
+/// Retries and rule-based fallbacks live in `llm_client`, not here.
+pub trait LlmProvider: Send + Sync {
+    /// Shown in errors and logs, e.g. "Gemini".
+    fn name(&self) -> &str;
+    fn model(&self) -> &str;
+    fn generate<'a>(&'a self, prompt: &'a str) -> LlmFuture<'a>;
+
+    /// Streams the completion through `on_token` and resolves to the full text.
+    /// Backends without streaming deliver the whole reply as a single fragment.
+    fn generate_stream<'a>(&'a self, prompt: &'a str, on_token: TokenSink<'a>) -> LlmFuture<'a> {
+        Box::pin(async move {
+            let text = self.generate(prompt).await?;
+            on_token(&text);
+            Ok(text)
+        })
+    }
+
+    /// Whether `generate_tool_calls` is implemented for this backend.
+    fn supports_tools(&self) -> bool {
+        false
+    }
+
+    /// Asks the model to answer only with calls to `tools`.
+    fn generate_tool_calls<'a>(&'a self, _prompt: &'a str, _tools: &'a [&'static ToolSpec]) -> ToolCallFuture<'a> {
+        Box::pin(async move { Err(format!("{} does not support function calling", self.name())) })
+    }
+}
+
+const GEMINI_DEFAULT_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+const GEMINI_DEFAULT_MODEL: &str = "gemini-3-flash-preview";
+const OPENAI_DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+const OPENAI_DEFAULT_MODEL: &str = "gpt-4o-mini";
+const OLLAMA_DEFAULT_BASE_URL: &str = "http://localhost:11434";
+const OLLAMA_DEFAULT_MODEL: &str = "llama3.1";
+
+// ============================================================================
+// GEMINI
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+struct GeminiRequest {
+    contents: Vec<Content>,
+}
+
+#[derive(Debug, Serialize)]
+struct Content {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Serialize)]
+struct Part {
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiResponse {
+    #[serde(default)]
+    candidates: Vec<Candidate>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Candidate {
+    content: ResponseContent,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseContent {
+    #[serde(default)]
+    parts: Vec<ResponsePart>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponsePart {
+    #[serde(default)]
+    text: String,
+    #[serde(default, rename = "functionCall")]
+    function_call: Option<GeminiFunctionCall>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiFunctionCall {
+    name: String,
+    #[serde(default)]
+    args: Value,
+}
+
+impl GeminiResponse {
+    fn first_candidate_text(&self) -> Option<String> {
+        self.candidates.first().map(|candidate| {
+            candidate
+                .content
+                .parts
+                .iter()
+                .map(|part| part.text.as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+    }
+}
+
+/// Google Gemini `generateContent`; the key comes from GEMINI_API_KEY.
+pub struct GeminiProvider {
+    base_url: String,
+    model: String,
+}
+
+impl GeminiProvider {
+    pub fn new(base_url: Option<String>, model: Option<String>) -> Self {
+        Self {
+            base_url: base_url.unwrap_or_else(|| GEMINI_DEFAULT_BASE_URL.to_string()),
+            model: model.unwrap_or_else(|| GEMINI_DEFAULT_MODEL.to_string()),
+        }
+    }
+
+    /// `method` is "generateContent" or "streamGenerateContent?alt=sse".
+    async fn send(&self, method: &str, prompt: &str) -> Result<reqwest::Response, String> {
+        let api_key = std::env::var("GEMINI_API_KEY")
+            .map_err(|_| "GEMINI_API_KEY environment variable not set".to_string())?;
+
+        let separator = if method.contains('?') { '&' } else { '?' };
+        let url = format!(
+            "{}/models/{}:{}{}key={}",
+            self.base_url.trim_end_matches('/'),
+            self.model,
+            method,
+            separator,
+            api_key
+        );
+
+        let request_body = GeminiRequest {
+            contents: vec![Content {
+                parts: vec![Part {
+                    text: prompt.to_string(),
+                }],
+            }],
+        };
+
+        reqwest::Client::new()
+            .post(&url)
+            .json(&request_body)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to send request to Gemini: {}", e))
+    }
+
+    async fn generate_content(&self, prompt: &str) -> Result<String, String> {
+        let response = self.send("generateContent", prompt).await?;
+        let gemini_response: GeminiResponse = read_json_response(response, self.name()).await?;
+
+        gemini_response
+            .first_candidate_text()
+            .ok_or_else(|| "No response text from Gemini".to_string())
+    }
+
+    async fn function_calls(&self, prompt: &str, tools: &[&'static ToolSpec]) -> Result<Vec<ToolCall>, String> {
+        let api_key = std::env::var("GEMINI_API_KEY")
+            .map_err(|_| "GEMINI_API_KEY environment variable not set".to_string())?;
+        let url = format!(
+            "{}/models/{}:generateContent?key={}",
+            self.base_url.trim_end_matches('/'),
+            self.model,
+            api_key
+        );
+
+        let declarations = tools
+            .iter()
+            .map(|tool| {
+                let mut declaration = json!({ "name": tool.name, "description": tool.description });
+                // Gemini rejects an empty properties object
+                if !tool.params.is_empty() {
+                    declaration["parameters"] = tool.parameters_schema();
+                }
+                declaration
+            })
+            .collect::<Vec<_>>();
+        let request_body = json!({
+            "contents": [{ "parts": [{ "text": prompt }] }],
+            "tools": [{ "functionDeclarations": declarations }],
+            "toolConfig": { "functionCallingConfig": { "mode": "ANY" } },
+        });
+
+        let response = reqwest::Client::new()
+            .post(&url)
+            .json(&request_body)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to send request to Gemini: {}", e))?;
+        let gemini_response: GeminiResponse = read_json_response(response, self.name()).await?;
+
+        let calls = gemini_response
+            .candidates
+            .into_iter()
+            .next()
+            .map(|candidate| candidate.content.parts)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|part| part.function_call)
+            .map(|call| ToolCall {
+                name: call.name,
+                arguments: call.args,
+            })
+            .collect::<Vec<_>>();
+        non_empty_calls(calls, self.name())
+    }
+
+    async fn stream_content(&self, prompt: &str, on_token: TokenSink<'_>) -> Result<String, String> {
+        let response = self.send("streamGenerateContent?alt=sse", prompt).await?;
+        let response = ensure_success(response, self.name()).await?;
+
+        let mut text = String::new();
+        for_each_line(response, self.name(), |line| {
+            let Some(data) = sse_data(line) else {
+                return Ok(());
+            };
+            let chunk: GeminiResponse = serde_json::from_str(data)
+                .map_err(|e| format!("Failed to parse Gemini stream chunk: {}", e))?;
+            if let Some(fragment) = chunk.first_candidate_text().filter(|f| !f.is_empty()) {
+                on_token(&fragment);
+                text.push_str(&fragment);
+            }
+            Ok(())
+        })
+        .await?;
+
+        if text.is_empty() {
+            return Err("No response text from Gemini".to_string());
+        }
+        Ok(text)
+    }
+}
+
+impl LlmProvider for GeminiProvider {
+    fn name(&self) -> &str {
+        "Gemini"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn generate<'a>(&'a self, prompt: &'a str) -> LlmFuture<'a> {
+        Box::pin(self.generate_content(prompt))
+    }
+
+    fn generate_stream<'a>(&'a self, prompt: &'a str, on_token: TokenSink<'a>) -> LlmFuture<'a> {
+        Box::pin(self.stream_content(prompt, on_token))
+    }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    fn generate_tool_calls<'a>(&'a self, prompt: &'a str, tools: &'a [&'static ToolSpec]) -> ToolCallFuture<'a> {
+        Box::pin(self.function_calls(prompt, tools))
+    }
+}
+
+// ============================================================================
+// OPENAI-COMPATIBLE CHAT COMPLETIONS
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+struct ChatCompletionRequest<'a> {
+    model: &'a str,
+    messages: Vec<ChatCompletionMessage<'a>>,
+    stream: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatCompletionMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionResponse {
+    choices: Vec<ChatCompletionChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionChoice {
+    message: ChatCompletionReply,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionReply {
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<ChatCompletionToolCall>,
+}
+
+/// Shared by OpenAI and Ollama; OpenAI encodes `arguments` as a JSON string.
+#[derive(Debug, Deserialize)]
+struct ChatCompletionToolCall {
+    function: ChatCompletionFunction,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionFunction {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+impl ChatCompletionToolCall {
+    fn into_tool_call(self) -> Result<ToolCall, String> {
+        let arguments = match self.function.arguments {
+            Value::String(raw) if raw.trim().is_empty() => json!({}),
+            Value::String(raw) => serde_json::from_str(&raw)
+                .map_err(|e| format!("Invalid arguments for {}: {}", self.function.name, e))?,
+            other => other,
+        };
+        Ok(ToolCall {
+            name: self.function.name,
+            arguments,
+        })
+    }
+}
+
+fn function_tools(tools: &[&'static ToolSpec]) -> Vec<Value> {
+    tools
+        .iter()
+        .map(|tool| {
+            json!({
+                "type": "function",
+                "function": {
+                    "name": tool.name,
+                    "description": tool.description,
+                    "parameters": tool.parameters_schema(),
+                }
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionChunk {
+    #[serde(default)]
+    choices: Vec<ChatCompletionChunkChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionChunkChoice {
+    delta: ChatCompletionReply,
+}
+
+/// Any server exposing `/chat/completions` (OpenAI, LM Studio, vLLM, llama.cpp).
+/// The key from settings is sent as a bearer token; without one, OPENAI_API_KEY
+/// is used for api.openai.com only. Local servers usually need none.
+pub struct OpenAiCompatibleProvider {
+    base_url: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+impl OpenAiCompatibleProvider {
+    pub fn new(base_url: Option<String>, model: Option<String>, api_key: Option<String>) -> Self {
+        let base_url = base_url.unwrap_or_else(|| OPENAI_DEFAULT_BASE_URL.to_string());
+        let api_key = api_key.or_else(|| {
+            is_openai_host(&base_url)
+                .then(|| std::env::var("OPENAI_API_KEY").ok())
+                .flatten()
+                .map(|key| key.trim().to_string())
+                .filter(|key| !key.is_empty())
+        });
+        Self {
+            base_url,
+            model: model.unwrap_or_else(|| OPENAI_DEFAULT_MODEL.to_string()),
+            api_key,
+        }
+    }
+
+    fn post(&self, body: &impl Serialize) -> reqwest::RequestBuilder {
+        let url = format!("{}/chat/completions", self.base_url.trim_end_matches('/'));
+        let request = reqwest::Client::new().post(&url).json(body);
+        match &self.api_key {
+            Some(api_key) => request.bearer_auth(api_key),
+            None => request,
+        }
+    }
+
+    async fn send(&self, prompt: &str, stream: bool) -> Result<reqwest::Response, String> {
+        let request_body = ChatCompletionRequest {
+            model: &self.model,
+            messages: vec![ChatCompletionMessage {
+                role: "user",
+                content: prompt,
+            }],
+            stream,
+        };
+
+        self.post(&request_body)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to send request to {}: {}", self.name(), e))
+    }
+
+    async fn chat_completion(&self, prompt: &str) -> Result<String, String> {
+        let response = self.send(prompt, false).await?;
+        let completion: ChatCompletionResponse = read_json_response(response, self.name()).await?;
+
+        completion
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.message.content)
+            .ok_or_else(|| format!("No response text from {}", self.name()))
+    }
+
+    async fn function_calls(&self, prompt: &str, tools: &[&'static ToolSpec]) -> Result<Vec<ToolCall>, String> {
+        let request_body = json!({
+            "model": self.model,
+            "messages": [{ "role": "user", "content": prompt }],
+            "tools": function_tools(tools),
+            "tool_choice": "required",
+        });
+
+        let response = self
+            .post(&request_body)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to send request to {}: {}", self.name(), e))?;
+        let completion: ChatCompletionResponse = read_json_response(response, self.name()).await?;
+
+        let calls = completion
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message.tool_calls)
+            .unwrap_or_default()
+            .into_iter()
+            .map(ChatCompletionToolCall::into_tool_call)
+            .collect::<Result<Vec<_>, _>>()?;
+        non_empty_calls(calls, self.name())
+    }
+
+    async fn stream_completion(&self, prompt: &str, on_token: TokenSink<'_>) -> Result<String, String> {
+        let response = self.send(prompt, true).await?;
+        let response = ensure_success(response, self.name()).await?;
+
+        let mut text = String::new();
+        for_each_line(response, self.name(), |line| {
+            let Some(data) = sse_data(line).filter(|data| *data != "[DONE]") else {
+                return Ok(());
+            };
+            let chunk: ChatCompletionChunk = serde_json::from_str(data)
+                .map_err(|e| format!("Failed to parse {} stream chunk: {}", self.name(), e))?;
+            if let Some(fragment) = chunk
+                .choices
+                .into_iter()
+                .next()
+                .and_then(|choice| choice.delta.content)
+                .filter(|f| !f.is_empty())
+            {
+                on_token(&fragment);
+                text.push_str(&fragment);
+            }
+            Ok(())
+        })
+        .await?;
+
+        if text.is_empty() {
+            return Err(format!("No response text from {}", self.name()));
+        }
+        Ok(text)
+    }
+}
+
+impl LlmProvider for OpenAiCompatibleProvider {
+    fn name(&self) -> &str {
+        "OpenAI-compatible"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn generate<'a>(&'a self, prompt: &'a str) -> LlmFuture<'a> {
+        Box::pin(self.chat_completion(prompt))
+    }
+
+    fn generate_stream<'a>(&'a self, prompt: &'a str, on_token: TokenSink<'a>) -> LlmFuture<'a> {
+        Box::pin(self.stream_completion(prompt, on_token))
+    }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    fn generate_tool_calls<'a>(&'a self, prompt: &'a str, tools: &'a [&'static ToolSpec]) -> ToolCallFuture<'a> {
+        Box::pin(self.function_calls(prompt, tools))
+    }
+}
+
+// ============================================================================
+// OLLAMA
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+struct OllamaGenerateRequest<'a> {
+    model: &'a str,
+    prompt: &'a str,
+    stream: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaGenerateResponse {
+    #[serde(default)]
+    response: String,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaChatResponse {
+    message: ChatCompletionReply,
+}
+
+/// Local Ollama server via `/api/generate`; function calling uses `/api/chat`.
+pub struct OllamaProvider {
+    base_url: String,
+    model: String,
+}
+
+impl OllamaProvider {
+    pub fn new(base_url: Option<String>, model: Option<String>) -> Self {
+        Self {
+            base_url: base_url.unwrap_or_else(|| OLLAMA_DEFAULT_BASE_URL.to_string()),
+            model: model.unwrap_or_else(|| OLLAMA_DEFAULT_MODEL.to_string()),
+        }
+    }
+
+    async fn send(&self, prompt: &str, stream: bool) -> Result<reqwest::Response, String> {
+        let url = format!("{}/api/generate", self.base_url.trim_end_matches('/'));
+        let request_body = OllamaGenerateRequest {
+            model: &self.model,
+            prompt,
+            stream,
+        };
+
+        reqwest::Client::new()
+            .post(&url)
+            .json(&request_body)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to send request to Ollama: {}", e))
+    }
+
+    async fn generate_once(&self, prompt: &str) -> Result<String, String> {
+        let response = self.send(prompt, false).await?;
+        let generated: OllamaGenerateResponse = read_json_response(response, self.name()).await?;
+        Ok(generated.response)
+    }
+
+    /// Only models trained for tools (e.g. llama3.1, qwen2.5) return calls.
+    async fn function_calls(&self, prompt: &str, tools: &[&'static ToolSpec]) -> Result<Vec<ToolCall>, String> {
+        let url = format!("{}/api/chat", self.base_url.trim_end_matches('/'));
+        let request_body = json!({
+            "model": self.model,
+            "messages": [{ "role": "user", "content": prompt }],
+            "tools": function_tools(tools),
+            "stream": false,
+        });
+
+        let response = reqwest::Client::new()
+            .post(&url)
+            .json(&request_body)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to send request to Ollama: {}", e))?;
+        let chat: OllamaChatResponse = read_json_response(response, self.name()).await?;
+
+        let calls = chat
+            .message
+            .tool_calls
+            .into_iter()
+            .map(ChatCompletionToolCall::into_tool_call)
+            .collect::<Result<Vec<_>, _>>()?;
+        non_empty_calls(calls, self.name())
+    }
+
+    /// Ollama streams one JSON object per line rather than SSE.
+    async fn stream_once(&self, prompt: &str, on_token: TokenSink<'_>) -> Result<String, String> {
+        let response = self.send(prompt, true).await?;
+        let response = ensure_success(response, self.name()).await?;
+
+        let mut text = String::new();
+        for_each_line(response, self.name(), |line| {
+            if line.trim().is_empty() {
+                return Ok(());
+            }
+            let chunk: OllamaGenerateResponse = serde_json::from_str(line)
+                .map_err(|e| format!("Failed to parse Ollama stream chunk: {}", e))?;
+            if let Some(error) = chunk.error {
+                return Err(format!("Ollama API error: {}", error));
+            }
+            if !chunk.response.is_empty() {
+                on_token(&chunk.response);
+                text.push_str(&chunk.response);
+            }
+            Ok(())
+        })
+        .await?;
+
+        Ok(text)
+    }
+}
+
+impl LlmProvider for OllamaProvider {
+    fn name(&self) -> &str {
+        "Ollama"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn generate<'a>(&'a self, prompt: &'a str) -> LlmFuture<'a> {
+        Box::pin(self.generate_once(prompt))
+    }
+
+    fn generate_stream<'a>(&'a self, prompt: &'a str, on_token: TokenSink<'a>) -> LlmFuture<'a> {
+        Box::pin(self.stream_once(prompt, on_token))
+    }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    fn generate_tool_calls<'a>(&'a self, prompt: &'a str, tools: &'a [&'static ToolSpec]) -> ToolCallFuture<'a> {
+        Box::pin(self.function_calls(prompt, tools))
+    }
+}
+
+async fn ensure_success(response: reqwest::Response, provider_name: &str) -> Result<reqwest::Response, String> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+
+    let status = response.status();
+    let error_text = response
+        .text()
+        .await
+        .unwrap_or_else(|_| "Unknown error".to_string());
+    Err(format!(
+        "{} API error (status {}): {}",
+        provider_name, status, error_text
+    ))
+}
+
+async fn read_json_response<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+    provider_name: &str,
+) -> Result<T, String> {
+    ensure_success(response, provider_name)
+        .await?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse {} response: {}", provider_name, e))
+}
+
+/// Feeds each complete line of a chunked body to `on_line`, stopping at the first error.
+async fn for_each_line(
+    mut response: reqwest::Response,
+    provider_name: &str,
+    mut on_line: impl FnMut(&str) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut buffer: Vec<u8> = Vec::new();
+
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to read {} stream: {}", provider_name, e))?
+    {
+        buffer.extend_from_slice(&chunk);
+        while let Some(newline) = buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=newline).collect();
+            on_line(String::from_utf8_lossy(&line).trim_end())?;
+        }
+    }
+
+    if !buffer.is_empty() {
+        on_line(String::from_utf8_lossy(&buffer).trim_end())?;
+    }
+    Ok(())
+}
+
+fn non_empty_calls(calls: Vec<ToolCall>, provider_name: &str) -> Result<Vec<ToolCall>, String> {
+    if calls.is_empty() {
+        return Err(format!("{} returned no function calls", provider_name));
+    }
+    Ok(calls)
+}
+
+/// True when `base_url` points at OpenAI itself, the only host OPENAI_API_KEY may be sent to.
+fn is_openai_host(base_url: &str) -> bool {
+    reqwest::Url::parse(base_url)
+        .map(|url| url.scheme() == "https" && url.host_str() == Some("api.openai.com"))
+        .unwrap_or(false)
+}
+
+/// Payload of an SSE `data:` line; other fields and comments are ignored.
+fn sse_data(line: &str) -> Option<&str> {
+    line.strip_prefix("data:").map(str::trim)
+}
+
+// ============================================================================
+// ACTIVE PROVIDER
+// ============================================================================
+
+/// Builds the backend selected in settings. Blank model/base URL fall back to the provider's defaults.
+pub fn provider_from_settings(settings: &AppSettings) -> Arc<dyn LlmProvider> {
+    let non_empty = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+    let base_url = non_empty(&settings.llm_base_url);
+    let model = non_empty(&settings.llm_model);
+    let api_key = non_empty(&settings.llm_api_key);
+
+    match settings.llm_provider {
+        LlmProviderKind::Gemini => Arc::new(GeminiProvider::new(base_url, model)),
+        LlmProviderKind::OpenaiCompatible => Arc::new(OpenAiCompatibleProvider::new(base_url, model, api_key)),
+        LlmProviderKind::Ollama => Arc::new(OllamaProvider::new(base_url, model)),
+    }
+}
+
+fn provider_slot() -> &'static RwLock<Arc<dyn LlmProvider>> {
+    static PROVIDER: OnceLock<RwLock<Arc<dyn LlmProvider>>> = OnceLock::new();
+    PROVIDER.get_or_init(|| RwLock::new(Arc::new(GeminiProvider::new(None, None))))
+}
+
+/// Returns the provider used for every LLM call.
+pub fn active_provider() -> Arc<dyn LlmProvider> {
+    match provider_slot().read() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+pub fn set_active_provider(provider: Arc<dyn LlmProvider>) {
+    match provider_slot().write() {
+        Ok(mut guard) => *guard = provider,
+        Err(poisoned) => *poisoned.into_inner() = provider,
+    }
+}
+
+/// Re-selects the provider after settings load or change.
+pub fn configure_from_settings(settings: &AppSettings) {
+    let provider = provider_from_settings(settings);
+    println!("✓ LLM provider: {} ({})", provider.name(), provider.model());
+    set_active_provider(provider);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn openai_key_is_only_sent_to_openai() {
+        assert!(is_openai_host(OPENAI_DEFAULT_BASE_URL));
+        assert!(is_openai_host("https://API.openai.com/v1/"));
+        assert!(!is_openai_host("http://api.openai.com/v1"));
+        assert!(!is_openai_host("https://api.openai.com.evil.example/v1"));
+        assert!(!is_openai_host("https://evil.example/api.openai.com"));
+        assert!(!is_openai_host("http://localhost:1234/v1"));
+
+        let local = OpenAiCompatibleProvider::new(Some("http://localhost:1234/v1".into()), None, None);
+        assert_eq!(local.api_key, None);
+        let configured = OpenAiCompatibleProvider::new(
+            Some("http://localhost:1234/v1".into()),
+            None,
+            Some("lm-studio".into()),
+        );
+        assert_eq!(configured.api_key.as_deref(), Some("lm-studio"));
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use super::llm_provider::{LlmFuture, LlmProvider, TokenSink, ToolCall, ToolCallFuture};
+use super::tool_registry::ToolSpec;
+
+/// Which pipeline stage a prompt came from, recognised by the template's opening line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PromptKind {
+    /// Function-calling planner; respond with a JSON array of `{"name", "arguments"}`.
+    ToolPlan,
+    ActionPlan,
+    Intent,
+    ReminderNormalization,
+    Keywords,
+    Timetable,
+    Answer,
+}
+
+impl PromptKind {
+    pub fn classify(prompt: &str) -> Self {
+        if prompt.contains("calls to the provided tools") {
+            PromptKind::ToolPlan
+        } else if prompt.contains("action planning engine") {
+            PromptKind::ActionPlan
+        } else if prompt.contains("AI system controller") {
+            PromptKind::Intent
+        } else if prompt.contains("reminder normalizer") {
+            PromptKind::ReminderNormalization
+        } else if prompt.contains("semantic keywords") {
+            PromptKind::Keywords
+        } else if prompt.contains("timetable parser") {
+            PromptKind::Timetable
+        } else {
+            PromptKind::Answer
+        }
+    }
+}
+
+/// Replays canned responses per prompt kind, in the order they were scripted.
+/// The last response for a kind is repeated once its queue is down to one entry;
+/// kinds with nothing scripted fail like an unreachable API.
+#[derive(Default)]
+pub struct ScriptedProvider {
+    responses: Mutex<HashMap<PromptKind, VecDeque<Result<String, String>>>>,
+    calls: Mutex<Vec<(PromptKind, String)>>,
+}
+
+impl ScriptedProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn respond(self, kind: PromptKind, response: impl Into<String>) -> Self {
+        self.push(kind, Ok(response.into()));
+        self
+    }
+
+    pub fn fail(self, kind: PromptKind, error: impl Into<String>) -> Self {
+        self.push(kind, Err(error.into()));
+        self
+    }
+
+    fn push(&self, kind: PromptKind, response: Result<String, String>) {
+        if let Ok(mut responses) = self.responses.lock() {
+            responses.entry(kind).or_default().push_back(response);
+        }
+    }
+
+    /// Every prompt received so far, in order.
+    pub fn calls(&self) -> Vec<(PromptKind, String)> {
+        self.calls.lock().map(|calls| calls.clone()).unwrap_or_default()
+    }
+
+    pub fn call_count(&self, kind: PromptKind) -> usize {
+        self.calls().iter().filter(|(k, _)| *k == kind).count()
+    }
+
+    fn next_response(&self, prompt: &str) -> Result<String, String> {
+        let kind = PromptKind::classify(prompt);
+        if let Ok(mut calls) = self.calls.lock() {
+            calls.push((kind, prompt.to_string()));
+        }
+
+        let mut responses = self
+            .responses
+            .lock()
+            .map_err(|e| format!("Scripted provider lock error: {}", e))?;
+        let queue = responses.get_mut(&kind).filter(|queue| !queue.is_empty());
+        let next = match queue {
+            Some(queue) if queue.len() > 1 => queue.pop_front(),
+            Some(queue) => queue.front().cloned(),
+            None => None,
+        };
+        next.unwrap_or_else(|| Err(format!("No scripted response for {:?} prompt", kind)))
+    }
+}
+
+impl LlmProvider for ScriptedProvider {
+    fn name(&self) -> &str {
+        "Scripted"
+    }
+
+    fn model(&self) -> &str {
+        "scripted"
+    }
+
+    fn generate<'a>(&'a self, prompt: &'a str) -> LlmFuture<'a> {
+        let response = self.next_response(prompt);
+        Box::pin(async move { response })
+    }
+
+    /// Function calling is offered only when a tool plan was scripted.
+    fn supports_tools(&self) -> bool {
+        self.responses
+            .lock()
+            .map(|responses| responses.contains_key(&PromptKind::ToolPlan))
+            .unwrap_or(false)
+    }
+
+    fn generate_tool_calls<'a>(&'a self, prompt: &'a str, _tools: &'a [&'static ToolSpec]) -> ToolCallFuture<'a> {
+        let calls = self.next_response(prompt).and_then(|raw| {
+            let values: Vec<serde_json::Value> = serde_json::from_str(&raw)
+                .map_err(|e| format!("Invalid scripted tool calls: {}", e))?;
+            Ok(values
+                .into_iter()
+                .map(|value| ToolCall {
+                    name: value["name"].as_str().unwrap_or_default().to_string(),
+                    arguments: value["arguments"].clone(),
+                })
+                .collect())
+        });
+        Box::pin(async move { calls })
+    }
+
+    /// Streams word by word so consumers see more than one fragment.
+    fn generate_stream<'a>(&'a self, prompt: &'a str, on_token: TokenSink<'a>) -> LlmFuture<'a> {
+        let response = self.next_response(prompt);
+        Box::pin(async move {
+            let text = response?;
+            for word in text.split_inclusive(' ') {
+                on_token(word);
+            }
+            Ok(text)
+        })
+    }
+}
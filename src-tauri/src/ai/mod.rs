@@ -1,12 +1,21 @@
 // AI module for LLM orchestration and tool execution.
+pub mod app_host;
 pub mod chat_service;
+pub mod context_budget;
 pub mod context_builder;
 pub mod intent_router;
 pub mod llm_client;
+pub mod llm_provider;
+#[cfg(test)]
+pub mod mock_provider;
 pub mod orchestrator;
 pub mod planner;
 pub mod prompt_templates;
 pub mod schedule_parser;
+#[cfg(test)]
+pub mod test_harness;
 pub mod tool_executor;
+pub mod tool_outcome;
+pub mod tool_registry;
 
 pub use chat_service::handle_chat;
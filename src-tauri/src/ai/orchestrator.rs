@@ -2,8 +2,9 @@ use chrono::Local;
 use serde::Deserialize;
 use serde_json::Value;
 
-use super::{llm_client, prompt_templates};
-use super::planner::{action_plan::ActionStep, action_plan_parser, plan_executor};
+use super::app_host::AppHost;
+use super::{context_budget, llm_client, prompt_templates};
+use super::planner::{action_plan::{ActionPlan, ActionStep}, action_plan_parser, plan_executor};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct StructuredIntent {
@@ -17,30 +18,29 @@ pub struct StructuredIntent {
 pub async fn process_user_command(
     message: String,
     user_id: &str,
-    app_handle: &tauri::AppHandle,
+    app_handle: &dyn AppHost,
     registry: &crate::AppRegistry,
     memory_store: &crate::MemoryStore,
     plugin_registry: &crate::plugin_registry::PluginRegistry,
     event_bus: &crate::EventBus,
     permissions: &crate::PermissionManager,
 ) -> Result<String, String> {
-    let history_text = {
+    // "2" or "the second one" answers a pending "which memory?" question.
+    let awaiting_choice = {
         let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
-        let items = crate::chat_history_store::get_messages(&conn, user_id, 6).unwrap_or_default();
-        items.iter()
-            .map(|m| format!("{}: {}", m.role, m.content))
-            .collect::<Vec<_>>()
-            .join("\n")
+        crate::pending_plan_store::latest_plan_awaiting_choice(&conn, user_id)?
     };
-
-    let runtime_context = super::context_builder::build_runtime_context(memory_store, user_id);
-    let planning_prompt = prompt_templates::build_action_planning_prompt(&message, &history_text, &runtime_context);
-    let raw_plan = llm_client::request_action_plan(planning_prompt).await?;
-    let mut plan = match action_plan_parser::parse_and_validate_action_plan(&raw_plan) {
-        Ok(plan) => plan,
-        Err(_) => {
-            return execute_legacy_single_intent(
-                &message,
+    if let Some(pending) = awaiting_choice {
+        if let Some(reply) = plan_executor::parse_choice_reply(&message, &pending.choices) {
+            let (approve, choice) = match reply {
+                plan_executor::ChoiceReply::Pick(idx) => (true, Some(idx)),
+                plan_executor::ChoiceReply::Cancel => (false, None),
+            };
+            let progress = plan_executor::resume_action_plan(
+                &pending.id,
+                approve,
+                choice,
+                None,
                 user_id,
                 app_handle,
                 registry,
@@ -48,10 +48,39 @@ pub async fn process_user_command(
                 plugin_registry,
                 event_bus,
                 permissions,
-                &runtime_context,
-                &history_text,
             )
-            .await;
+            .await?;
+            return Ok(progress.message);
+        }
+    }
+
+    let history_text = load_history_text(memory_store, user_id)?;
+
+    let runtime_context = super::context_builder::build_runtime_context(memory_store, user_id, permissions);
+    let mut plan = match plan_with_tool_calls(&message, &history_text, &runtime_context).await {
+        Some(plan) => plan,
+        None => {
+            let planning_prompt =
+                prompt_templates::build_action_planning_prompt(&message, &history_text, &runtime_context);
+            let raw_plan = llm_client::request_action_plan(planning_prompt).await?;
+            match action_plan_parser::parse_and_validate_action_plan(&raw_plan) {
+                Ok(plan) => plan,
+                Err(_) => {
+                    return execute_legacy_single_intent(
+                        &message,
+                        user_id,
+                        app_handle,
+                        registry,
+                        memory_store,
+                        plugin_registry,
+                        event_bus,
+                        permissions,
+                        &runtime_context,
+                        &history_text,
+                    )
+                    .await;
+                }
+            }
         }
     };
 
@@ -91,10 +120,65 @@ pub async fn process_user_command(
     .await
 }
 
+/// Recent conversation for planning prompts, with older turns summarized once
+/// they no longer fit the history budget.
+fn load_history_text(memory_store: &crate::MemoryStore, user_id: &str) -> Result<String, String> {
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let items = crate::chat_history_store::get_messages(&conn, user_id, context_budget::HISTORY_TURNS)
+        .unwrap_or_default();
+    Ok(context_budget::fit_history(&items, context_budget::HISTORY_TOKEN_BUDGET))
+}
+
+/// The planning prompt `process_user_command` would send for `message`, with
+/// per-section token usage. Nothing is sent to the model.
+pub fn preview_planning_prompt(
+    message: &str,
+    user_id: &str,
+    memory_store: &crate::MemoryStore,
+    permissions: &crate::PermissionManager,
+) -> Result<prompt_templates::PromptComposition, String> {
+    let history_text = load_history_text(memory_store, user_id)?;
+    let runtime_context = super::context_builder::build_runtime_context(memory_store, user_id, permissions);
+    Ok(prompt_templates::compose_action_planning_prompt(message, &history_text, &runtime_context))
+}
+
+/// Builds the plan from native function calls when the provider supports them.
+/// None means the caller should fall back to the JSON planning prompt.
+async fn plan_with_tool_calls(message: &str, history_text: &str, runtime_context: &str) -> Option<ActionPlan> {
+    let prompt = prompt_templates::build_tool_planning_prompt(message, history_text, runtime_context);
+    let calls = match llm_client::request_tool_calls(prompt).await? {
+        Ok(calls) => calls,
+        Err(err) => {
+            eprintln!("⚠️  Function-calling planner failed, using JSON planner: {}", err);
+            return None;
+        }
+    };
+
+    let mut plan = ActionPlan {
+        actions: calls
+            .into_iter()
+            .map(|call| ActionStep {
+                intent: call.name,
+                parameters: call.arguments,
+                requires_confirmation: false,
+            })
+            .collect(),
+        reasoning: Some("function calling".to_string()),
+    };
+
+    match action_plan_parser::validate_plan(&mut plan) {
+        Ok(()) => Some(plan),
+        Err(err) => {
+            eprintln!("⚠️  Function-calling plan rejected, using JSON planner: {}", err);
+            None
+        }
+    }
+}
+
 async fn execute_legacy_single_intent(
     message: &str,
     user_id: &str,
-    app_handle: &tauri::AppHandle,
+    app_handle: &dyn AppHost,
     registry: &crate::AppRegistry,
     memory_store: &crate::MemoryStore,
     plugin_registry: &crate::plugin_registry::PluginRegistry,
@@ -118,7 +202,37 @@ async fn execute_legacy_single_intent(
         normalize_reminder_parameters_with_llm(message, &mut structured_intent).await;
     }
 
-    super::intent_router::route_intent(
+    // Confident intents run as a one-step plan, so destructive steps pause for
+    // confirmation, capability policies apply and a "which one?" question is
+    // persisted for the user's pick.
+    if structured_intent.confidence >= 0.35 {
+        let destructive = super::tool_registry::find_tool(&structured_intent.intent)
+            .map(|tool| tool.requires_confirmation)
+            .unwrap_or(false);
+        let mut plan = ActionPlan {
+            actions: vec![ActionStep {
+                intent: structured_intent.intent,
+                parameters: structured_intent.parameters,
+                requires_confirmation: destructive,
+            }],
+            reasoning: Some("Fallback from single-intent payload".to_string()),
+        };
+        action_plan_parser::validate_plan(&mut plan)?;
+        return plan_executor::execute_action_plan(
+            message,
+            plan,
+            user_id,
+            app_handle,
+            registry,
+            memory_store,
+            plugin_registry,
+            event_bus,
+            permissions,
+        )
+        .await;
+    }
+
+    let outcome = super::intent_router::route_intent(
         message,
         structured_intent,
         user_id,
@@ -129,7 +243,9 @@ async fn execute_legacy_single_intent(
         event_bus,
         permissions,
     )
-    .await
+    .await?;
+
+    Ok(outcome.message)
 }
 
 async fn normalize_reminder_parameters_with_llm(
@@ -181,6 +297,15 @@ async fn normalize_reminder_parameters_with_llm(
         merged["time_description"] = Value::String(time_description.to_string());
     }
 
+    if let Some(recurrence) = parsed
+        .get("recurrence")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+    {
+        merged["recurrence"] = Value::String(recurrence.to_string());
+    }
+
     structured_intent.parameters = merged;
 
     if let Some(confidence) = parsed
@@ -236,12 +361,532 @@ fn extract_json_object(raw_response: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::extract_json_object;
+    use crate::ai::mock_provider::{PromptKind, ScriptedProvider};
+    use crate::ai::test_harness::Harness;
 
     #[test]
     fn extracts_json_from_code_fence() {
         let value = extract_json_object("```json\n{\"intent\":\"open_app\"}\n```");
         assert_eq!(value, "{\"intent\":\"open_app\"}");
     }
+
+    #[tokio::test]
+    async fn planned_memory_is_saved() {
+        let provider = ScriptedProvider::new().respond(
+            PromptKind::ActionPlan,
+            r#"{"actions":[{"intent":"save_memory","parameters":{"content":"My locker code is 4512"}}]}"#,
+        );
+        let harness = Harness::new(provider).await;
+
+        let reply = harness.send("remember my locker code is 4512").await.unwrap();
+
+        assert_eq!(reply, "Got it, I'll remember that.");
+        assert_eq!(harness.memory_contents(), vec!["My locker code is 4512".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn reminder_uses_normalized_trigger_time() {
+        let normalized_at = chrono::Local::now().timestamp() + 7200;
+        let provider = ScriptedProvider::new()
+            .respond(
+                PromptKind::ActionPlan,
+                r#"{"actions":[{"intent":"set_reminder","parameters":{"content":"call mom","time_description":"in an hour"}}]}"#,
+            )
+            .respond(
+                PromptKind::ReminderNormalization,
+                format!(r#"{{"content":"Call mom","trigger_at":{},"confidence":0.9}}"#, normalized_at),
+            );
+        let harness = Harness::new(provider).await;
+
+        let reply = harness.send("remind me to call mom in two hours").await.unwrap();
+
+        let reminders = harness.pending_reminders();
+        assert_eq!(reminders.len(), 1);
+        assert_eq!(reminders[0].content, "Call mom");
+        assert_eq!(reminders[0].trigger_at, normalized_at);
+        assert!(reply.starts_with("Reminder scheduled for"));
+        assert_eq!(harness.host.event_names(), vec!["reminder_scheduled".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn unparseable_plan_falls_back_to_single_intent() {
+        let provider = ScriptedProvider::new()
+            .respond(PromptKind::ActionPlan, "Sure! I'll look that up for you.")
+            .respond(
+                PromptKind::Intent,
+                r#"{"intent":"search_web","parameters":{"query":"rust lifetimes"},"confidence":0.9}"#,
+            );
+        let harness = Harness::new(provider).await;
+
+        let reply = harness.send("search the web for rust lifetimes").await.unwrap();
+
+        assert_eq!(
+            harness.host.opened_urls(),
+            vec!["https://www.google.com/search?q=rust+lifetimes".to_string()]
+        );
+        assert_eq!(reply, "Opened: https://www.google.com/search?q=rust+lifetimes");
+        assert_eq!(harness.provider.call_count(PromptKind::Intent), 1);
+    }
+
+    #[tokio::test]
+    async fn low_confidence_intent_asks_for_clarification() {
+        let provider = ScriptedProvider::new()
+            .respond(PromptKind::ActionPlan, "not a plan")
+            .respond(PromptKind::Intent, r#"{"intent":"open_app","parameters":{},"confidence":0.2}"#);
+        let harness = Harness::new(provider).await;
+
+        let reply = harness.send("do the thing").await.unwrap();
+
+        assert!(reply.contains("Could you be more specific?"));
+        assert!(harness.host.opened_urls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn correction_overrides_planned_actions() {
+        let provider = ScriptedProvider::new().respond(
+            PromptKind::ActionPlan,
+            r#"{"actions":[{"intent":"save_memory","parameters":{"content":"actually it's at 11 AM"}}]}"#,
+        );
+        let harness = Harness::new(provider).await;
+        crate::save_memory(&harness.memory_store, &harness.user_id, "class of Big Data at 10 AM on monday").unwrap();
+
+        let reply = harness.send("actually it's at 11 AM").await.unwrap();
+
+        assert!(reply.starts_with("Updated memory:"));
+        let memories = harness.memory_contents();
+        assert_eq!(memories.len(), 1);
+        assert!(memories[0].contains("11 AM"));
+        assert!(!memories[0].contains("10 AM"));
+    }
+
+    #[tokio::test]
+    async fn denied_capability_reports_failed_step() {
+        let provider = ScriptedProvider::new()
+            .respond(
+                PromptKind::ActionPlan,
+                r#"{"actions":[{"intent":"set_reminder","parameters":{"content":"stretch","trigger_at":4102444800}}]}"#,
+            )
+            .respond(PromptKind::ReminderNormalization, "{}");
+        let harness = Harness::new(provider).await;
+        harness.permissions.revoke(crate::Capability::ReminderSchedule);
+
+        let reply = harness.send("remind me to stretch").await.unwrap();
+
+        assert!(reply.contains("failed on set_reminder"));
+        assert!(harness.pending_reminders().is_empty());
+    }
+
+    #[tokio::test]
+    async fn user_permission_changes_reach_running_plans() {
+        let provider = ScriptedProvider::new().respond(
+            PromptKind::ActionPlan,
+            r#"{"actions":[{"intent":"search_web","parameters":{"query":"rust lifetimes"}}]}"#,
+        );
+        let harness = Harness::new(provider).await;
+        let settings_copy = harness.permissions.clone();
+        settings_copy.apply_user_permissions(&crate::permissions_service::UserPermissions {
+            network_access: false,
+            ..Default::default()
+        });
+
+        let reply = harness.send("search the web for rust lifetimes").await.unwrap();
+
+        assert!(reply.contains("failed on search_web"));
+        assert!(harness.host.opened_urls().is_empty());
+        assert!(!harness.permissions.allows(crate::Capability::OpenApp));
+        assert!(harness.permissions.allows(crate::Capability::MemoryWrite));
+    }
+
+    #[tokio::test]
+    async fn ask_policy_pauses_until_granted_and_deny_policy_refuses() {
+        use crate::permission_policy_store::{self, policy, GrantScope};
+        let provider = ScriptedProvider::new()
+            .respond(
+                PromptKind::ActionPlan,
+                r#"{"actions":[{"intent":"save_memory","parameters":{"content":"Locker 12"}}]}"#,
+            )
+            .respond(
+                PromptKind::ActionPlan,
+                r#"{"actions":[{"intent":"save_memory","parameters":{"content":"Locker 14"}}]}"#,
+            )
+            .respond(
+                PromptKind::ActionPlan,
+                r#"{"actions":[{"intent":"search_memory","parameters":{"query":"locker"}}]}"#,
+            );
+        let harness = Harness::new(provider).await;
+        {
+            let conn = harness.memory_store.conn.lock().unwrap();
+            permission_policy_store::set_policy(&conn, &harness.user_id, crate::Capability::MemoryWrite, policy::ASK)
+                .unwrap();
+            permission_policy_store::set_policy(&conn, &harness.user_id, crate::Capability::MemoryRead, policy::DENY)
+                .unwrap();
+        }
+
+        let reply = harness.send("remember locker 12").await.unwrap();
+
+        assert!(reply.contains("needs your permission to use memory_write"));
+        assert!(harness.memory_contents().is_empty());
+        let request = &harness.host.event_payloads("plan_confirmation_required")[0];
+        assert_eq!(request["permission"], "memory_write");
+        let progress = harness.grant(request["plan_id"].as_str().unwrap(), GrantScope::Session).await.unwrap();
+        assert_eq!(progress.status, "completed");
+
+        // The session grant covers the next save without asking again.
+        harness.send("remember locker 14").await.unwrap();
+        assert_eq!(harness.memory_contents().len(), 2);
+        assert_eq!(harness.host.event_payloads("plan_confirmation_required").len(), 1);
+
+        let reply = harness.send("what's my locker").await.unwrap();
+        assert!(reply.contains("You've blocked Noddy from using memory_read."));
+
+        let conn = harness.memory_store.conn.lock().unwrap();
+        let decisions = permission_policy_store::list_decisions(&conn, &harness.user_id, 10).unwrap();
+        let trail = decisions
+            .iter()
+            .rev()
+            .map(|record| format!("{}:{}:{}", record.capability, record.decision, record.source))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            trail,
+            vec![
+                "memory_write:ask:policy",
+                "memory_write:allow:user",
+                "memory_write:allow:session_grant",
+                "memory_read:deny:policy",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn ai_query_answer_is_streamed() {
+        let provider = ScriptedProvider::new()
+            .respond(
+                PromptKind::ActionPlan,
+                r#"{"actions":[{"intent":"ai_query","parameters":{"query":"what is the capital of France"}}]}"#,
+            )
+            .respond(PromptKind::Keywords, r#"["capital", "france"]"#)
+            .respond(PromptKind::Answer, "Paris is the capital of France.");
+        let harness = Harness::new(provider).await;
+
+        let reply = harness.send("what is the capital of France").await.unwrap();
+
+        let tokens = harness.host.streamed_tokens();
+        assert!(tokens.len() > 1);
+        assert_eq!(tokens.concat(), reply);
+        assert_eq!(reply, "Paris is the capital of France.");
+    }
+
+    #[tokio::test]
+    async fn function_calls_become_plan_steps() {
+        let provider = ScriptedProvider::new()
+            .respond(
+                PromptKind::ToolPlan,
+                r#"[
+                    {"name":"save_memory","arguments":{"content":"Dentist is Dr. Lee","tags":["health"]}},
+                    {"name":"set_reminder","arguments":{"content":"book dentist","trigger_at":4102444800}}
+                ]"#,
+            )
+            .respond(PromptKind::ReminderNormalization, "{}");
+        let harness = Harness::new(provider).await;
+
+        let reply = harness.send("remember my dentist is Dr. Lee and remind me to book").await.unwrap();
+
+        assert!(reply.starts_with("Plan completed:"));
+        assert_eq!(harness.provider.call_count(PromptKind::ActionPlan), 0);
+        assert_eq!(harness.memory_contents(), vec!["Dentist is Dr. Lee".to_string()]);
+        assert_eq!(harness.pending_reminders()[0].trigger_at, 4102444800);
+    }
+
+    #[tokio::test]
+    async fn invalid_function_arguments_fall_back_to_json_planner() {
+        let provider = ScriptedProvider::new()
+            .respond(PromptKind::ToolPlan, r#"[{"name":"save_memory","arguments":{"content":42}}]"#)
+            .respond(
+                PromptKind::ActionPlan,
+                r#"{"actions":[{"intent":"save_memory","parameters":{"content":"Gym at 6"}}]}"#,
+            );
+        let harness = Harness::new(provider).await;
+
+        harness.send("remember gym at 6").await.unwrap();
+
+        assert_eq!(harness.provider.call_count(PromptKind::ActionPlan), 1);
+        assert_eq!(harness.memory_contents(), vec!["Gym at 6".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn planner_outage_uses_rule_based_fallback() {
+        let provider = ScriptedProvider::new().fail(PromptKind::ActionPlan, "connection refused");
+        let harness = Harness::new(provider).await;
+
+        let reply = harness.send("remember that the wifi password is hunter2").await.unwrap();
+
+        assert_eq!(harness.provider.call_count(PromptKind::ActionPlan), 3);
+        assert_eq!(reply, "Got it, I'll remember that.");
+        assert_eq!(harness.memory_contents().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn destructive_step_pauses_until_confirmed() {
+        let provider = ScriptedProvider::new().respond(
+            PromptKind::ActionPlan,
+            r#"{"actions":[
+                {"intent":"delete_memory","parameters":{"query":"dentist"}},
+                {"intent":"save_memory","parameters":{"content":"Dentist is Dr. Park"}}
+            ]}"#,
+        );
+        let harness = Harness::new(provider).await;
+        crate::save_memory(&harness.memory_store, &harness.user_id, "Dentist is Dr. Lee").unwrap();
+
+        let reply = harness.send("forget my old dentist and remember Dr. Park").await.unwrap();
+
+        assert!(reply.contains("needs your confirmation"));
+        assert_eq!(harness.memory_contents(), vec!["Dentist is Dr. Lee".to_string()]);
+        let requests = harness.host.event_payloads("plan_confirmation_required");
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0]["intent"], "delete_memory");
+        let plan_id = requests[0]["plan_id"].as_str().unwrap().to_string();
+
+        let progress = harness.confirm(&plan_id, true).await.unwrap();
+
+        assert_eq!(progress.status, "completed");
+        assert_eq!(harness.memory_contents(), vec!["Dentist is Dr. Park".to_string()]);
+        assert!(harness.confirm(&plan_id, true).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn cancelled_confirmation_drops_the_rest_of_the_plan() {
+        let provider = ScriptedProvider::new().respond(
+            PromptKind::ActionPlan,
+            r#"{"actions":[
+                {"intent":"save_memory","parameters":{"content":"Gym moved to 7"}},
+                {"intent":"delete_memory","parameters":{"query":"gym"}}
+            ]}"#,
+        );
+        let harness = Harness::new(provider).await;
+
+        harness.send("remember gym moved to 7, then forget it").await.unwrap();
+        let requests = harness.host.event_payloads("plan_confirmation_required");
+        assert_eq!(requests[0]["step_index"], 1);
+        assert_eq!(requests[0]["completed"][0], "Got it, I'll remember that.");
+        let plan_id = requests[0]["plan_id"].as_str().unwrap();
+
+        let progress = harness.confirm(plan_id, false).await.unwrap();
+
+        assert_eq!(progress.status, "cancelled");
+        assert_eq!(harness.memory_contents(), vec!["Gym moved to 7".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn resume_error_marks_the_plan_failed() {
+        let provider = ScriptedProvider::new().respond(
+            PromptKind::ActionPlan,
+            r#"{"actions":[{"intent":"delete_memory","parameters":{"query":"dentist"}}]}"#,
+        );
+        let harness = Harness::new(provider).await;
+        crate::save_memory(&harness.memory_store, &harness.user_id, "Dentist is Dr. Lee").unwrap();
+        harness.send("forget my dentist").await.unwrap();
+        let requests = harness.host.event_payloads("plan_confirmation_required");
+        let plan_id = requests[0]["plan_id"].as_str().unwrap();
+        {
+            let conn = harness.memory_store.conn.lock().unwrap();
+            crate::pending_plan_store::advance_pending_plan(&conn, plan_id, 5, &[]).unwrap();
+        }
+
+        assert!(harness.confirm(plan_id, true).await.is_err());
+
+        let conn = harness.memory_store.conn.lock().unwrap();
+        let plan = crate::pending_plan_store::get_pending_plan(&conn, &harness.user_id, plan_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(plan.status, crate::pending_plan_store::status::FAILED);
+    }
+
+    #[tokio::test]
+    async fn later_steps_read_earlier_step_results() {
+        let provider = ScriptedProvider::new()
+            .respond(
+                PromptKind::ActionPlan,
+                r#"{"actions":[
+                    {"intent":"search_memory","parameters":{"query":"physics exam"}},
+                    {"intent":"set_reminder","parameters":{
+                        "content":"Study for {{steps[0].result.content}}",
+                        "trigger_at":"{{steps[0].result.trigger_at - 86400}}"
+                    }},
+                    {"intent":"set_reminder","parameters":{"content":"x","trigger_at":"{{steps[1].result.missing}}"}}
+                ]}"#,
+            )
+            .respond(PromptKind::ReminderNormalization, "{}");
+        let harness = Harness::new(provider).await;
+        crate::save_memory(&harness.memory_store, &harness.user_id, "Physics exam on 2030-06-14 at 10am").unwrap();
+
+        let reply = harness.send("remind me the day before my physics exam").await.unwrap();
+
+        let exam = chrono::NaiveDate::from_ymd_opt(2030, 6, 14).unwrap().and_hms_opt(10, 0, 0).unwrap();
+        let exam_ts = chrono::TimeZone::from_local_datetime(&chrono::Local, &exam).unwrap().timestamp();
+        let reminders = harness.pending_reminders();
+        assert_eq!(reminders.len(), 1);
+        assert_eq!(reminders[0].content, "Study for Physics exam on 2030-06-14 at 10am");
+        assert_eq!(reminders[0].trigger_at, exam_ts - 86400);
+        assert!(reply.contains("failed on set_reminder: Could not resolve {{steps[1].result.missing}} in step 3"));
+    }
+
+    #[tokio::test]
+    async fn tool_outcomes_are_stored_in_command_history() {
+        let provider = ScriptedProvider::new().respond(
+            PromptKind::ActionPlan,
+            r#"{"actions":[{"intent":"search_memory","parameters":{"query":"passport number"}}]}"#,
+        );
+        let harness = Harness::new(provider).await;
+
+        harness.send("what is my passport number").await.unwrap();
+
+        let conn = harness.memory_store.conn.lock().unwrap();
+        let records = crate::command_history_service::fetch_recent_history(&conn, &harness.user_id, 1).unwrap();
+        assert_eq!(records[0].status, "no_results");
+        let outcome: serde_json::Value = serde_json::from_str(records[0].metadata.as_deref().unwrap()).unwrap();
+        assert_eq!(outcome["data"]["query"], "passport number");
+        assert_eq!(outcome["follow_ups"][0]["intent"], "search_web");
+    }
+
+    #[tokio::test]
+    async fn undo_reverts_the_most_recent_plan_first() {
+        let provider = ScriptedProvider::new()
+            .respond(
+                PromptKind::ActionPlan,
+                r#"{"actions":[
+                    {"intent":"update_memory","parameters":{"query":"dentist","new_content":"Dentist is Dr. Park"}},
+                    {"intent":"save_memory","parameters":{"content":"Gym at 7"}}
+                ]}"#,
+            )
+            .respond(PromptKind::ActionPlan, r#"{"actions":[{"intent":"delete_memory","parameters":{"query":"gym"}}]}"#)
+            .respond(PromptKind::ActionPlan, r#"{"actions":[{"intent":"undo","parameters":{}}]}"#);
+        let harness = Harness::new(provider).await;
+        crate::save_memory(&harness.memory_store, &harness.user_id, "Dentist is Dr. Lee").unwrap();
+
+        harness.send("my dentist is Dr. Park now, and remember gym at 7").await.unwrap();
+        harness.send("forget the gym").await.unwrap();
+        let plan_id = harness.host.event_payloads("plan_confirmation_required")[0]["plan_id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        harness.confirm(&plan_id, true).await.unwrap();
+        assert_eq!(harness.memory_contents(), vec!["Dentist is Dr. Park".to_string()]);
+
+        let reply = harness.send("undo that").await.unwrap();
+
+        assert_eq!(reply, "Undone: restored \"Gym at 7\".");
+        let mut contents = harness.memory_contents();
+        contents.sort();
+        assert_eq!(contents, vec!["Dentist is Dr. Park".to_string(), "Gym at 7".to_string()]);
+
+        let conn = harness.memory_store.conn.lock().unwrap();
+        let report = crate::action_journal::undo_last_action(&conn, &harness.user_id).unwrap().unwrap();
+        assert_eq!(report.undone, vec!["removed \"Gym at 7\"", "changed a memory back to \"Dentist is Dr. Lee\""]);
+        assert!(crate::action_journal::undo_last_action(&conn, &harness.user_id).unwrap().is_none());
+        drop(conn);
+        assert_eq!(harness.memory_contents(), vec!["Dentist is Dr. Lee".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn undoing_a_reminder_cancels_its_background_task() {
+        let provider = ScriptedProvider::new()
+            .respond(
+                PromptKind::ActionPlan,
+                r#"{"actions":[{"intent":"set_reminder","parameters":{"content":"stretch","trigger_at":4102444800}}]}"#,
+            )
+            .respond(PromptKind::ActionPlan, r#"{"actions":[{"intent":"undo","parameters":{}}]}"#);
+        let harness = Harness::new(provider).await;
+
+        harness.send("remind me to stretch").await.unwrap();
+        assert_eq!(harness.pending_reminders().len(), 1);
+        let reply = harness.send("undo that").await.unwrap();
+
+        assert_eq!(reply, "Undone: cancelled the reminder \"stretch\".");
+        assert!(harness.pending_reminders().is_empty());
+        let conn = harness.memory_store.conn.lock().unwrap();
+        let pending_tasks: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM background_tasks WHERE task_type = 'reminder' AND status = 'pending'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(pending_tasks, 0);
+    }
+
+    #[tokio::test]
+    async fn ambiguous_update_asks_which_memory_and_takes_a_typed_pick() {
+        let provider = ScriptedProvider::new().respond(
+            PromptKind::ActionPlan,
+            r#"{"actions":[{"intent":"update_memory","parameters":{"query":"dentist","new_content":"Dentist is Dr. Park"}}]}"#,
+        );
+        let harness = Harness::new(provider).await;
+        crate::save_memory(&harness.memory_store, &harness.user_id, "Dentist appointment on Monday").unwrap();
+        crate::save_memory(&harness.memory_store, &harness.user_id, "Dentist is Dr. Lee").unwrap();
+
+        let reply = harness.send("my dentist is Dr. Park now").await.unwrap();
+
+        assert!(reply.starts_with("I found several memories matching \"dentist\""));
+        let requests = harness.host.event_payloads("plan_confirmation_required");
+        let choices = requests[0]["choices"].as_array().unwrap();
+        assert_eq!(choices.len(), 2);
+        let lee = choices.iter().position(|choice| choice["label"] == "Dentist is Dr. Lee").unwrap();
+
+        let reply = harness.send(&(lee + 1).to_string()).await.unwrap();
+
+        assert_eq!(reply, "Updated memory: Dentist is Dr. Park");
+        let mut contents = harness.memory_contents();
+        contents.sort();
+        assert_eq!(contents, vec!["Dentist appointment on Monday".to_string(), "Dentist is Dr. Park".to_string()]);
+        assert_eq!(harness.provider.call_count(PromptKind::ActionPlan), 1);
+    }
+
+    #[tokio::test]
+    async fn single_intent_fallback_keeps_the_question_for_a_typed_pick() {
+        let provider = ScriptedProvider::new().respond(PromptKind::ActionPlan, "not a plan").respond(
+            PromptKind::Intent,
+            r#"{"intent":"update_memory","parameters":{"query":"dentist","new_content":"Dentist is Dr. Park"},"confidence":0.9}"#,
+        );
+        let harness = Harness::new(provider).await;
+        crate::save_memory(&harness.memory_store, &harness.user_id, "Dentist appointment on Monday").unwrap();
+        crate::save_memory(&harness.memory_store, &harness.user_id, "Dentist is Dr. Lee").unwrap();
+
+        let reply = harness.send("my dentist is Dr. Park now").await.unwrap();
+
+        assert!(reply.starts_with("I found several memories matching \"dentist\""));
+        let reply = harness.send("Dentist is Dr. Lee").await.unwrap();
+
+        assert_eq!(reply, "Updated memory: Dentist is Dr. Park");
+        assert_eq!(harness.provider.call_count(PromptKind::Intent), 1);
+    }
+
+    #[tokio::test]
+    async fn confirmed_forget_with_several_matches_deletes_the_picked_memory() {
+        let provider = ScriptedProvider::new().respond(
+            PromptKind::ActionPlan,
+            r#"{"actions":[{"intent":"delete_memory","parameters":{"query":"dentist"}}]}"#,
+        );
+        let harness = Harness::new(provider).await;
+        crate::save_memory(&harness.memory_store, &harness.user_id, "Dentist appointment on Monday").unwrap();
+        crate::save_memory(&harness.memory_store, &harness.user_id, "Dentist is Dr. Lee").unwrap();
+
+        harness.send("forget my dentist").await.unwrap();
+        let plan_id = harness.host.event_payloads("plan_confirmation_required")[0]["plan_id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let progress = harness.confirm(&plan_id, true).await.unwrap();
+
+        assert_eq!(progress.status, "choice_required");
+        assert!(harness.confirm(&plan_id, true).await.is_err());
+        let choices = &progress.confirmation.unwrap().choices;
+        let monday = choices.iter().position(|choice| choice.label.contains("Monday")).unwrap();
+
+        let progress = harness.choose(&plan_id, monday).await.unwrap();
+
+        assert_eq!(progress.status, "completed");
+        assert_eq!(harness.memory_contents(), vec!["Dentist is Dr. Lee".to_string()]);
+    }
 }
 
 fn disambiguate_intent(message: &str, structured_intent: &mut StructuredIntent) {
@@ -2,6 +2,8 @@ use serde::Deserialize;
 use serde_json::Value;
 
 use super::action_plan::{ActionPlan, ActionStep};
+use super::step_references;
+use crate::ai::tool_registry;
 
 const MAX_ACTIONS: usize = 5;
 
@@ -37,7 +39,8 @@ pub fn parse_and_validate_action_plan(raw: &str) -> Result<ActionPlan, String> {
     Err(format!("Invalid action plan payload: {}", raw))
 }
 
-fn validate_plan(plan: &mut ActionPlan) -> Result<(), String> {
+/// Normalizes intent names and checks every step against the tool registry.
+pub fn validate_plan(plan: &mut ActionPlan) -> Result<(), String> {
     if plan.actions.is_empty() {
         return Err("Action plan has no actions".to_string());
     }
@@ -46,97 +49,21 @@ fn validate_plan(plan: &mut ActionPlan) -> Result<(), String> {
         return Err(format!("Action plan exceeds max actions ({})", MAX_ACTIONS));
     }
 
-    for step in &mut plan.actions {
+    for (idx, step) in plan.actions.iter_mut().enumerate() {
         step.intent = step.intent.trim().to_string();
         if step.intent.is_empty() {
             return Err("Action step contains empty intent".to_string());
         }
 
-        if !is_supported_intent(&step.intent) {
-            return Err(format!("Unknown intent in action plan: {}", step.intent));
-        }
-
-        if !step.parameters.is_object() {
-            return Err(format!("Parameters must be an object for intent {}", step.intent));
-        }
-
-        validate_parameters(&step.intent, &step.parameters)?;
-    }
-
-    Ok(())
-}
-
-fn validate_parameters(intent: &str, params: &Value) -> Result<(), String> {
-    let has = |k: &str| params.get(k).and_then(Value::as_str).map(|v| !v.trim().is_empty()).unwrap_or(false);
-
-    match intent {
-        "set_reminder" => {
-            if !has("content") && !has("task") && !has("message") {
-                return Err("set_reminder requires content/task/message".to_string());
-            }
-        }
-        "save_memory" => {
-            if !has("content") && !has("memory") && !has("text") {
-                return Err("save_memory requires content/memory/text".to_string());
-            }
-        }
-        "search_memory" | "delete_memory" | "forget_memory" => {
-            if !has("query") && !has("keyword") && !has("target") {
-                return Err(format!("{} requires query/keyword/target", intent));
-            }
-        }
-        "update_memory" => {
-            if !has("query") && !has("keyword") && !has("target") {
-                return Err("update_memory requires query/keyword/target".to_string());
-            }
-            if !has("new_content") && !has("new_time") && !has("content") {
-                return Err("update_memory requires new_content/new_time/content".to_string());
-            }
-        }
-        "open_app" => {
-            if !has("target") && !has("app") && !has("app_name") {
-                return Err("open_app requires target/app/app_name".to_string());
-            }
-        }
-        "search_web" => {
-            if !has("query") && !has("url") {
-                return Err("search_web requires query/url".to_string());
-            }
-        }
-        "plugin_action" => {
-            if !has("plugin_id") || !has("command") {
-                return Err("plugin_action requires plugin_id and command".to_string());
-            }
-        }
-        "ai_query" => {
-            if !has("query") {
-                return Err("ai_query requires query".to_string());
-            }
-        }
-        "unknown" => {}
-        _ => {}
+        let tool = tool_registry::find_tool(&step.intent)
+            .ok_or_else(|| format!("Unknown intent in action plan: {}", step.intent))?;
+        tool_registry::validate_arguments(tool, &step.parameters)?;
+        step_references::check_references(&step.parameters, idx)?;
     }
 
     Ok(())
 }
 
-fn is_supported_intent(intent: &str) -> bool {
-    matches!(
-        intent,
-        "set_reminder"
-            | "save_memory"
-            | "update_memory"
-            | "delete_memory"
-            | "forget_memory"
-            | "search_memory"
-            | "open_app"
-            | "search_web"
-            | "plugin_action"
-            | "ai_query"
-            | "unknown"
-    )
-}
-
 fn extract_json_payload(raw: &str) -> String {
     let trimmed = raw.trim();
     let without_fence = trimmed
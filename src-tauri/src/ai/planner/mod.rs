@@ -1,3 +1,4 @@
 pub mod action_plan;
 pub mod action_plan_parser;
 pub mod plan_executor;
+pub mod step_references;
@@ -1,6 +1,13 @@
-use super::action_plan::ActionPlan;
-use crate::ai::intent_router;
+use super::action_plan::{ActionPlan, ActionStep};
+use super::step_references;
+use crate::ai::app_host::AppHost;
 use crate::ai::orchestrator::StructuredIntent;
+use crate::ai::tool_outcome::{FollowUp, OutcomeStatus, ToolOutcome};
+use crate::ai::{intent_router, tool_registry};
+use crate::pending_plan_store;
+use crate::permission_policy_store::{self, Decision, GrantScope};
+use serde::Serialize;
+use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::{Mutex, OnceLock};
 
@@ -14,11 +21,54 @@ fn plan_state() -> &'static Mutex<PlanExecutionState> {
     STATE.get_or_init(|| Mutex::new(PlanExecutionState::default()))
 }
 
+/// A paused step the user has to approve or pick a target for, sent to the
+/// frontend as `plan_confirmation_required` and returned from `confirm_plan_step`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfirmationRequest {
+    pub plan_id: String,
+    /// Zero-based index of the paused step.
+    pub step_index: usize,
+    pub total_steps: usize,
+    pub intent: String,
+    pub parameters: Value,
+    pub summary: String,
+    /// Responses of the steps that already ran.
+    pub completed: Vec<String>,
+    /// Set when the step matched several memories; resuming needs the index
+    /// of the one the user picked.
+    pub choices: Vec<FollowUp>,
+    /// Capability the user's policy asks about before this step runs.
+    pub permission: Option<String>,
+}
+
+/// Where a plan run stopped.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanProgress {
+    /// "completed", "failed", "cancelled", "confirmation_required",
+    /// "choice_required" or "permission_required"
+    pub status: String,
+    pub message: String,
+    pub confirmation: Option<ConfirmationRequest>,
+    /// Structured results of the steps that ran, for rendering result cards.
+    pub outcomes: Vec<ToolOutcome>,
+}
+
+impl PlanProgress {
+    fn finished(status: &str, message: String, outcomes: Vec<ToolOutcome>) -> Self {
+        Self {
+            status: status.to_string(),
+            message,
+            confirmation: None,
+            outcomes,
+        }
+    }
+}
+
 pub async fn execute_action_plan(
     user_message: &str,
     plan: ActionPlan,
     user_id: &str,
-    app_handle: &tauri::AppHandle,
+    app_handle: &dyn AppHost,
     registry: &crate::AppRegistry,
     memory_store: &crate::MemoryStore,
     plugin_registry: &crate::plugin_registry::PluginRegistry,
@@ -29,7 +79,6 @@ pub async fn execute_action_plan(
         return Err("Planner returned an empty action plan".to_string());
     }
 
-    let plan_started = std::time::Instant::now();
     let signature = plan_signature(&plan);
     let now_ts = current_timestamp();
     {
@@ -44,35 +93,128 @@ pub async fn execute_action_plan(
             .insert(user_id.to_string(), (signature, now_ts));
     }
 
-    let mut responses = Vec::new();
+    let progress = run_plan_steps(
+        user_message,
+        &plan,
+        PlanCursor {
+            plan_id: uuid::Uuid::new_v4().to_string(),
+            persisted: false,
+            start: 0,
+            approved_step: None,
+            outputs: Vec::new(),
+        },
+        user_id,
+        app_handle,
+        registry,
+        memory_store,
+        plugin_registry,
+        event_bus,
+        permissions,
+    )
+    .await?;
 
-    for (idx, step) in plan.actions.iter().enumerate() {
-        let started = std::time::Instant::now();
-        if step.requires_confirmation {
-            let summary = if responses.is_empty() {
-                ""
+    Ok(progress.message)
+}
+
+/// Resumes a plan paused by a confirmation request. Approving runs the paused
+/// step and continues until the plan ends or the next step needs confirmation.
+/// A step paused on ambiguous matches runs with the picked `choice` instead.
+/// For a step paused by an ask policy, `grant` says how long the answer lasts.
+pub async fn resume_action_plan(
+    plan_id: &str,
+    approve: bool,
+    choice: Option<usize>,
+    grant: Option<GrantScope>,
+    user_id: &str,
+    app_handle: &dyn AppHost,
+    registry: &crate::AppRegistry,
+    memory_store: &crate::MemoryStore,
+    plugin_registry: &crate::plugin_registry::PluginRegistry,
+    event_bus: &crate::EventBus,
+    permissions: &crate::PermissionManager,
+) -> Result<PlanProgress, String> {
+    let pending = {
+        let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let pending = pending_plan_store::get_pending_plan(&conn, user_id, plan_id)?
+            .ok_or_else(|| "This plan no longer exists.".to_string())?;
+        match pending.status.as_str() {
+            pending_plan_store::status::PENDING => {}
+            pending_plan_store::status::EXPIRED => {
+                return Err("This plan expired before it was confirmed.".to_string());
+            }
+            other => return Err(format!("This plan is already {}.", other)),
+        }
+        if approve && !pending.choices.is_empty() && choice.and_then(|idx| pending.choices.get(idx)).is_none() {
+            return Err(format!("Pick one of the {} listed options to continue.", pending.choices.len()));
+        }
+        // Claiming the plan keeps a double click from running the step twice.
+        if !pending_plan_store::claim_pending_plan(&conn, plan_id)? {
+            return Err("This plan is already being resumed.".to_string());
+        }
+        pending
+    };
+
+    // From here on the plan is `running`; an error must not leave it stuck there.
+    let result: Result<PlanProgress, String> = async {
+        let step = pending
+            .plan
+            .actions
+            .get(pending.next_step)
+            .ok_or_else(|| "Stored plan has no step to confirm".to_string())?;
+
+        if let Some(capability) = tool_registry::find_tool(&step.intent).and_then(|tool| tool.capability) {
+            let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+            let (decision, _) = permission_policy_store::evaluate(&conn, permissions, user_id, capability)?;
+            if decision == Decision::Ask {
+                permission_policy_store::answer_prompt(
+                    &conn,
+                    permissions,
+                    user_id,
+                    capability,
+                    approve,
+                    grant.unwrap_or(GrantScope::Once),
+                    Some(&step.intent),
+                )?;
+            }
+        }
+
+        if !approve {
+            {
+                let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+                pending_plan_store::set_plan_status(&conn, plan_id, pending_plan_store::status::CANCELLED)?;
+            }
+            emit_plan_telemetry(event_bus, 0, "cancelled", Some(&step.intent));
+            let message = if pending.outputs.is_empty() {
+                format!("Cancelled. I didn't run {}.", describe_step(step))
             } else {
-                &format!(" Completed so far: {}", responses.join(" | "))
+                format!(
+                    "Cancelled. I didn't run {}. Completed before that: {}",
+                    describe_step(step),
+                    join_messages(&pending.outputs)
+                )
             };
-            let message = format!(
-                "Step {} requires confirmation before continuing: {}.{}",
-                idx + 1,
-                step.intent,
-                summary
-            );
-            emit_plan_telemetry(event_bus, plan_started.elapsed().as_millis(), "confirmation_required", Some(&step.intent));
-            return Ok(message);
+            return Ok(PlanProgress::finished("cancelled", message, pending.outputs));
         }
 
-        let structured = StructuredIntent {
-            intent: step.intent.clone(),
-            parameters: step.parameters.clone(),
-            confidence: 1.0,
-        };
+        let mut plan = pending.plan.clone();
+        if let Some(picked) = choice.and_then(|idx| pending.choices.get(idx)) {
+            plan.actions[pending.next_step] = ActionStep {
+                intent: picked.intent.clone(),
+                parameters: picked.parameters.clone(),
+                requires_confirmation: step.requires_confirmation,
+            };
+        }
 
-        match intent_router::route_intent(
-            user_message,
-            structured,
+        run_plan_steps(
+            &pending.user_message,
+            &plan,
+            PlanCursor {
+                plan_id: pending.id.clone(),
+                persisted: true,
+                start: pending.next_step,
+                approved_step: Some(pending.next_step),
+                outputs: pending.outputs.clone(),
+            },
             user_id,
             app_handle,
             registry,
@@ -82,57 +224,410 @@ pub async fn execute_action_plan(
             permissions,
         )
         .await
-        {
-            Ok(response) => {
+    }
+    .await;
+
+    if result.is_err() {
+        if let Ok(conn) = memory_store.conn.lock() {
+            let _ = pending_plan_store::set_plan_status(&conn, plan_id, pending_plan_store::status::FAILED);
+        }
+    }
+    result
+}
+
+/// How a typed chat message answers a pending "which memory?" question.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChoiceReply {
+    Pick(usize),
+    Cancel,
+}
+
+/// Reads "2", "#2", "the second one", "last" or a listed memory's exact text
+/// as a pick, and "cancel"/"never mind" as a cancel. `None` when the message
+/// is something else and should be planned as a new request.
+pub fn parse_choice_reply(message: &str, choices: &[FollowUp]) -> Option<ChoiceReply> {
+    let reply = message
+        .trim()
+        .trim_end_matches(['.', '!', '?'])
+        .to_lowercase();
+    if reply.is_empty() || choices.is_empty() {
+        return None;
+    }
+    if matches!(reply.as_str(), "cancel" | "none" | "neither" | "none of them" | "never mind" | "nevermind") {
+        return Some(ChoiceReply::Cancel);
+    }
+
+    const ORDINALS: [(&str, &str); 4] = [("first", "1st"), ("second", "2nd"), ("third", "3rd"), ("fourth", "4th")];
+    let words = reply
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty() && !matches!(*word, "the" | "one" | "number" | "option" | "memory"))
+        .collect::<Vec<_>>();
+    if let [word] = words.as_slice() {
+        let position = if let Ok(number) = word.parse::<usize>() {
+            number.checked_sub(1)
+        } else if *word == "last" {
+            Some(choices.len() - 1)
+        } else {
+            ORDINALS
+                .iter()
+                .position(|(long, short)| word == long || word == short)
+        };
+        if let Some(idx) = position.filter(|idx| *idx < choices.len()) {
+            return Some(ChoiceReply::Pick(idx));
+        }
+    }
+
+    choices
+        .iter()
+        .position(|choice| choice.label.trim().trim_end_matches(['.', '!', '?']).to_lowercase() == reply)
+        .map(ChoiceReply::Pick)
+}
+
+/// Position of a run within a plan. `plan_id` keys the undo journal and
+/// becomes the pending plan's id once the plan is persisted for confirmation.
+struct PlanCursor {
+    plan_id: String,
+    persisted: bool,
+    start: usize,
+    approved_step: Option<usize>,
+    outputs: Vec<ToolOutcome>,
+}
+
+async fn run_plan_steps(
+    user_message: &str,
+    plan: &ActionPlan,
+    cursor: PlanCursor,
+    user_id: &str,
+    app_handle: &dyn AppHost,
+    registry: &crate::AppRegistry,
+    memory_store: &crate::MemoryStore,
+    plugin_registry: &crate::plugin_registry::PluginRegistry,
+    event_bus: &crate::EventBus,
+    permissions: &crate::PermissionManager,
+) -> Result<PlanProgress, String> {
+    let plan_started = std::time::Instant::now();
+    let PlanCursor {
+        plan_id,
+        persisted,
+        start,
+        approved_step,
+        mut outputs,
+    } = cursor;
+
+    // Persists the plan so `resume_action_plan` can pick it up at `idx`.
+    let persist_pause = |idx: usize, outputs: &[ToolOutcome]| -> Result<(), String> {
+        let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+        if persisted {
+            pending_plan_store::advance_pending_plan(&conn, &plan_id, idx, outputs)
+        } else {
+            pending_plan_store::create_pending_plan(&conn, &plan_id, user_id, user_message, plan, idx, outputs)
+        }
+    };
+
+    for (idx, planned) in plan.actions.iter().enumerate().skip(start) {
+        let started = std::time::Instant::now();
+
+        // Placeholders like {{steps[0].result.trigger_at}} read earlier outputs.
+        let step = match step_references::resolve_parameters(&planned.parameters, idx, &outputs) {
+            Ok(parameters) => ActionStep {
+                parameters,
+                ..planned.clone()
+            },
+            Err(err) => {
+                if persisted {
+                    if let Ok(conn) = memory_store.conn.lock() {
+                        let _ = pending_plan_store::set_plan_status(&conn, &plan_id, pending_plan_store::status::FAILED);
+                    }
+                }
+                emit_plan_telemetry(event_bus, plan_started.elapsed().as_millis(), "failed", Some(&planned.intent));
+                return Ok(failed_progress(idx, &planned.intent, &err, &outputs));
+            }
+        };
+
+        // A deny policy fails the step; an ask policy pauses it like a confirmation.
+        let mut denied = None;
+        let step_capability = tool_registry::find_tool(&step.intent).and_then(|tool| tool.capability);
+        if let Some(capability) = step_capability {
+            let (decision, decided_by) = {
+                let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+                let (decision, decided_by) = permission_policy_store::evaluate(&conn, permissions, user_id, capability)?;
+                // An approved step was recorded when it paused; its answer by `resume_action_plan`.
+                if approved_step != Some(idx) || decision == Decision::Deny {
+                    permission_policy_store::record_decision(
+                        &conn,
+                        user_id,
+                        capability,
+                        decision,
+                        decided_by,
+                        Some(&step.intent),
+                    )?;
+                }
+                (decision, decided_by)
+            };
+
+            match decision {
+                Decision::Deny if decided_by == permission_policy_store::source::SETTINGS => {
+                    denied = Some(format!("Permission denied for capability: {:?}", capability));
+                }
+                Decision::Deny => {
+                    denied = Some(format!("You've blocked Noddy from using {}.", capability.as_str()));
+                }
+                Decision::Ask if approved_step != Some(idx) => {
+                    persist_pause(idx, &outputs)?;
+                    let mut request = pause_request(&plan_id, plan, idx, &step, &outputs, Vec::new());
+                    request.permission = Some(capability.as_str().to_string());
+                    app_handle.emit_event(
+                        "plan_confirmation_required",
+                        serde_json::to_value(&request).unwrap_or(Value::Null),
+                    );
+                    emit_plan_telemetry(event_bus, plan_started.elapsed().as_millis(), "permission_required", Some(&step.intent));
+
+                    let message = format!(
+                        "Step {} needs your permission to use {}: {}.{}",
+                        idx + 1,
+                        capability.as_str(),
+                        request.summary,
+                        completed_so_far(&outputs)
+                    );
+                    return Ok(PlanProgress {
+                        status: "permission_required".to_string(),
+                        message,
+                        confirmation: Some(request),
+                        outcomes: outputs,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        if denied.is_none() && needs_confirmation(&step) && approved_step != Some(idx) {
+            persist_pause(idx, &outputs)?;
+            let request = pause_request(&plan_id, plan, idx, &step, &outputs, Vec::new());
+            app_handle.emit_event(
+                "plan_confirmation_required",
+                serde_json::to_value(&request).unwrap_or(Value::Null),
+            );
+            emit_plan_telemetry(event_bus, plan_started.elapsed().as_millis(), "confirmation_required", Some(&step.intent));
+
+            let message = format!(
+                "Step {} needs your confirmation before I continue: {}.{}",
+                idx + 1,
+                request.summary,
+                completed_so_far(&outputs)
+            );
+            return Ok(PlanProgress {
+                status: "confirmation_required".to_string(),
+                message,
+                confirmation: Some(request),
+                outcomes: outputs,
+            });
+        }
+
+        let structured = StructuredIntent {
+            intent: step.intent.clone(),
+            parameters: step.parameters.clone(),
+            confidence: 1.0,
+        };
+
+        let was_denied = denied.is_some();
+        let result = match denied {
+            Some(reason) => Err(reason),
+            None => {
+                intent_router::route_intent(
+                    user_message,
+                    structured,
+                    user_id,
+                    app_handle,
+                    registry,
+                    memory_store,
+                    plugin_registry,
+                    event_bus,
+                    permissions,
+                )
+                .await
+            }
+        };
+
+        if let Some(action) = step_capability.and_then(crate::audit_log::audit_action) {
+            if let Ok(conn) = memory_store.conn.lock() {
+                let target = crate::audit_log::target_from_parameters(&step.parameters);
+                let written = match &result {
+                    Err(reason) if was_denied => crate::audit_log::append(
+                        &conn,
+                        Some(user_id),
+                        action,
+                        target.as_deref(),
+                        crate::audit_log::outcome::DENIED,
+                        Some(reason),
+                    ),
+                    _ => crate::audit_log::append_result(&conn, Some(user_id), action, target.as_deref(), &result),
+                };
+                if let Err(err) = written {
+                    eprintln!("⚠️  Failed to audit {}: {}", step.intent, err);
+                }
+            }
+        }
+
+        match result {
+            Ok(outcome) => {
                 let duration_ms = started.elapsed().as_millis();
                 if let Ok(conn) = memory_store.conn.lock() {
-                    let _ = crate::command_history_service::record_command_execution(
+                    let _ = crate::command_history_service::record_tool_outcome(
                         &conn,
                         user_id,
                         &step.intent,
                         &step.parameters.to_string(),
-                        true,
+                        Ok(&outcome),
                         duration_ms,
                     );
+                    if let Err(err) =
+                        crate::action_journal::record_outcome(&conn, user_id, &plan_id, &step.intent, &outcome)
+                    {
+                        eprintln!("⚠️  Failed to journal {} for undo: {}", step.intent, err);
+                    }
                 }
-                responses.push(response)
+
+                // Several memories matched: wait for the user to pick one.
+                if outcome.status == OutcomeStatus::NeedsInput && !outcome.follow_ups.is_empty() {
+                    persist_pause(idx, &outputs)?;
+                    {
+                        let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+                        pending_plan_store::set_plan_choices(&conn, &plan_id, &outcome.follow_ups)?;
+                    }
+                    let request = pause_request(&plan_id, plan, idx, &step, &outputs, outcome.follow_ups.clone());
+                    app_handle.emit_event(
+                        "plan_confirmation_required",
+                        serde_json::to_value(&request).unwrap_or(Value::Null),
+                    );
+                    emit_plan_telemetry(event_bus, plan_started.elapsed().as_millis(), "choice_required", Some(&step.intent));
+
+                    let message = format!("{}{}", outcome.message, completed_so_far(&outputs));
+                    return Ok(PlanProgress {
+                        status: "choice_required".to_string(),
+                        message,
+                        confirmation: Some(request),
+                        outcomes: outputs,
+                    });
+                }
+                outputs.push(outcome)
             }
             Err(err) => {
                 let duration_ms = started.elapsed().as_millis();
                 if let Ok(conn) = memory_store.conn.lock() {
-                    let _ = crate::command_history_service::record_command_execution(
+                    let _ = crate::command_history_service::record_tool_outcome(
                         &conn,
                         user_id,
                         &step.intent,
                         &step.parameters.to_string(),
-                        false,
+                        Err(&err),
                         duration_ms,
                     );
+                    if persisted {
+                        let _ = pending_plan_store::set_plan_status(&conn, &plan_id, pending_plan_store::status::FAILED);
+                    }
                 }
-                let completed = if responses.is_empty() {
-                    "none".to_string()
-                } else {
-                    responses.join(" | ")
-                };
-                let message = format!(
-                    "Completed {} step(s), but failed on {}: {}.",
-                    idx + 1,
-                    step.intent,
-                    err
-                );
-                let friendly = format!("{} Completed steps: {}", message, completed);
                 emit_plan_telemetry(event_bus, plan_started.elapsed().as_millis(), "failed", Some(&step.intent));
-                return Ok(friendly);
+                return Ok(failed_progress(idx, &step.intent, &err, &outputs));
             }
         }
     }
 
+    if persisted {
+        let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+        pending_plan_store::set_plan_status(&conn, &plan_id, pending_plan_store::status::COMPLETED)?;
+    }
     emit_plan_telemetry(event_bus, plan_started.elapsed().as_millis(), "completed", None);
 
-    if responses.len() == 1 {
-        Ok(responses.remove(0))
+    let message = if outputs.len() == 1 {
+        outputs[0].message.clone()
+    } else {
+        format!("Plan completed: {}", join_messages(&outputs))
+    };
+    Ok(PlanProgress::finished("completed", message, outputs))
+}
+
+fn pause_request(
+    plan_id: &str,
+    plan: &ActionPlan,
+    idx: usize,
+    step: &ActionStep,
+    outputs: &[ToolOutcome],
+    choices: Vec<FollowUp>,
+) -> ConfirmationRequest {
+    ConfirmationRequest {
+        plan_id: plan_id.to_string(),
+        step_index: idx,
+        total_steps: plan.actions.len(),
+        intent: step.intent.clone(),
+        parameters: step.parameters.clone(),
+        summary: describe_step(step),
+        completed: outputs.iter().map(|output| output.message.clone()).collect(),
+        choices,
+        permission: None,
+    }
+}
+
+fn completed_so_far(outputs: &[ToolOutcome]) -> String {
+    if outputs.is_empty() {
+        String::new()
+    } else {
+        format!(" Completed so far: {}", join_messages(outputs))
+    }
+}
+
+fn failed_progress(idx: usize, intent: &str, err: &str, outputs: &[ToolOutcome]) -> PlanProgress {
+    let completed = if outputs.is_empty() {
+        "none".to_string()
+    } else {
+        join_messages(outputs)
+    };
+    let message = format!(
+        "Completed {} step(s), but failed on {}: {}.",
+        idx + 1,
+        intent,
+        err
+    );
+    PlanProgress::finished(
+        "failed",
+        format!("{} Completed steps: {}", message, completed),
+        outputs.to_vec(),
+    )
+}
+
+fn join_messages(outputs: &[ToolOutcome]) -> String {
+    outputs
+        .iter()
+        .map(|output| output.message.as_str())
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// Steps flagged by the planner, plus destructive tools that always ask.
+fn needs_confirmation(step: &ActionStep) -> bool {
+    step.requires_confirmation
+        || tool_registry::find_tool(&step.intent)
+            .map(|tool| tool.requires_confirmation)
+            .unwrap_or(false)
+}
+
+/// "delete_memory (query: dentist)"
+fn describe_step(step: &ActionStep) -> String {
+    let details = step
+        .parameters
+        .as_object()
+        .map(|object| {
+            object
+                .iter()
+                .filter_map(|(key, value)| value.as_str().map(|text| format!("{}: {}", key, text)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .unwrap_or_default();
+
+    if details.is_empty() {
+        step.intent.clone()
     } else {
-        Ok(format!("Plan completed: {}", responses.join(" | ")))
+        format!("{} ({})", step.intent, details)
     }
 }
 
@@ -163,3 +658,34 @@ fn emit_plan_telemetry(event_bus: &crate::EventBus, duration_ms: u128, status: &
         duration_ms,
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn choices() -> Vec<FollowUp> {
+        ["Dentist appointment on Monday", "Dentist is Dr. Lee"]
+            .iter()
+            .map(|label| FollowUp {
+                label: label.to_string(),
+                intent: "delete_memory".to_string(),
+                parameters: Value::Null,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn choice_replies_accept_numbers_ordinals_and_exact_labels() {
+        let choices = choices();
+        assert_eq!(parse_choice_reply("2", &choices), Some(ChoiceReply::Pick(1)));
+        assert_eq!(parse_choice_reply("#1", &choices), Some(ChoiceReply::Pick(0)));
+        assert_eq!(parse_choice_reply("The second one.", &choices), Some(ChoiceReply::Pick(1)));
+        assert_eq!(parse_choice_reply("last", &choices), Some(ChoiceReply::Pick(1)));
+        assert_eq!(parse_choice_reply("Dentist is Dr. Lee", &choices), Some(ChoiceReply::Pick(1)));
+        assert_eq!(parse_choice_reply("Dr. Lee", &choices), None);
+        assert_eq!(parse_choice_reply("no", &choices), None);
+        assert_eq!(parse_choice_reply("never mind", &choices), Some(ChoiceReply::Cancel));
+        assert_eq!(parse_choice_reply("3", &choices), None);
+        assert_eq!(parse_choice_reply("what's the weather?", &choices), None);
+    }
+}
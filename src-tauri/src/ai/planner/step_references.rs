@@ -0,0 +1,258 @@
+use serde_json::Value;
+
+use crate::ai::tool_outcome::ToolOutcome;
+
+/// A `{{steps[N].result.field}}` placeholder inside a step parameter. An
+/// integer offset may follow, e.g. `{{steps[0].result.trigger_at - 86400}}`.
+#[derive(Debug, Clone, PartialEq)]
+struct StepReference {
+    step: usize,
+    path: Vec<PathSegment>,
+    offset: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PathSegment {
+    Field(String),
+    Index(usize),
+}
+
+/// True when the whole string is a single placeholder, so its type is only
+/// known once the referenced step has run.
+pub fn is_placeholder(text: &str) -> bool {
+    let trimmed = text.trim();
+    trimmed.starts_with("{{") && trimmed.ends_with("}}") && trimmed.matches("{{").count() == 1
+}
+
+/// Checks placeholder syntax and that each one points at an earlier step.
+pub fn check_references(parameters: &Value, step_index: usize) -> Result<(), String> {
+    for text in string_leaves(parameters) {
+        for (raw, inner) in placeholders(text)? {
+            let reference = parse_reference(inner).map_err(|e| format!("Invalid reference {}: {}", raw, e))?;
+            if reference.step >= step_index {
+                return Err(format!(
+                    "Step {} references {}, but only earlier steps can be referenced",
+                    step_index + 1,
+                    raw
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Replaces every placeholder with the referenced output. A string that is
+/// exactly one placeholder takes the referenced value's type; placeholders
+/// inside longer text are substituted as text.
+pub fn resolve_parameters(parameters: &Value, step_index: usize, outputs: &[ToolOutcome]) -> Result<Value, String> {
+    match parameters {
+        Value::String(text) => resolve_string(text, step_index, outputs),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| resolve_parameters(item, step_index, outputs))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array),
+        Value::Object(object) => object
+            .iter()
+            .map(|(key, value)| Ok((key.clone(), resolve_parameters(value, step_index, outputs)?)))
+            .collect::<Result<serde_json::Map<_, _>, String>>()
+            .map(Value::Object),
+        other => Ok(other.clone()),
+    }
+}
+
+fn resolve_string(text: &str, step_index: usize, outputs: &[ToolOutcome]) -> Result<Value, String> {
+    let found = placeholders(text)?;
+    if found.is_empty() {
+        return Ok(Value::String(text.to_string()));
+    }
+
+    let resolve = |raw: &str, inner: &str| -> Result<Value, String> {
+        let reference = parse_reference(inner).map_err(|e| format!("Invalid reference {}: {}", raw, e))?;
+        lookup(&reference, outputs)
+            .map_err(|reason| format!("Could not resolve {} in step {}: {}", raw, step_index + 1, reason))
+    };
+
+    if is_placeholder(text) {
+        let (raw, inner) = found[0];
+        return resolve(raw, inner);
+    }
+
+    let mut resolved = text.to_string();
+    for (raw, inner) in found {
+        let value = resolve(raw, inner)?;
+        let replacement = match value {
+            Value::String(text) => text,
+            other => other.to_string(),
+        };
+        resolved = resolved.replacen(raw, &replacement, 1);
+    }
+
+    Ok(Value::String(resolved))
+}
+
+fn lookup(reference: &StepReference, outputs: &[ToolOutcome]) -> Result<Value, String> {
+    let output = outputs
+        .get(reference.step)
+        .ok_or_else(|| format!("step {} has not run", reference.step + 1))?;
+
+    let mut segments = reference.path.iter();
+    let mut current = match segments.next() {
+        Some(PathSegment::Field(root)) if root == "result" => output.data.clone(),
+        Some(PathSegment::Field(root)) if root == "message" => Value::String(output.message.clone()),
+        _ => return Err("a reference must start with result or message".to_string()),
+    };
+
+    for segment in segments {
+        let next = match segment {
+            PathSegment::Field(name) => current.get(name.as_str()),
+            PathSegment::Index(idx) => current.get(*idx),
+        };
+        current = match next {
+            Some(value) if !value.is_null() => value.clone(),
+            _ => {
+                let label = match segment {
+                    PathSegment::Field(name) => name.clone(),
+                    PathSegment::Index(idx) => format!("[{}]", idx),
+                };
+                return Err(format!("step {} produced no {}", reference.step + 1, label));
+            }
+        };
+    }
+
+    if reference.offset == 0 {
+        return Ok(current);
+    }
+
+    let number = current
+        .as_i64()
+        .or_else(|| current.as_str().and_then(|text| text.trim().parse::<i64>().ok()))
+        .ok_or_else(|| "an offset needs a whole number".to_string())?;
+    Ok(Value::from(number + reference.offset))
+}
+
+/// Every `{{...}}` in `text`, as (full placeholder, inner expression).
+fn placeholders(text: &str) -> Result<Vec<(&str, &str)>, String> {
+    let mut found = Vec::new();
+    let mut rest = text;
+    let mut consumed = 0;
+
+    while let Some(start) = rest.find("{{") {
+        let end = rest[start..]
+            .find("}}")
+            .map(|end| start + end + 2)
+            .ok_or_else(|| format!("Unclosed placeholder in \"{}\"", text))?;
+        let absolute_start = consumed + start;
+        let absolute_end = consumed + end;
+        found.push((
+            &text[absolute_start..absolute_end],
+            text[absolute_start + 2..absolute_end - 2].trim(),
+        ));
+        rest = &rest[end..];
+        consumed = absolute_end;
+    }
+
+    Ok(found)
+}
+
+fn parse_reference(expression: &str) -> Result<StepReference, String> {
+    let (path_text, offset) = match expression.find(['+', '-']) {
+        Some(pos) => {
+            let amount = expression[pos..]
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<String>()
+                .parse::<i64>()
+                .map_err(|_| "offset must be a whole number".to_string())?;
+            (expression[..pos].trim(), amount)
+        }
+        None => (expression.trim(), 0),
+    };
+
+    let rest = path_text
+        .strip_prefix("steps[")
+        .ok_or_else(|| "expected steps[N]".to_string())?;
+    let close = rest.find(']').ok_or_else(|| "expected steps[N]".to_string())?;
+    let step = rest[..close]
+        .trim()
+        .parse::<usize>()
+        .map_err(|_| "step index must be a number".to_string())?;
+
+    let mut path = Vec::new();
+    let mut rest = &rest[close + 1..];
+    while !rest.is_empty() {
+        if let Some(after_dot) = rest.strip_prefix('.') {
+            let end = after_dot.find(['.', '[']).unwrap_or(after_dot.len());
+            let name = &after_dot[..end];
+            if name.is_empty() {
+                return Err("empty field name".to_string());
+            }
+            path.push(PathSegment::Field(name.to_string()));
+            rest = &after_dot[end..];
+        } else if let Some(after_bracket) = rest.strip_prefix('[') {
+            let close = after_bracket.find(']').ok_or_else(|| "unclosed [".to_string())?;
+            let idx = after_bracket[..close]
+                .trim()
+                .parse::<usize>()
+                .map_err(|_| "array index must be a number".to_string())?;
+            path.push(PathSegment::Index(idx));
+            rest = &after_bracket[close + 1..];
+        } else {
+            return Err(format!("unexpected \"{}\"", rest));
+        }
+    }
+
+    if path.is_empty() {
+        return Err("expected steps[N].result or steps[N].message".to_string());
+    }
+
+    Ok(StepReference { step, path, offset })
+}
+
+fn string_leaves(value: &Value) -> Vec<&str> {
+    match value {
+        Value::String(text) => vec![text.as_str()],
+        Value::Array(items) => items.iter().flat_map(string_leaves).collect(),
+        Value::Object(object) => object.values().flat_map(string_leaves).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_references, resolve_parameters};
+    use crate::ai::tool_outcome::ToolOutcome;
+    use serde_json::json;
+
+    #[test]
+    fn resolves_typed_values_offsets_and_embedded_text() {
+        let outputs = vec![ToolOutcome::success("From your memory: exam on 2030-06-14").with_data(
+            json!({ "content": "exam on 2030-06-14", "trigger_at": 1907913600, "matches": ["exam on 2030-06-14"] }),
+        )];
+        let params = json!({
+            "content": "Study for {{ steps[0].result.matches[0] }}",
+            "trigger_at": "{{steps[0].result.trigger_at - 86400}}",
+        });
+
+        let resolved = resolve_parameters(&params, 1, &outputs).unwrap();
+
+        assert_eq!(resolved["content"], "Study for exam on 2030-06-14");
+        assert_eq!(resolved["trigger_at"], 1907827200);
+    }
+
+    #[test]
+    fn rejects_forward_missing_and_malformed_references() {
+        assert!(check_references(&json!({ "query": "{{steps[1].message}}" }), 1).is_err());
+        assert!(check_references(&json!({ "query": "{{step[0].message}}" }), 1).is_err());
+        assert!(check_references(&json!({ "query": "{{steps[0].message" }), 1).is_err());
+
+        let outputs = vec![ToolOutcome::success("Got it, I'll remember that.")];
+        let err = resolve_parameters(&json!({ "trigger_at": "{{steps[0].result.trigger_at}}" }), 1, &outputs)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            "Could not resolve {{steps[0].result.trigger_at}} in step 2: step 1 produced no trigger_at"
+        );
+    }
+}
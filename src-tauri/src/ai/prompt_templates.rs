@@ -1,19 +1,16 @@
+use serde::Serialize;
+
+use super::context_budget::{
+    estimate_tokens, AssembledContext, ContextAssembler, Keep, SectionUsage, HISTORY_TOKEN_BUDGET,
+    MEMORY_CONTEXT_TOKEN_BUDGET, PROMPT_TOKEN_BUDGET, RUNTIME_CONTEXT_TOKEN_BUDGET,
+};
+
 pub const SYSTEM_CONTROLLER_PROMPT: &str = r#"
 You are Noddy, an AI system controller. Your job is not to answer conversationally.
 Your job is to determine what backend action the system should perform.
 
-Supported intents:
-- set_reminder
-- save_memory
-- update_memory
-- delete_memory
-- forget_memory
-- search_memory
-- open_app
-- search_web
-- plugin_action
-- ai_query
-- unknown
+Supported intents (parameters marked ? are optional):
+{supported_intents}
 
 Return only valid JSON in this exact shape:
 {
@@ -31,10 +28,14 @@ Rules:
 - Only choose set_reminder for explicit reminder intent ("remind me", "set reminder", "alert me").
 - For reminders, prefer parameters.content and parameters.trigger_at when possible.
 - If exact reminder time cannot be normalized, return parameters.time_description.
+- For repeating reminders ("every monday", "every weekday at 9am", "every 2 weeks on friday", "daily"), put the repeat phrase in parameters.recurrence.
 - For memory search, use parameters.query.
 - For memory save, use parameters.content.
 - For memory update, use parameters.query and parameters.new_content when available.
 - For memory delete/forget, use parameters.query.
+- For tagging a memory, use tag_memory with parameters.query (which memory) and parameters.tags (array of tag names without '#').
+- For showing memories with a tag ("show my #work notes", "what's tagged ideas"), use list_by_tag with parameters.tag.
+- For memory save, copy any tags the user names into parameters.tags; #hashtags in the content are picked up automatically.
 - For app launch, use parameters.target.
 - For web search, use parameters.query or parameters.url.
 - If the user is giving you their class schedule (e.g., "every monday I have X at 10am", "on Tuesdays I attend Y"), choose save_memory and put the full message in parameters.content.
@@ -43,6 +44,7 @@ Rules:
 - If the user asks about their own saved/past information ("when is my class", "what did I tell you", "do you remember"), choose search_memory and set parameters.query to the key topic.
 - If the user corrects previous info ("no, it's at 11", "actually it's 3 PM", "update my class time"), choose update_memory.
 - If the user asks to remove memory ("forget that", "delete that memory", "remove my class note"), choose forget_memory or delete_memory.
+- If the user wants to revert what you just did ("undo that", "undo", "put it back"), choose undo. Do not confuse it with update_memory corrections.
 - If the user asks a conversational question, asks you to explain, tell them about, or process knowledge ("what is", "tell me about", "do you know"), choose ai_query and use parameters.query.
 - If the user says "yes", "do it", "open it" or similar in response to your previous offer to search Google, choose search_web. Extract the topic from previous context if needed.
 - For plugin actions, use parameters.plugin_id and parameters.command.
@@ -51,12 +53,15 @@ Rules:
 Examples:
 - "remember I have a class tomorrow" -> {"intent":"save_memory","parameters":{"content":"I have a class tomorrow"},"confidence":0.95}
 - "remind me about my class tomorrow" -> {"intent":"set_reminder","parameters":{"content":"my class","time_description":"tomorrow"},"confidence":0.95}
+- "remind me to water the plants every monday at 8am" -> {"intent":"set_reminder","parameters":{"content":"water the plants","recurrence":"every monday at 8am"},"confidence":0.95}
 - "do you know when I have the big data class" -> {"intent":"search_memory","parameters":{"query":"big data class"},"confidence":0.9}
 - "when will I usually have service oriented architecture class" -> {"intent":"search_memory","parameters":{"query":"service oriented architecture class"},"confidence":0.9}
 - "every monday I have big data analytics at 10am and OS at 2pm" -> {"intent":"save_memory","parameters":{"content":"every monday I have big data analytics at 10am and OS at 2pm"},"confidence":0.95}
 - "show me my full timetable" -> {"intent":"search_memory","parameters":{"query":"class schedule"},"confidence":0.9}
 - "actually it's at 11 AM" -> {"intent":"update_memory","parameters":{"query":"class","new_time":"11 AM"},"confidence":0.85}
 - "forget my old dbms class note" -> {"intent":"forget_memory","parameters":{"query":"dbms class"},"confidence":0.9}
+- "tag my dbms exam note as school and exams" -> {"intent":"tag_memory","parameters":{"query":"dbms exam","tags":["school","exams"]},"confidence":0.9}
+- "show me everything tagged work" -> {"intent":"list_by_tag","parameters":{"tag":"work"},"confidence":0.9}
 "#;
 
 pub const REMINDER_NORMALIZER_PROMPT: &str = r#"
@@ -68,6 +73,7 @@ Return only valid JSON in this exact shape:
   "content": "string",
   "trigger_at": 0,
   "time_description": "string",
+  "recurrence": "string",
   "confidence": 0.0
 }
 
@@ -77,6 +83,7 @@ Rules:
 - Prefer "trigger_at" as a Unix timestamp in seconds when the time can be determined.
 - If exact timestamp is ambiguous, set "trigger_at" to 0 and provide "time_description".
 - If both are available, include both.
+- If the reminder repeats, set "recurrence" to the repeat phrase (e.g. "every weekday at 9am"); otherwise leave it empty.
 - Never include explanatory text.
 "#;
 
@@ -125,28 +132,72 @@ Constraints:
 - Use only supported intents.
 - Keep simple requests as a single action.
 - For risky actions, set requires_confirmation=true.
+- To use an earlier action's output, write a placeholder as the parameter value:
+  {{steps[0].result.trigger_at}} (search_memory and set_reminder results carry trigger_at, content and matches).
+  A whole-number offset in seconds is allowed: {{steps[0].result.trigger_at - 86400}} is one day earlier.
 - No markdown, no prose, JSON only.
 
-Supported intents:
-- set_reminder
-- save_memory
-- update_memory
-- delete_memory
-- forget_memory
-- search_memory
-- open_app
-- search_web
-- plugin_action
-- ai_query
-- unknown
+Supported intents (parameters marked ? are optional):
+{supported_intents}
+"#;
+
+pub const TOOL_PLANNING_PROMPT: &str = r#"
+You are Noddy's action planning engine.
+Convert the user request into calls to the provided tools, in execution order.
+
+Constraints:
+- At most 5 calls.
+- Keep simple requests as a single call.
+- Distinguish "remember ..." (save_memory) from "remind me ..." (set_reminder).
+- For corrections ("actually it's at 11"), call update_memory.
+- To use an earlier call's output, pass a placeholder string such as {{steps[0].result.trigger_at}}
+  or {{steps[0].result.trigger_at - 86400}} (one day earlier), where 0 is the call's position.
+- Call unknown when nothing is actionable.
+- Do not answer in text.
 "#;
 
+/// Fills the intent list of a prompt from the tool registry.
+fn with_supported_intents(template: &str) -> String {
+    template.replace("{supported_intents}", &super::tool_registry::describe_intents())
+}
+
+/// A budgeted prompt and how each context section was fitted into it.
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptComposition {
+    pub prompt: String,
+    pub estimated_tokens: usize,
+    pub budget: usize,
+    pub sections: Vec<SectionUsage>,
+}
+
+/// Fits runtime context and conversation history around the fixed parts of a
+/// prompt. Runtime context (date and time first) outranks older conversation.
+fn budget_context(system: &str, message: &str, runtime_context: &str, history: &str) -> AssembledContext {
+    ContextAssembler::new(PROMPT_TOKEN_BUDGET)
+        .reserve(system)
+        .reserve(message)
+        .section("runtime_context", runtime_context, RUNTIME_CONTEXT_TOKEN_BUDGET, 2, Keep::Head)
+        .section("history", history, HISTORY_TOKEN_BUDGET, 1, Keep::Tail)
+        .assemble()
+}
+
+fn composition(prompt: String, context: AssembledContext) -> PromptComposition {
+    PromptComposition {
+        estimated_tokens: estimate_tokens(&prompt),
+        budget: PROMPT_TOKEN_BUDGET,
+        sections: context.usage,
+        prompt,
+    }
+}
+
 pub fn build_intent_prompt(message: &str, history: &str, datetime_context: &str) -> String {
+    let system = with_supported_intents(SYSTEM_CONTROLLER_PROMPT);
+    let context = budget_context(&system, message, datetime_context, history);
     format!(
         "{system}\n\n{datetime}\n\nRecent Conversation History:\n{history}\n\nUser request:\n{message}\n\nReturn JSON only.",
-        system = SYSTEM_CONTROLLER_PROMPT,
-        datetime = datetime_context,
-        history = history,
+        system = system,
+        datetime = context.get("runtime_context"),
+        history = context.get("history"),
         message = message.trim()
     )
 }
@@ -174,6 +225,8 @@ pub fn build_ai_assistant_query_prompt(query: &str, datetime_context: &str) -> S
     )
 }
 
+/// `memories` are ordered by relevance; the least relevant are left out first
+/// when they do not fit the memory budget.
 pub fn build_ai_assistant_query_with_context_prompt(
     query: &str,
     memories: &[String],
@@ -181,17 +234,22 @@ pub fn build_ai_assistant_query_with_context_prompt(
 ) -> String {
     let memory_context = memories
         .iter()
-        .take(5)
         .enumerate()
-        .map(|(i, m)| format!("{}. {}", i + 1, m))
+        .map(|(i, m)| format!("{}. {}", i + 1, m.replace('\n', " ")))
         .collect::<Vec<_>>()
         .join("\n");
+    let context = ContextAssembler::new(PROMPT_TOKEN_BUDGET)
+        .reserve(AI_ASSISTANT_STYLE_PROMPT)
+        .reserve(query)
+        .section("runtime_context", datetime_context, RUNTIME_CONTEXT_TOKEN_BUDGET, 2, Keep::Head)
+        .section("memories", &memory_context, MEMORY_CONTEXT_TOKEN_BUDGET, 1, Keep::Head)
+        .assemble();
 
     format!(
         "{system}\n\n{datetime}\n\nPersonal context from your memory:\n{context}\n\nUser question:\n{query}\n\nProvide the response now.",
         system = AI_ASSISTANT_STYLE_PROMPT,
-        datetime = datetime_context,
-        context = memory_context,
+        datetime = context.get("runtime_context"),
+        context = context.get("memories"),
         query = query.trim(),
     )
 }
@@ -222,11 +280,30 @@ pub fn build_timetable_parser_prompt(message: &str) -> String {
 }
 
 pub fn build_action_planning_prompt(message: &str, history: &str, runtime_context: &str) -> String {
-    format!(
+    compose_action_planning_prompt(message, history, runtime_context).prompt
+}
+
+/// The action planning prompt along with how its context was budgeted.
+pub fn compose_action_planning_prompt(message: &str, history: &str, runtime_context: &str) -> PromptComposition {
+    let system = with_supported_intents(ACTION_PLANNING_PROMPT);
+    let context = budget_context(&system, message, runtime_context, history);
+    let prompt = format!(
         "{system}\n\nRuntime context:\n{runtime}\n\nRecent Conversation History:\n{history}\n\nUser request:\n{message}\n\nReturn JSON only.",
-        system = ACTION_PLANNING_PROMPT,
-        runtime = runtime_context,
-        history = history,
+        system = system,
+        runtime = context.get("runtime_context"),
+        history = context.get("history"),
+        message = message.trim(),
+    );
+    composition(prompt, context)
+}
+
+pub fn build_tool_planning_prompt(message: &str, history: &str, runtime_context: &str) -> String {
+    let context = budget_context(TOOL_PLANNING_PROMPT, message, runtime_context, history);
+    format!(
+        "{system}\n\nRuntime context:\n{runtime}\n\nRecent Conversation History:\n{history}\n\nUser request:\n{message}",
+        system = TOOL_PLANNING_PROMPT,
+        runtime = context.get("runtime_context"),
+        history = context.get("history"),
         message = message.trim(),
     )
 }
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use rusqlite::Connection;
+use serde_json::Value;
+use tokio::sync::OwnedMutexGuard;
+
+use super::app_host::AppHost;
+use super::llm_provider::{self, LlmProvider};
+use super::mock_provider::ScriptedProvider;
+
+/// Records frontend events and opened URLs instead of touching a window.
+#[derive(Default)]
+pub struct RecordingHost {
+    events: Mutex<Vec<(String, Value)>>,
+    opened_urls: Mutex<Vec<String>>,
+    tokens: Mutex<Vec<String>>,
+}
+
+impl RecordingHost {
+    pub fn event_names(&self) -> Vec<String> {
+        self.events
+            .lock()
+            .map(|events| events.iter().map(|(name, _)| name.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Payloads of every `event` emitted so far, oldest first.
+    pub fn event_payloads(&self, event: &str) -> Vec<Value> {
+        self.events
+            .lock()
+            .map(|events| {
+                events
+                    .iter()
+                    .filter(|(name, _)| name == event)
+                    .map(|(_, payload)| payload.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn opened_urls(&self) -> Vec<String> {
+        self.opened_urls.lock().map(|urls| urls.clone()).unwrap_or_default()
+    }
+
+    pub fn streamed_tokens(&self) -> Vec<String> {
+        self.tokens.lock().map(|tokens| tokens.clone()).unwrap_or_default()
+    }
+}
+
+impl AppHost for RecordingHost {
+    fn emit_event(&self, event: &str, payload: Value) {
+        if let Ok(mut events) = self.events.lock() {
+            events.push((event.to_string(), payload));
+        }
+    }
+
+    fn open_url(&self, url: &str) -> Result<(), String> {
+        if let Ok(mut urls) = self.opened_urls.lock() {
+            urls.push(url.to_string());
+        }
+        Ok(())
+    }
+
+    fn stream_token(&self, token: &str) {
+        if let Ok(mut tokens) = self.tokens.lock() {
+            tokens.push(token.to_string());
+        }
+    }
+}
+
+/// The active LLM provider is process-wide, so harnesses take turns.
+fn provider_lock() -> Arc<tokio::sync::Mutex<()>> {
+    static LOCK: OnceLock<Arc<tokio::sync::Mutex<()>>> = OnceLock::new();
+    LOCK.get_or_init(|| Arc::new(tokio::sync::Mutex::new(()))).clone()
+}
+
+/// Drives `orchestrator::process_user_command` against an in-memory database
+/// and a scripted provider.
+pub struct Harness {
+    pub user_id: String,
+    pub provider: Arc<ScriptedProvider>,
+    pub host: RecordingHost,
+    pub memory_store: crate::MemoryStore,
+    pub registry: crate::AppRegistry,
+    pub plugin_registry: crate::plugin_registry::PluginRegistry,
+    pub event_bus: crate::EventBus,
+    pub permissions: crate::PermissionManager,
+    _provider_guard: OwnedMutexGuard<()>,
+}
+
+impl Harness {
+    pub async fn new(provider: ScriptedProvider) -> Self {
+        let guard = provider_lock().lock_owned().await;
+
+        let conn = Connection::open_in_memory().expect("Failed to open in-memory database");
+        crate::database::initialize_database(&conn).expect("Failed to initialize database");
+        // Plan deduplication is keyed by user, so every harness gets a fresh one.
+        let user_id = uuid::Uuid::new_v4().to_string();
+        conn.execute(
+            "INSERT INTO users (id, email, password_hash, created_at) VALUES (?1, ?2, '', 0)",
+            rusqlite::params![user_id, format!("{}@example.com", user_id)],
+        )
+        .expect("Failed to create test user");
+
+        let provider = Arc::new(provider);
+        llm_provider::set_active_provider(provider.clone() as Arc<dyn LlmProvider>);
+
+        Self {
+            user_id,
+            provider,
+            host: RecordingHost::default(),
+            memory_store: crate::MemoryStore {
+                conn: Mutex::new(conn),
+            },
+            registry: crate::AppRegistry {
+                apps: HashMap::new(),
+                display_names: Vec::new(),
+            },
+            plugin_registry: crate::plugin_registry::PluginRegistry::new(),
+            event_bus: crate::EventBus::new(),
+            permissions: crate::PermissionManager::default_permissions(),
+            _provider_guard: guard,
+        }
+    }
+
+    pub async fn send(&self, message: &str) -> Result<String, String> {
+        super::orchestrator::process_user_command(
+            message.to_string(),
+            &self.user_id,
+            &self.host,
+            &self.registry,
+            &self.memory_store,
+            &self.plugin_registry,
+            &self.event_bus,
+            &self.permissions,
+        )
+        .await
+    }
+
+    /// Approves or cancels the plan step awaiting confirmation, like `confirm_plan_step`.
+    pub async fn confirm(
+        &self,
+        plan_id: &str,
+        approve: bool,
+    ) -> Result<super::planner::plan_executor::PlanProgress, String> {
+        self.resume(plan_id, approve, None, None).await
+    }
+
+    /// Picks one of the memories a paused step offered, like `confirm_plan_step` with a `choice`.
+    pub async fn choose(
+        &self,
+        plan_id: &str,
+        choice: usize,
+    ) -> Result<super::planner::plan_executor::PlanProgress, String> {
+        self.resume(plan_id, true, Some(choice), None).await
+    }
+
+    /// Answers a permission prompt, like `confirm_plan_step` with a `grant`.
+    pub async fn grant(
+        &self,
+        plan_id: &str,
+        scope: crate::permission_policy_store::GrantScope,
+    ) -> Result<super::planner::plan_executor::PlanProgress, String> {
+        self.resume(plan_id, true, None, Some(scope)).await
+    }
+
+    async fn resume(
+        &self,
+        plan_id: &str,
+        approve: bool,
+        choice: Option<usize>,
+        grant: Option<crate::permission_policy_store::GrantScope>,
+    ) -> Result<super::planner::plan_executor::PlanProgress, String> {
+        super::planner::plan_executor::resume_action_plan(
+            plan_id,
+            approve,
+            choice,
+            grant,
+            &self.user_id,
+            &self.host,
+            &self.registry,
+            &self.memory_store,
+            &self.plugin_registry,
+            &self.event_bus,
+            &self.permissions,
+        )
+        .await
+    }
+
+    pub fn memory_contents(&self) -> Vec<String> {
+        let conn = self.memory_store.conn.lock().expect("Lock error");
+        crate::memory_graph_repository::list_all_memories(&conn, &self.user_id)
+            .expect("Failed to list memories")
+            .into_iter()
+            .map(|memory| memory.content)
+            .collect()
+    }
+
+    pub fn pending_reminders(&self) -> Vec<crate::reminder_store::Reminder> {
+        let conn = self.memory_store.conn.lock().expect("Lock error");
+        crate::reminder_store::get_pending_reminders(&conn, &self.user_id).expect("Failed to list reminders")
+    }
+}
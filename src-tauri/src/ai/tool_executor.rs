@@ -1,6 +1,16 @@
 use serde_json::Value;
 use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday};
-use tauri::Emitter;
+use super::app_host::AppHost;
+use super::tool_outcome::ToolOutcome;
+
+use crate::action_journal::InverseOperation;
+use crate::memory_store::SavedMemory;
+use crate::reminder_recurrence::{self, Frequency, Recurrence};
+
+/// Other matches scoring within this gap of the best one make an update or
+/// forget ambiguous, so the user is asked to pick.
+const AMBIGUOUS_SCORE_GAP: f64 = 0.08;
+const MAX_MEMORY_CHOICES: usize = 4;
 
 fn parse_json_array(raw: &str) -> Option<Vec<Value>> {
     // Try direct parse first
@@ -21,11 +31,11 @@ pub fn execute_set_reminder(
     parameters: &Value,
     user_message: &str,
     user_id: &str,
-    app_handle: &tauri::AppHandle,
+    app_handle: &dyn AppHost,
     memory_store: &crate::MemoryStore,
     event_bus: &crate::EventBus,
     permissions: &crate::PermissionManager,
-) -> Result<String, String> {
+) -> Result<ToolOutcome, String> {
     permissions.check_permission(crate::Capability::ReminderSchedule)?;
 
     let content = first_nonempty_string_param(
@@ -48,6 +58,8 @@ pub fn execute_set_reminder(
         content
     };
 
+    let recurring = recurrence_param(parameters)?;
+
     let trigger_at = timestamp_param(parameters)
         .or_else(|| {
             parameters
@@ -61,17 +73,21 @@ pub fn execute_set_reminder(
                 .and_then(Value::as_str)
                 .and_then(parse_time_description)
         })
+        .or_else(|| recurring.as_ref().and_then(|(first, _)| *first))
         .ok_or_else(|| "Reminder intent requires a supported trigger_at or time description".to_string())?;
 
+    let recurrence = recurring.map(|(_, rule)| rule);
+
     let payload = serde_json::json!({
         "content": content,
         "trigger_at": trigger_at,
+        "recurrence": recurrence,
     })
     .to_string();
 
-    crate::set_reminder(memory_store, user_id, &payload)?;
+    let reminder_id = crate::set_reminder(memory_store, user_id, &payload)?;
     event_bus.emit(&crate::Event::ReminderScheduled(content.clone()));
-    let _ = app_handle.emit(
+    app_handle.emit_event(
         "reminder_scheduled",
         serde_json::json!({
             "user_id": user_id,
@@ -80,7 +96,43 @@ pub fn execute_set_reminder(
         }),
     );
 
-    Ok(format!("Reminder scheduled for {}.", trigger_at))
+    let message = match &recurrence {
+        Some(rule) => format!(
+            "Reminder scheduled for {}, repeating {}.",
+            trigger_at,
+            rule.describe()
+        ),
+        None => format!("Reminder scheduled for {}.", trigger_at),
+    };
+    Ok(ToolOutcome::success(message)
+        .with_data(serde_json::json!({
+            "reminder_id": reminder_id,
+            "content": content,
+            "trigger_at": trigger_at,
+            "recurrence": recurrence,
+        }))
+        .with_inverse(InverseOperation::CancelReminder { reminder_id }))
+}
+
+/// Repeat rule from `recurrence` (a rule object or a phrase like "every monday")
+/// or from a recurring `time_description`/`time`. The first occurrence is
+/// returned alongside when it can be derived from the phrase.
+fn recurrence_param(parameters: &Value) -> Result<Option<(Option<i64>, Recurrence)>, String> {
+    if let Some(value) = parameters.get("recurrence").filter(|value| value.is_object()) {
+        let rule: Recurrence = serde_json::from_value(value.clone())
+            .map_err(|e| format!("Invalid recurrence rule: {}", e))?;
+        rule.validate()?;
+        let default_time = NaiveTime::from_hms_opt(9, 0, 0)
+            .ok_or_else(|| "Invalid default reminder time".to_string())?;
+        let first = reminder_recurrence::first_occurrence(&rule, default_time, Local::now().timestamp());
+        return Ok(Some((first, rule)));
+    }
+
+    Ok(["recurrence", "repeat", "time_description", "time"]
+        .iter()
+        .filter_map(|key| parameters.get(*key).and_then(Value::as_str))
+        .find_map(parse_recurrence_description)
+        .map(|(first, rule)| (Some(first), rule)))
 }
 
 pub async fn execute_save_memory(
@@ -89,7 +141,7 @@ pub async fn execute_save_memory(
     memory_store: &crate::MemoryStore,
     event_bus: &crate::EventBus,
     permissions: &crate::PermissionManager,
-) -> Result<String, String> {
+) -> Result<ToolOutcome, String> {
     permissions.check_permission(crate::Capability::MemoryWrite)?;
 
     let content = string_param(parameters, &["content", "memory", "text"])?;
@@ -97,8 +149,10 @@ pub async fn execute_save_memory(
     // Bulk schedule paste path (e.g. "Mon: Big Data 10am, OS 2pm").
     let bulk_entries = super::schedule_parser::parse_bulk_schedule_input(content);
     if !bulk_entries.is_empty() {
-        let labels = save_schedule_entries(memory_store, user_id, event_bus, &bulk_entries)?;
-        return Ok(format!("Got it, saved to memory: {}.", labels.join("; ")));
+        let (labels, saved) = save_schedule_entries(memory_store, user_id, event_bus, &bulk_entries)?;
+        let outcome = ToolOutcome::success(format!("Got it, saved to memory: {}.", labels.join("; ")))
+            .with_data(serde_json::json!({ "content": content, "entries": labels }));
+        return Ok(with_save_inverses(outcome, &saved));
     }
 
     // If the content looks like a class schedule, parse it into structured memory entries
@@ -108,6 +162,7 @@ pub async fn execute_save_memory(
             if let Some(parsed) = parse_json_array(&raw) {
                 if !parsed.is_empty() {
                     let mut labels = Vec::new();
+                    let mut saved = Vec::new();
                     for entry in &parsed {
                         let day = entry.get("day").and_then(Value::as_str).unwrap_or("").to_lowercase();
                         let subject = match entry.get("subject").and_then(Value::as_str) {
@@ -123,7 +178,7 @@ pub async fn execute_save_memory(
                             (true,  true)  => format!("class of {}", subject),
                         };
 
-                        crate::save_memory(memory_store, user_id, &memory_text)?;
+                        saved.push(crate::save_memory(memory_store, user_id, &memory_text)?);
                         event_bus.emit(&crate::Event::MemorySaved(memory_text));
 
                         let label = match (day.is_empty(), time.is_empty()) {
@@ -135,7 +190,9 @@ pub async fn execute_save_memory(
                         labels.push(label);
                     }
                     if !labels.is_empty() {
-                        return Ok(format!("Got it, saved to memory: {}.", labels.join("; ")));
+                        let outcome = ToolOutcome::success(format!("Got it, saved to memory: {}.", labels.join("; ")))
+                            .with_data(serde_json::json!({ "content": content, "entries": labels }));
+                        return Ok(with_save_inverses(outcome, &saved));
                     }
                 }
             }
@@ -143,9 +200,28 @@ pub async fn execute_save_memory(
         // Fallthrough: save as plain memory if parsing failed
     }
 
-    crate::save_memory(memory_store, user_id, content)?;
+    let tags = tags_param(parameters);
+    let saved = crate::save_memory_with_tags(memory_store, user_id, content, Some(tags.clone()))?;
     event_bus.emit(&crate::Event::MemorySaved(content.to_string()));
-    Ok("Got it, I'll remember that.".to_string())
+    let outcome = ToolOutcome::success("Got it, I'll remember that.").with_data(serde_json::json!({
+        "id": saved.id,
+        "content": content,
+        "tags": tags,
+    }));
+    Ok(with_save_inverses(outcome, &[saved]))
+}
+
+/// Undoing a save removes the memories it created. Content merged into an
+/// existing near-duplicate is left alone.
+fn with_save_inverses(outcome: ToolOutcome, saved: &[SavedMemory]) -> ToolOutcome {
+    saved
+        .iter()
+        .filter(|memory| !memory.merged)
+        .fold(outcome, |outcome, memory| {
+            outcome.with_inverse(InverseOperation::DeleteMemory {
+                memory_id: memory.id.clone(),
+            })
+        })
 }
 
 pub fn execute_update_memory(
@@ -154,17 +230,20 @@ pub fn execute_update_memory(
     memory_store: &crate::MemoryStore,
     event_bus: &crate::EventBus,
     permissions: &crate::PermissionManager,
-) -> Result<String, String> {
+) -> Result<ToolOutcome, String> {
     permissions.check_permission(crate::Capability::MemoryWrite)?;
 
     let query = first_nonempty_string_param(parameters, &["query", "keyword", "target", "old_content"])
         .unwrap_or("class");
 
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
-    let matches = crate::memory_store::search_memories(&conn, user_id, query.to_string(), 5)?;
-    let target = matches
-        .first()
-        .ok_or_else(|| format!("I couldn't find a memory to update for '{}'.", query))?;
+    let target = match find_target_memory(&conn, user_id, parameters, query)? {
+        Some(MemoryTarget::Found(target)) => target,
+        Some(MemoryTarget::Ambiguous(candidates)) => {
+            return Ok(memory_choice_outcome("update_memory", "update", query, parameters, &candidates));
+        }
+        None => return Err(format!("I couldn't find a memory to update for '{}'.", query)),
+    };
 
     let new_content = if let Some(explicit) = first_nonempty_string_param(
         parameters,
@@ -182,7 +261,12 @@ pub fn execute_update_memory(
     crate::memory_intelligence_service::calculate_memory_importance(&conn, user_id, &target.id)?;
 
     event_bus.emit(&crate::Event::MemoryUpdated(target.id.clone()));
-    Ok(format!("Updated memory: {}", new_content))
+    Ok(ToolOutcome::success(format!("Updated memory: {}", new_content))
+        .with_data(serde_json::json!({ "id": target.id, "content": new_content, "previous_content": target.content }))
+        .with_inverse(InverseOperation::RestoreContent {
+            memory_id: target.id.clone(),
+            content: target.content.clone(),
+        }))
 }
 
 pub fn execute_delete_memory(
@@ -191,19 +275,224 @@ pub fn execute_delete_memory(
     memory_store: &crate::MemoryStore,
     event_bus: &crate::EventBus,
     permissions: &crate::PermissionManager,
-) -> Result<String, String> {
+) -> Result<ToolOutcome, String> {
     permissions.check_permission(crate::Capability::MemoryWrite)?;
 
+    let query = string_param(parameters, &["query", "keyword", "target", "memory"])?;
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let target = match find_target_memory(&conn, user_id, parameters, query)? {
+        Some(MemoryTarget::Found(target)) => target,
+        Some(MemoryTarget::Ambiguous(candidates)) => {
+            return Ok(memory_choice_outcome("delete_memory", "forget", query, parameters, &candidates));
+        }
+        None => return Err(format!("I couldn't find a memory to forget for '{}'.", query)),
+    };
+
+    crate::memory_store::delete_memory(&conn, user_id, &target.id)?;
+    event_bus.emit(&crate::Event::MemoryDeleted(target.id.clone()));
+    Ok(ToolOutcome::success("Done. I forgot that memory.")
+        .with_data(serde_json::json!({ "id": target.id, "content": target.content }))
+        .with_inverse(InverseOperation::RestoreMemory {
+            memory_id: target.id.clone(),
+        }))
+}
+
+enum MemoryTarget {
+    Found(crate::memory_store::Memory),
+    /// Near-equal matches, best first
+    Ambiguous(Vec<crate::memory_store::Memory>),
+}
+
+/// The memory an update or forget acts on: the one the user picked via
+/// `memory_id`, otherwise the best search hit unless others score about as well.
+fn find_target_memory(
+    conn: &rusqlite::Connection,
+    user_id: &str,
+    parameters: &Value,
+    query: &str,
+) -> Result<Option<MemoryTarget>, String> {
+    if let Some(memory_id) = first_nonempty_string_param(parameters, &["memory_id"]) {
+        let record = crate::memory_graph_repository::get_memory(conn, user_id, memory_id)
+            .map_err(|_| "That memory no longer exists.".to_string())?;
+        return Ok(Some(MemoryTarget::Found(crate::memory_store::Memory {
+            id: record.id,
+            content: record.content,
+            created_at: record.created_at,
+            importance: record.importance,
+            source: "user_input".to_string(),
+        })));
+    }
+
+    let hits = crate::memory_store::search_memories_ranked(conn, user_id, query, 5)?;
+    let Some(best_score) = hits.first().map(|hit| hit.score) else {
+        return Ok(None);
+    };
+    let mut candidates = hits
+        .into_iter()
+        .take_while(|hit| best_score - hit.score <= AMBIGUOUS_SCORE_GAP)
+        .take(MAX_MEMORY_CHOICES)
+        .map(|hit| hit.memory)
+        .collect::<Vec<_>>();
+
+    Ok(Some(if candidates.len() == 1 {
+        MemoryTarget::Found(candidates.remove(0))
+    } else {
+        MemoryTarget::Ambiguous(candidates)
+    }))
+}
+
+/// Asks the user which memory they meant. Each follow-up repeats the call
+/// with the candidate's `memory_id` so picking it completes the operation.
+fn memory_choice_outcome(
+    intent: &str,
+    verb: &str,
+    query: &str,
+    parameters: &Value,
+    candidates: &[crate::memory_store::Memory],
+) -> ToolOutcome {
+    let list = candidates
+        .iter()
+        .enumerate()
+        .map(|(idx, memory)| format!("{}. {}", idx + 1, memory.content))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let outcome = ToolOutcome::needs_input(format!(
+        "I found several memories matching \"{}\". Which one should I {}?\n{}",
+        query, verb, list
+    ))
+    .with_data(serde_json::json!({
+        "query": query,
+        "choices": candidates
+            .iter()
+            .map(|memory| serde_json::json!({ "id": memory.id, "content": memory.content }))
+            .collect::<Vec<_>>(),
+    }));
+
+    candidates.iter().fold(outcome, |outcome, memory| {
+        let mut picked = parameters.as_object().cloned().unwrap_or_default();
+        picked.insert("memory_id".to_string(), Value::String(memory.id.clone()));
+        outcome.with_follow_up(memory.content.clone(), intent, Value::Object(picked))
+    })
+}
+
+pub fn execute_undo(
+    user_id: &str,
+    memory_store: &crate::MemoryStore,
+    event_bus: &crate::EventBus,
+    permissions: &crate::PermissionManager,
+) -> Result<ToolOutcome, String> {
+    permissions.check_permission(crate::Capability::MemoryWrite)?;
+
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let report = crate::action_journal::undo_last_action(&conn, user_id)?;
+    event_bus.emit(&crate::Event::IntentExecuted {
+        intent_name: "undo".to_string(),
+        duration_ms: 0,
+    });
+
+    Ok(match report {
+        Some(report) => ToolOutcome::success(report.summary()).with_data(serde_json::json!({
+            "plan_id": report.plan_id,
+            "undone": report.undone,
+        })),
+        None => ToolOutcome::no_results("There's nothing to undo."),
+    })
+}
+
+pub fn execute_tag_memory(
+    parameters: &Value,
+    user_id: &str,
+    memory_store: &crate::MemoryStore,
+    event_bus: &crate::EventBus,
+    permissions: &crate::PermissionManager,
+) -> Result<ToolOutcome, String> {
+    permissions.check_permission(crate::Capability::MemoryWrite)?;
+
+    let tags = tags_param(parameters);
+    if tags.is_empty() {
+        return Err("Tag intent requires at least one tag".to_string());
+    }
+
     let query = string_param(parameters, &["query", "keyword", "target", "memory"])?;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
     let matches = crate::memory_store::search_memories(&conn, user_id, query.to_string(), 5)?;
     let target = matches
         .first()
-        .ok_or_else(|| format!("I couldn't find a memory to forget for '{}'.", query))?;
+        .ok_or_else(|| format!("I couldn't find a memory to tag for '{}'.", query))?;
+
+    let previous_tags = crate::memory_tag_service::get_memory_tags(&conn, user_id, &target.id)?
+        .into_iter()
+        .map(|tag| tag.tag)
+        .collect::<Vec<_>>();
+    let attached = crate::memory_tag_service::attach_tags_by_name(&conn, user_id, &target.id, &tags)?;
+    if attached.is_empty() {
+        return Err("Tag intent requires at least one tag".to_string());
+    }
 
-    crate::memory_store::delete_memory(&conn, user_id, &target.id)?;
-    event_bus.emit(&crate::Event::MemoryDeleted(target.id.clone()));
-    Ok("Done. I forgot that memory.".to_string())
+    event_bus.emit(&crate::Event::MemoryUpdated(target.id.clone()));
+    let labels = attached
+        .iter()
+        .map(|tag| format!("#{}", tag))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let added = attached
+        .iter()
+        .filter(|tag| !previous_tags.contains(tag))
+        .cloned()
+        .collect::<Vec<_>>();
+    let outcome =
+        ToolOutcome::success(format!("Tagged \"{}\" with {}.", target.content, labels)).with_data(serde_json::json!({
+            "id": target.id,
+            "content": target.content,
+            "tags": attached,
+        }));
+    if added.is_empty() {
+        return Ok(outcome);
+    }
+    Ok(outcome.with_inverse(InverseOperation::DetachTags {
+        memory_id: target.id.clone(),
+        tags: added,
+    }))
+}
+
+pub fn execute_list_by_tag(
+    parameters: &Value,
+    user_id: &str,
+    memory_store: &crate::MemoryStore,
+    event_bus: &crate::EventBus,
+    permissions: &crate::PermissionManager,
+) -> Result<ToolOutcome, String> {
+    permissions.check_permission(crate::Capability::MemoryRead)?;
+
+    let tag = tags_param(parameters)
+        .into_iter()
+        .next()
+        .ok_or_else(|| "List-by-tag intent requires a tag".to_string())?;
+
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let memories = crate::memories_with_tag(&conn, user_id, &tag, 20)?;
+    event_bus.emit(&crate::Event::IntentExecuted {
+        intent_name: "list_by_tag".to_string(),
+        duration_ms: 0,
+    });
+
+    let label = tag.trim().trim_start_matches('#').to_lowercase();
+    let contents = memories.iter().map(|memory| memory.content.clone()).collect::<Vec<_>>();
+    let outcome = if contents.is_empty() {
+        ToolOutcome::no_results(format!("You don't have any memories tagged #{}.", label))
+    } else {
+        let lines = contents
+            .iter()
+            .map(|content| format!("- {}", content))
+            .collect::<Vec<_>>()
+            .join("\n");
+        ToolOutcome::success(format!("Memories tagged #{}:\n{}", label, lines))
+    };
+    Ok(outcome.with_data(serde_json::json!({
+        "tag": label,
+        "memory_ids": memories.iter().map(|memory| memory.id.clone()).collect::<Vec<_>>(),
+        "matches": contents,
+    })))
 }
 
 fn looks_like_schedule(text: &str) -> bool {
@@ -221,7 +510,7 @@ pub fn execute_search_memory(
     memory_store: &crate::MemoryStore,
     event_bus: &crate::EventBus,
     permissions: &crate::PermissionManager,
-) -> Result<String, String> {
+) -> Result<ToolOutcome, String> {
     permissions.check_permission(crate::Capability::MemoryRead)?;
 
     let query = string_param(parameters, &["query", "keyword", "text"])?;
@@ -231,11 +520,19 @@ pub fn execute_search_memory(
         duration_ms: 0,
     });
 
-    if results.is_empty() {
-        Ok(format!("I couldn't find anything in your memories about {}.", query))
+    let outcome = if results.is_empty() {
+        ToolOutcome::no_results(format!("I couldn't find anything in your memories about {}.", query))
+            .with_follow_up("Search the web", "search_web", serde_json::json!({ "query": query }))
     } else {
-        Ok(build_memory_search_answer(query, &results))
-    }
+        ToolOutcome::success(build_memory_search_answer(query, &results))
+    };
+    Ok(outcome.with_data(serde_json::json!({
+        "query": query,
+        "matches": results,
+        "content": results.first(),
+        // First date written in the top match, e.g. "exam on 2026-11-20 at 9am"
+        "trigger_at": results.first().and_then(|content| extract_timestamp(content)),
+    })))
 }
 
 fn build_memory_search_answer(query: &str, results: &[String]) -> String {
@@ -464,7 +761,7 @@ pub fn execute_open_app(
     registry: &crate::AppRegistry,
     event_bus: &crate::EventBus,
     permissions: &crate::PermissionManager,
-) -> Result<String, String> {
+) -> Result<ToolOutcome, String> {
     permissions.check_permission(crate::Capability::OpenApp)?;
 
     let target = string_param(parameters, &["target", "app", "app_name"])?;
@@ -474,15 +771,33 @@ pub fn execute_open_app(
         duration_ms: 0,
     });
 
-    Ok(format!("Opened app: {}", target))
+    Ok(ToolOutcome::success(format!("Opened app: {}", target)).with_data(serde_json::json!({ "target": target })))
+}
+
+pub fn execute_kill_process(
+    parameters: &Value,
+    event_bus: &crate::EventBus,
+    permissions: &crate::PermissionManager,
+) -> Result<ToolOutcome, String> {
+    permissions.check_permission(crate::Capability::KillProcess)?;
+
+    let process = string_param(parameters, &["process", "target", "process_name"])?;
+    crate::kill_process_internal(process.to_string())?;
+    event_bus.emit(&crate::Event::IntentExecuted {
+        intent_name: "kill_process".to_string(),
+        duration_ms: 0,
+    });
+
+    Ok(ToolOutcome::success(format!("Terminated process: {}", process))
+        .with_data(serde_json::json!({ "process": process })))
 }
 
 pub fn execute_search_web(
     parameters: &Value,
-    app_handle: &tauri::AppHandle,
+    app_handle: &dyn AppHost,
     event_bus: &crate::EventBus,
     permissions: &crate::PermissionManager,
-) -> Result<String, String> {
+) -> Result<ToolOutcome, String> {
     permissions.check_permission(crate::Capability::WebSearch)?;
 
     let destination = parameters
@@ -503,13 +818,13 @@ pub fn execute_search_web(
         crate::build_fallback_url(&destination)
     };
 
-    crate::open_url_internal(&final_url, app_handle)?;
+    app_handle.open_url(&final_url)?;
     event_bus.emit(&crate::Event::IntentExecuted {
         intent_name: "search_web".to_string(),
         duration_ms: 0,
     });
 
-    Ok(format!("Opened: {}", final_url))
+    Ok(ToolOutcome::success(format!("Opened: {}", final_url)).with_data(serde_json::json!({ "url": final_url })))
 }
 
 pub fn execute_plugin_action(
@@ -517,7 +832,10 @@ pub fn execute_plugin_action(
     memory_store: &crate::MemoryStore,
     plugin_registry: &crate::plugin_registry::PluginRegistry,
     event_bus: &crate::EventBus,
-) -> Result<String, String> {
+    permissions: &crate::PermissionManager,
+) -> Result<ToolOutcome, String> {
+    permissions.check_permission(crate::Capability::PluginAccess)?;
+
     let plugin_id = string_param(parameters, &["plugin_id", "plugin"])?;
     let command = string_param(parameters, &["command", "action"])?;
 
@@ -528,25 +846,31 @@ pub fn execute_plugin_action(
         duration_ms: 0,
     });
 
-    Ok(format!("Plugin result: {}", result))
+    Ok(ToolOutcome::success(format!("Plugin result: {}", result)).with_data(serde_json::json!({
+        "plugin_id": plugin_id,
+        "command": command,
+        "output": result,
+    })))
 }
 
 pub async fn execute_ai_query(
     parameters: &Value,
     user_message: &str,
     user_id: &str,
+    app_handle: &dyn AppHost,
     memory_store: &crate::MemoryStore,
-) -> Result<String, String> {
+    permissions: &crate::PermissionManager,
+) -> Result<ToolOutcome, String> {
     let query = parameters
         .get("query")
         .and_then(Value::as_str)
         .unwrap_or(user_message);
 
     if query.trim().is_empty() {
-        return Ok("I'm not sure what you're asking about.".to_string());
+        return Ok(ToolOutcome::needs_input("I'm not sure what you're asking about."));
     }
 
-    let runtime_context = super::context_builder::build_runtime_context(memory_store, user_id);
+    let runtime_context = super::context_builder::build_runtime_context(memory_store, user_id, permissions);
     let semantic_keywords = super::context_builder::extract_semantic_keywords(query).await;
 
     // Fetch relevant personal context to ground the answer.
@@ -581,8 +905,14 @@ pub async fn execute_ai_query(
         )
     };
 
-    let answer = super::llm_client::generate_structured_response(prompt).await?;
-    Ok(answer)
+    let answer = super::llm_client::generate_streaming_response(prompt, &|token| {
+        app_handle.stream_token(token)
+    })
+    .await?;
+    Ok(ToolOutcome::success(answer).with_data(serde_json::json!({
+        "query": query,
+        "memories_used": memories.len(),
+    })))
 }
 
 fn save_schedule_entries(
@@ -590,17 +920,18 @@ fn save_schedule_entries(
     user_id: &str,
     event_bus: &crate::EventBus,
     entries: &[super::schedule_parser::ScheduleEntry],
-) -> Result<Vec<String>, String> {
+) -> Result<(Vec<String>, Vec<SavedMemory>), String> {
     let mut labels = Vec::new();
+    let mut saved = Vec::new();
 
     for entry in entries {
         let memory_text = format!("class of {} at {} on {}", entry.subject, entry.time, entry.day);
-        crate::save_memory(memory_store, user_id, &memory_text)?;
+        saved.push(crate::save_memory(memory_store, user_id, &memory_text)?);
         event_bus.emit(&crate::Event::MemorySaved(memory_text));
         labels.push(format!("{} on {} at {}", entry.subject, entry.day, entry.time));
     }
 
-    Ok(labels)
+    Ok((labels, saved))
 }
 
 fn apply_time_correction(existing: &str, new_time: &str) -> String {
@@ -627,6 +958,28 @@ fn first_nonempty_string_param<'a>(parameters: &'a Value, keys: &[&str]) -> Opti
         .filter(|value| !value.is_empty())
 }
 
+/// Tags may arrive as an array or a comma/space separated string, under "tags" or "tag".
+fn tags_param(parameters: &Value) -> Vec<String> {
+    ["tags", "tag"]
+        .iter()
+        .filter_map(|key| parameters.get(*key))
+        .flat_map(|value| match value {
+            Value::Array(items) => items
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect::<Vec<_>>(),
+            Value::String(text) => text
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .map(str::to_string)
+                .collect(),
+            _ => Vec::new(),
+        })
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.trim_start_matches('#').is_empty())
+        .collect()
+}
+
 fn string_param<'a>(parameters: &'a Value, keys: &[&str]) -> Result<&'a str, String> {
     first_nonempty_string_param(parameters, keys)
         .ok_or_else(|| format!("Missing required parameter. Expected one of: {}", keys.join(", ")))
@@ -664,6 +1017,11 @@ fn parse_time_description(value: &str) -> Option<i64> {
     let normalized = normalize_time_description(value);
     let now = Local::now();
 
+    // "every monday at 9am" resolves to its first occurrence
+    if let Some((ts, _)) = parse_recurring_time(&normalized, now) {
+        return Some(ts);
+    }
+
     if let Some(ts) = parse_relative_time_description(&normalized, now) {
         return Some(ts);
     }
@@ -810,6 +1168,197 @@ fn parse_weekday_time(value: &str, now: chrono::DateTime<Local>) -> Option<i64>
     build_local_timestamp(target_date, time)
 }
 
+fn parse_recurrence_description(value: &str) -> Option<(i64, Recurrence)> {
+    parse_recurring_time(&normalize_time_description(value), Local::now())
+}
+
+/// Repeat phrases: "daily at 8pm", "every weekday at 9am", "every mon and thu",
+/// "every 2 weeks on friday", "every month on the 15th", "every 3 hours",
+/// optionally followed by "until <date>" or "for N times".
+fn parse_recurring_time(value: &str, now: chrono::DateTime<Local>) -> Option<(i64, Recurrence)> {
+    let (body, until) = match value.split_once(" until ") {
+        Some((body, until_text)) => (body, Some(parse_recurrence_until(until_text.trim())?)),
+        None => (value, None),
+    };
+
+    let mut tokens = body.split_whitespace().collect::<Vec<_>>();
+    let count = take_recurrence_count(&mut tokens);
+
+    let (mut rule, consumed, period_time) = parse_recurrence_rule(&tokens)?;
+    rule.until = until;
+    rule.count = count;
+
+    let tail = tokens[consumed..].join(" ");
+    let time = if tail.is_empty() {
+        period_time.or_else(|| NaiveTime::from_hms_opt(9, 0, 0))?
+    } else {
+        parse_clock_time(tail.strip_prefix("at ").unwrap_or(&tail))?
+    };
+
+    let first = reminder_recurrence::first_occurrence(&rule, time, now.timestamp())?;
+    if until.map(|limit| first > limit).unwrap_or(false) {
+        return None;
+    }
+
+    Some((first, rule))
+}
+
+/// Returns the rule, how many tokens it used and a time implied by words like "morning".
+fn parse_recurrence_rule(tokens: &[&str]) -> Option<(Recurrence, usize, Option<NaiveTime>)> {
+    let simple = match *tokens.first()? {
+        "hourly" => Some(Frequency::Hourly),
+        "daily" => Some(Frequency::Daily),
+        "weekly" => Some(Frequency::Weekly),
+        "monthly" => Some(Frequency::Monthly),
+        _ => None,
+    };
+    if let Some(frequency) = simple {
+        let mut rule = Recurrence::new(frequency);
+        let consumed = parse_recurrence_qualifier(&mut rule, tokens, 1);
+        return Some((rule, consumed, None));
+    }
+
+    if tokens[0] != "every" {
+        return None;
+    }
+
+    let mut idx = 1usize;
+    let mut interval = 1u32;
+    if tokens.get(idx) == Some(&"other") {
+        interval = 2;
+        idx += 1;
+    } else if let Some(amount) = tokens.get(idx).and_then(|token| token.parse::<u32>().ok()) {
+        interval = amount.max(1);
+        idx += 1;
+    }
+
+    let unit = *tokens.get(idx)?;
+    let mut period_time = None;
+    let mut rule = match unit {
+        "hour" | "hours" => Recurrence::new(Frequency::Hourly),
+        "day" | "days" => Recurrence::new(Frequency::Daily),
+        "week" | "weeks" => Recurrence::new(Frequency::Weekly),
+        "month" | "months" => Recurrence::new(Frequency::Monthly),
+        "morning" | "afternoon" | "evening" | "night" => {
+            let hour = match unit {
+                "morning" => 9,
+                "afternoon" => 14,
+                _ => 19,
+            };
+            period_time = NaiveTime::from_hms_opt(hour, 0, 0);
+            Recurrence::new(Frequency::Daily)
+        }
+        "weekday" | "weekdays" => {
+            let mut rule = Recurrence::new(Frequency::Weekly);
+            rule.weekdays = ["mon", "tue", "wed", "thu", "fri"].map(str::to_string).to_vec();
+            rule
+        }
+        "weekend" | "weekends" => {
+            let mut rule = Recurrence::new(Frequency::Weekly);
+            rule.weekdays = ["sat", "sun"].map(str::to_string).to_vec();
+            rule
+        }
+        _ => {
+            let (weekdays, next) = parse_weekday_list(tokens, idx);
+            if weekdays.is_empty() {
+                return None;
+            }
+            let mut rule = Recurrence::new(Frequency::Weekly);
+            rule.weekdays = weekdays;
+            rule.interval = interval;
+            return Some((rule, next, None));
+        }
+    };
+    idx += 1;
+
+    rule.interval = interval;
+    let consumed = parse_recurrence_qualifier(&mut rule, tokens, idx);
+    Some((rule, consumed, period_time))
+}
+
+/// "on friday" for weekly rules, "on the 15th" for monthly rules.
+fn parse_recurrence_qualifier(rule: &mut Recurrence, tokens: &[&str], idx: usize) -> usize {
+    if tokens.get(idx) != Some(&"on") {
+        return idx;
+    }
+
+    match rule.frequency {
+        Frequency::Weekly => {
+            let (weekdays, next) = parse_weekday_list(tokens, idx + 1);
+            if !weekdays.is_empty() {
+                rule.weekdays = weekdays;
+                return next;
+            }
+        }
+        Frequency::Monthly => {
+            let cursor = if tokens.get(idx + 1) == Some(&"the") { idx + 2 } else { idx + 1 };
+            if let Some(day) = tokens.get(cursor).and_then(|token| parse_ordinal_day(token)) {
+                rule.month_day = Some(day);
+                return cursor + 1;
+            }
+        }
+        _ => {}
+    }
+
+    idx
+}
+
+/// "monday and thursday", "mon wed fri", "mondays" -> ["mon", "wed", ...]
+fn parse_weekday_list(tokens: &[&str], start: usize) -> (Vec<String>, usize) {
+    let as_weekday = |token: &str| parse_weekday(token).or_else(|| token.strip_suffix('s').and_then(parse_weekday));
+
+    let mut weekdays = Vec::new();
+    let mut idx = start;
+    while let Some(day) = tokens.get(idx).and_then(|token| as_weekday(token)) {
+        let code = reminder_recurrence::weekday_code(day).to_string();
+        if !weekdays.contains(&code) {
+            weekdays.push(code);
+        }
+        idx += 1;
+
+        if tokens.get(idx) == Some(&"and") && tokens.get(idx + 1).and_then(|token| as_weekday(token)).is_some() {
+            idx += 1;
+        }
+    }
+
+    (weekdays, idx)
+}
+
+fn parse_ordinal_day(token: &str) -> Option<u32> {
+    let digits = token
+        .trim_end_matches("st")
+        .trim_end_matches("nd")
+        .trim_end_matches("rd")
+        .trim_end_matches("th");
+    digits.parse::<u32>().ok().filter(|day| (1..=31).contains(day))
+}
+
+/// Strips a trailing "(for) N times" and returns N.
+fn take_recurrence_count(tokens: &mut Vec<&str>) -> Option<u32> {
+    let len = tokens.len();
+    if len < 3 || !matches!(tokens[len - 1], "times" | "occurrences") {
+        return None;
+    }
+
+    let count = tokens[len - 2].parse::<u32>().ok().filter(|count| *count > 0)?;
+    tokens.truncate(len - 2);
+    if tokens.last() == Some(&"for") {
+        tokens.pop();
+    }
+    Some(count)
+}
+
+/// A bare date means "through the end of that day".
+fn parse_recurrence_until(value: &str) -> Option<i64> {
+    for fmt in ["%Y-%m-%d", "%Y/%m/%d"] {
+        if let Ok(date) = NaiveDate::parse_from_str(value, fmt) {
+            return build_local_timestamp(date, NaiveTime::from_hms_opt(23, 59, 59)?);
+        }
+    }
+
+    parse_time_description(value)
+}
+
 fn parse_absolute_datetime(value: &str) -> Option<i64> {
     for fmt in [
         "%Y-%m-%d %H:%M",
@@ -836,6 +1385,33 @@ fn parse_absolute_datetime(value: &str) -> Option<i64> {
     None
 }
 
+/// First written date in free text ("exam on 2026-11-20 at 9am"), at 9:00
+/// unless a clock time follows it.
+fn extract_timestamp(text: &str) -> Option<i64> {
+    let normalized = normalize_time_description(text);
+    let tokens = normalized
+        .split_whitespace()
+        .map(|token| token.trim_end_matches(['.', ';', ')']))
+        .collect::<Vec<_>>();
+
+    tokens.iter().enumerate().find_map(|(idx, token)| {
+        let date = ["%Y-%m-%d", "%Y/%m/%d"]
+            .iter()
+            .find_map(|fmt| NaiveDate::parse_from_str(token, fmt).ok())?;
+        let following = tokens[idx + 1..]
+            .iter()
+            .skip_while(|token| **token == "at")
+            .take(2)
+            .copied()
+            .collect::<Vec<_>>();
+        let time = (1..=following.len())
+            .rev()
+            .find_map(|len| parse_clock_time(&following[..len].join(" ")))
+            .or_else(|| NaiveTime::from_hms_opt(9, 0, 0))?;
+        build_local_timestamp(date, time)
+    })
+}
+
 fn parse_time_only(value: &str, now: chrono::DateTime<Local>) -> Option<i64> {
     let candidate = value.strip_prefix("at ").unwrap_or(value).trim();
     let time = parse_clock_time(candidate)?;
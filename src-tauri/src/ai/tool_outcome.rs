@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::action_journal::InverseOperation;
+
+/// How a tool call ended. Hard failures are still returned as `Err(String)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutcomeStatus {
+    #[default]
+    Success,
+    /// Ran fine but found nothing (empty search, no tagged memories)
+    NoResults,
+    /// Nothing was done because the request was unclear
+    NeedsInput,
+}
+
+impl OutcomeStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OutcomeStatus::Success => "success",
+            OutcomeStatus::NoResults => "no_results",
+            OutcomeStatus::NeedsInput => "needs_input",
+        }
+    }
+}
+
+/// An action the frontend can offer after a result, e.g. "Search the web".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FollowUp {
+    pub label: String,
+    pub intent: String,
+    #[serde(default)]
+    pub parameters: Value,
+}
+
+/// Result of one tool call: prose for the chat plus machine-readable `data`
+/// (created ids, matched memories, timestamps). Later plan steps read `data`
+/// through `{{steps[N].result...}}` placeholders.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolOutcome {
+    #[serde(default)]
+    pub status: OutcomeStatus,
+    pub message: String,
+    #[serde(default)]
+    pub data: Value,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub follow_ups: Vec<FollowUp>,
+    /// How to revert what the tool changed; journaled by the caller for undo.
+    #[serde(skip)]
+    pub inverses: Vec<InverseOperation>,
+}
+
+impl ToolOutcome {
+    pub fn success(message: impl Into<String>) -> Self {
+        Self::with_status(OutcomeStatus::Success, message)
+    }
+
+    pub fn no_results(message: impl Into<String>) -> Self {
+        Self::with_status(OutcomeStatus::NoResults, message)
+    }
+
+    pub fn needs_input(message: impl Into<String>) -> Self {
+        Self::with_status(OutcomeStatus::NeedsInput, message)
+    }
+
+    fn with_status(status: OutcomeStatus, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+            data: Value::Null,
+            follow_ups: Vec::new(),
+            inverses: Vec::new(),
+        }
+    }
+
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = data;
+        self
+    }
+
+    pub fn with_follow_up(mut self, label: impl Into<String>, intent: &str, parameters: Value) -> Self {
+        self.follow_ups.push(FollowUp {
+            label: label.into(),
+            intent: intent.to_string(),
+            parameters,
+        });
+        self
+    }
+
+    pub fn with_inverse(mut self, inverse: InverseOperation) -> Self {
+        self.inverses.push(inverse);
+        self
+    }
+}
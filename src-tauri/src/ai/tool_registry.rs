@@ -0,0 +1,438 @@
+use serde_json::{json, Map, Value};
+
+use super::planner::step_references;
+use crate::Capability;
+
+/// How a parameter is declared to the model and checked before routing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    String,
+    Integer,
+    /// An array of strings; a comma/space separated string is also accepted.
+    StringList,
+    /// A phrase like "every monday at 9am" or a `Recurrence` rule object.
+    Recurrence,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ToolParam {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub description: &'static str,
+    pub required: bool,
+    /// Older names the router still accepts in place of `name`. Not shown to models.
+    pub aliases: &'static [&'static str],
+}
+
+impl ToolParam {
+    pub const fn required(name: &'static str, kind: ParamKind, description: &'static str) -> Self {
+        Self {
+            name,
+            kind,
+            description,
+            required: true,
+            aliases: &[],
+        }
+    }
+
+    pub const fn optional(name: &'static str, kind: ParamKind, description: &'static str) -> Self {
+        Self {
+            name,
+            kind,
+            description,
+            required: false,
+            aliases: &[],
+        }
+    }
+
+    pub const fn aliases(mut self, aliases: &'static [&'static str]) -> Self {
+        self.aliases = aliases;
+        self
+    }
+}
+
+/// One intent the planner can emit and `intent_router::route_intent` can execute.
+#[derive(Debug, Clone, Copy)]
+pub struct ToolSpec {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub params: &'static [ToolParam],
+    /// Older intent names that route to this tool.
+    pub intent_aliases: &'static [&'static str],
+    /// Optional params of which at least one must be present.
+    pub one_of: &'static [&'static str],
+    /// Hidden tools are still routable but never offered to the model.
+    pub exposed: bool,
+    /// Destructive tools; a plan always pauses for the user before running them.
+    pub requires_confirmation: bool,
+    /// Checked against the user's permission policy before a plan runs the tool.
+    pub capability: Option<Capability>,
+}
+
+impl ToolSpec {
+    /// JSON Schema for the arguments object, as used by function-calling APIs.
+    pub fn parameters_schema(&self) -> Value {
+        let mut properties = Map::new();
+        for param in self.params {
+            let mut schema = match param.kind {
+                ParamKind::String | ParamKind::Recurrence => json!({ "type": "string" }),
+                ParamKind::Integer => json!({ "type": "integer" }),
+                ParamKind::StringList => json!({ "type": "array", "items": { "type": "string" } }),
+            };
+            schema["description"] = Value::String(param.description.to_string());
+            properties.insert(param.name.to_string(), schema);
+        }
+
+        let required = self
+            .params
+            .iter()
+            .filter(|param| param.required)
+            .map(|param| Value::String(param.name.to_string()))
+            .collect::<Vec<_>>();
+
+        json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        })
+    }
+}
+
+const QUERY_ALIASES: &[&str] = &["keyword", "target"];
+/// Set when the user picked one memory out of an ambiguous match.
+const MEMORY_ID_DESCRIPTION: &str = "Exact memory id, only when the user already picked one";
+
+const TOOLS: &[ToolSpec] = &[
+    ToolSpec {
+        name: "set_reminder",
+        description: "Schedule a reminder. Only for explicit reminder requests (\"remind me\", \"alert me\").",
+        params: &[
+            ToolParam::required("content", ParamKind::String, "What to remind the user about")
+                .aliases(&["task", "message"]),
+            ToolParam::optional("trigger_at", ParamKind::Integer, "Unix timestamp in seconds, when known"),
+            ToolParam::optional("time_description", ParamKind::String, "When, as the user said it (\"tomorrow at 9am\")"),
+            ToolParam::optional("recurrence", ParamKind::Recurrence, "Repeat phrase such as \"every monday at 8am\""),
+        ],
+        intent_aliases: &[],
+        one_of: &[],
+        exposed: true,
+        requires_confirmation: false,
+        capability: Some(Capability::ReminderSchedule),
+    },
+    ToolSpec {
+        name: "save_memory",
+        description: "Remember a fact the user shares (\"remember ...\"), including class schedules.",
+        params: &[
+            ToolParam::required("content", ParamKind::String, "The fact to remember").aliases(&["memory", "text"]),
+            ToolParam::optional("tags", ParamKind::StringList, "Tag names without '#'"),
+        ],
+        intent_aliases: &[],
+        one_of: &[],
+        exposed: true,
+        requires_confirmation: false,
+        capability: Some(Capability::MemoryWrite),
+    },
+    ToolSpec {
+        name: "update_memory",
+        description: "Correct a saved memory (\"actually it's at 11\"). Provide new_content or new_time.",
+        params: &[
+            ToolParam::required("query", ParamKind::String, "Words identifying the memory to change")
+                .aliases(QUERY_ALIASES),
+            ToolParam::optional("memory_id", ParamKind::String, MEMORY_ID_DESCRIPTION),
+            ToolParam::optional("new_content", ParamKind::String, "Full replacement text").aliases(&["content"]),
+            ToolParam::optional("new_time", ParamKind::String, "New time, when only the time changes"),
+        ],
+        intent_aliases: &[],
+        one_of: &["new_content", "new_time"],
+        exposed: true,
+        requires_confirmation: false,
+        capability: Some(Capability::MemoryWrite),
+    },
+    ToolSpec {
+        name: "delete_memory",
+        description: "Forget a saved memory.",
+        params: &[
+            ToolParam::required("query", ParamKind::String, "Words identifying the memory to forget")
+                .aliases(QUERY_ALIASES),
+            ToolParam::optional("memory_id", ParamKind::String, MEMORY_ID_DESCRIPTION),
+        ],
+        intent_aliases: &["forget_memory"],
+        one_of: &[],
+        exposed: true,
+        requires_confirmation: true,
+        capability: Some(Capability::MemoryWrite),
+    },
+    ToolSpec {
+        name: "undo",
+        description: "Revert the changes made by the last request (\"undo that\", \"that was wrong, put it back\").",
+        params: &[],
+        intent_aliases: &["undo_last_action"],
+        one_of: &[],
+        exposed: true,
+        requires_confirmation: false,
+        capability: Some(Capability::MemoryWrite),
+    },
+    ToolSpec {
+        name: "search_memory",
+        description: "Look up what the user told you before, including their timetable (query \"class schedule\").",
+        params: &[ToolParam::required("query", ParamKind::String, "Key topic to search for").aliases(QUERY_ALIASES)],
+        intent_aliases: &[],
+        one_of: &[],
+        exposed: true,
+        requires_confirmation: false,
+        capability: Some(Capability::MemoryRead),
+    },
+    ToolSpec {
+        name: "tag_memory",
+        description: "Add tags to a saved memory.",
+        params: &[
+            ToolParam::required("query", ParamKind::String, "Words identifying the memory to tag").aliases(QUERY_ALIASES),
+            ToolParam::required("tags", ParamKind::StringList, "Tag names without '#'").aliases(&["tag"]),
+        ],
+        intent_aliases: &[],
+        one_of: &[],
+        exposed: true,
+        requires_confirmation: false,
+        capability: Some(Capability::MemoryWrite),
+    },
+    ToolSpec {
+        name: "list_by_tag",
+        description: "Show memories carrying a tag (\"show my #work notes\").",
+        params: &[ToolParam::required("tag", ParamKind::StringList, "Tag name without '#'").aliases(&["tags"])],
+        intent_aliases: &[],
+        one_of: &[],
+        exposed: true,
+        requires_confirmation: false,
+        capability: Some(Capability::MemoryRead),
+    },
+    ToolSpec {
+        name: "open_app",
+        description: "Launch an installed application.",
+        params: &[ToolParam::required("target", ParamKind::String, "Application name").aliases(&["app", "app_name"])],
+        intent_aliases: &[],
+        one_of: &[],
+        exposed: true,
+        requires_confirmation: false,
+        capability: Some(Capability::OpenApp),
+    },
+    ToolSpec {
+        name: "kill_process",
+        description: "Force-quit a running application or process.",
+        params: &[ToolParam::required("process", ParamKind::String, "Process or application name")
+            .aliases(&["target", "process_name"])],
+        intent_aliases: &[],
+        one_of: &[],
+        exposed: true,
+        requires_confirmation: true,
+        capability: Some(Capability::KillProcess),
+    },
+    ToolSpec {
+        name: "search_web",
+        description: "Open a web search or URL in the browser.",
+        params: &[
+            ToolParam::required("query", ParamKind::String, "Search terms").aliases(&["url"]),
+            ToolParam::optional("url", ParamKind::String, "A full URL to open instead of searching"),
+        ],
+        intent_aliases: &[],
+        one_of: &[],
+        exposed: true,
+        requires_confirmation: false,
+        capability: Some(Capability::WebSearch),
+    },
+    ToolSpec {
+        name: "plugin_action",
+        description: "Run a command on an installed plugin.",
+        params: &[
+            ToolParam::required("plugin_id", ParamKind::String, "Plugin id"),
+            ToolParam::required("command", ParamKind::String, "Plugin command name"),
+        ],
+        intent_aliases: &[],
+        one_of: &[],
+        exposed: true,
+        requires_confirmation: false,
+        capability: Some(Capability::PluginAccess),
+    },
+    ToolSpec {
+        name: "ai_query",
+        description: "Answer a conversational or knowledge question.",
+        params: &[ToolParam::optional("query", ParamKind::String, "The question; defaults to the user's message")],
+        intent_aliases: &[],
+        one_of: &[],
+        exposed: true,
+        requires_confirmation: false,
+        capability: Some(Capability::MemoryRead),
+    },
+    ToolSpec {
+        name: "query_timetable",
+        description: "Legacy timetable lookup, now served by search_memory.",
+        params: &[],
+        intent_aliases: &[],
+        one_of: &[],
+        exposed: false,
+        requires_confirmation: false,
+        capability: Some(Capability::MemoryRead),
+    },
+    ToolSpec {
+        name: "unknown",
+        description: "Use when the request is not actionable.",
+        params: &[],
+        intent_aliases: &[],
+        one_of: &[],
+        exposed: true,
+        requires_confirmation: false,
+        capability: None,
+    },
+];
+
+/// Resolves an intent name, including legacy aliases such as "forget_memory".
+pub fn find_tool(intent: &str) -> Option<&'static ToolSpec> {
+    TOOLS
+        .iter()
+        .find(|tool| tool.name == intent || tool.intent_aliases.contains(&intent))
+}
+
+/// Tools offered to the model, in declaration order.
+pub fn exposed_tools() -> Vec<&'static ToolSpec> {
+    TOOLS.iter().filter(|tool| tool.exposed).collect()
+}
+
+/// Bullet list of intents and their parameters for text prompts.
+pub fn describe_intents() -> String {
+    exposed_tools()
+        .iter()
+        .map(|tool| {
+            let params = tool
+                .params
+                .iter()
+                .map(|param| {
+                    if param.required {
+                        param.name.to_string()
+                    } else {
+                        format!("{}?", param.name)
+                    }
+                })
+                .collect::<Vec<_>>();
+            if params.is_empty() {
+                format!("- {}", tool.name)
+            } else {
+                format!("- {} ({})", tool.name, params.join(", "))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Checks an intent's parameters against its schema. Unknown keys are ignored.
+pub fn validate_arguments(tool: &ToolSpec, params: &Value) -> Result<(), String> {
+    let empty = Map::new();
+    let object = match params {
+        Value::Object(object) => object,
+        Value::Null => &empty,
+        _ => return Err(format!("Parameters must be an object for intent {}", tool.name)),
+    };
+
+    for param in tool.params {
+        let present = std::iter::once(&param.name)
+            .chain(param.aliases.iter())
+            .filter_map(|key| object.get(*key).map(|value| (*key, value)))
+            .filter(|(_, value)| is_present(value))
+            .collect::<Vec<_>>();
+
+        if present.is_empty() && param.required {
+            let names = std::iter::once(&param.name)
+                .chain(param.aliases.iter())
+                .copied()
+                .collect::<Vec<_>>();
+            return Err(format!("{} requires {}", tool.name, names.join("/")));
+        }
+
+        for (key, value) in present {
+            if !matches_kind(value, param.kind) {
+                return Err(format!(
+                    "{}.{} must be {}",
+                    tool.name,
+                    key,
+                    kind_label(param.kind)
+                ));
+            }
+        }
+    }
+
+    let alternatives = tool
+        .params
+        .iter()
+        .filter(|param| tool.one_of.contains(&param.name))
+        .flat_map(|param| std::iter::once(param.name).chain(param.aliases.iter().copied()))
+        .collect::<Vec<_>>();
+    if !alternatives.is_empty() && !alternatives.iter().any(|key| object.get(*key).is_some_and(is_present)) {
+        return Err(format!("{} requires {}", tool.name, alternatives.join("/")));
+    }
+
+    Ok(())
+}
+
+fn is_present(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::String(text) => !text.trim().is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        _ => true,
+    }
+}
+
+fn matches_kind(value: &Value, kind: ParamKind) -> bool {
+    // A step reference is typed by the output it points at.
+    if value.as_str().is_some_and(step_references::is_placeholder) {
+        return true;
+    }
+
+    match kind {
+        ParamKind::String => value.is_string(),
+        ParamKind::Integer => value.is_i64() || value.is_u64(),
+        ParamKind::StringList => match value {
+            Value::String(_) => true,
+            Value::Array(items) => items.iter().all(Value::is_string),
+            _ => false,
+        },
+        ParamKind::Recurrence => value.is_string() || value.is_object(),
+    }
+}
+
+fn kind_label(kind: ParamKind) -> &'static str {
+    match kind {
+        ParamKind::String => "a string",
+        ParamKind::Integer => "an integer",
+        ParamKind::StringList => "a list of strings",
+        ParamKind::Recurrence => "a repeat phrase or rule object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_tool, validate_arguments};
+    use serde_json::json;
+
+    #[test]
+    fn validates_required_params_aliases_and_types() {
+        let reminder = find_tool("set_reminder").unwrap();
+        assert!(validate_arguments(reminder, &json!({ "task": "stretch", "trigger_at": 1 })).is_ok());
+        assert_eq!(
+            validate_arguments(reminder, &json!({ "time_description": "tomorrow" })).unwrap_err(),
+            "set_reminder requires content/task/message"
+        );
+        assert!(validate_arguments(reminder, &json!({ "content": "x", "trigger_at": "soon" })).is_err());
+
+        let update = find_tool("update_memory").unwrap();
+        assert!(validate_arguments(update, &json!({ "query": "class", "new_time": "11 AM" })).is_ok());
+        assert!(validate_arguments(update, &json!({ "query": "dentist", "content": "Dr. Park" })).is_ok());
+        assert_eq!(
+            validate_arguments(update, &json!({ "query": "dentist", "new_content": " " })).unwrap_err(),
+            "update_memory requires new_content/content/new_time"
+        );
+
+        let forget = find_tool("forget_memory").unwrap();
+        assert_eq!(forget.name, "delete_memory");
+        assert!(validate_arguments(find_tool("unknown").unwrap(), &serde_json::Value::Null).is_ok());
+        assert!(find_tool("format_disk").is_none());
+    }
+}
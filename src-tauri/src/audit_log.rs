@@ -0,0 +1,427 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::Capability;
+
+/// `prev_hash` of the first entry in the chain.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+const HMAC_BLOCK_SIZE: usize = 64;
+
+/// Key the chain is signed with. It lives outside the database, so someone who
+/// edits the file cannot recompute valid hashes after changing a row.
+static SIGNING_KEY: OnceLock<Vec<u8>> = OnceLock::new();
+
+const SIGNING_KEY_FILE: &str = "audit_log.key";
+const SIGNING_KEY_BYTES: usize = 32;
+
+pub mod action {
+    pub const SIGNUP: &str = "auth.signup";
+    pub const LOGIN: &str = "auth.login";
+    pub const REFRESH: &str = "auth.refresh";
+    pub const LOGOUT: &str = "auth.logout";
+    pub const CHANGE_PASSWORD: &str = "auth.change_password";
+    pub const REVOKE_SESSION: &str = "auth.revoke_session";
+    pub const REVOKE_ALL_SESSIONS: &str = "auth.revoke_all_sessions";
+    pub const OPEN_APP: &str = "action.open_app";
+    pub const KILL_PROCESS: &str = "action.kill_process";
+}
+
+pub mod outcome {
+    pub const SUCCESS: &str = "success";
+    pub const FAILURE: &str = "failure";
+    /// Refused by a permission setting or policy before it ran
+    pub const DENIED: &str = "denied";
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub user_id: Option<String>,
+    pub action: String,
+    pub target: Option<String>,
+    pub outcome: String,
+    pub detail: Option<String>,
+    pub client_info: String,
+    pub created_at: i64,
+    pub hash: String,
+}
+
+/// Optional filters for `get_audit_log`; unset fields match everything.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditLogFilter {
+    pub action: Option<String>,
+    pub outcome: Option<String>,
+    /// Substring of the target (app, process or email)
+    pub target: Option<String>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainVerification {
+    pub entries_checked: i64,
+    pub intact: bool,
+    /// First entry whose hash or link does not match.
+    pub first_broken_id: Option<i64>,
+}
+
+/// Capabilities whose use is written to the audit log.
+pub fn audit_action(capability: Capability) -> Option<&'static str> {
+    match capability {
+        Capability::OpenApp => Some(action::OPEN_APP),
+        Capability::KillProcess => Some(action::KILL_PROCESS),
+        _ => None,
+    }
+}
+
+/// The app or process a privileged tool call names.
+pub fn target_from_parameters(parameters: &Value) -> Option<String> {
+    ["target", "app", "app_name", "process", "process_name"]
+        .iter()
+        .find_map(|key| parameters.get(*key).and_then(Value::as_str))
+        .map(str::to_string)
+}
+
+pub fn host_name() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_else(|_| "unknown-host".to_string())
+}
+
+/// App version, OS and machine name of the client writing the entry.
+pub fn client_info() -> String {
+    format!(
+        "noddy/{} ({}; {})",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        host_name()
+    )
+}
+
+/// Loads the chain key from `config_dir`, creating a random one on first run.
+/// It is kept out of the database so whoever can edit the file cannot rehash
+/// rewritten rows. Only the first call takes effect.
+pub fn init_signing_key(config_dir: &Path) -> Result<(), String> {
+    let key = load_or_create_key(&config_dir.join(SIGNING_KEY_FILE))?;
+    let _ = SIGNING_KEY.set(key);
+    Ok(())
+}
+
+fn load_or_create_key(path: &Path) -> Result<Vec<u8>, String> {
+    match fs::read(path) {
+        Ok(key) if key.len() == SIGNING_KEY_BYTES => return Ok(key),
+        Ok(_) => return Err(format!("Audit log key {} is corrupt", path.display())),
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("Failed to read audit log key: {}", e)),
+    }
+
+    let mut key = vec![0u8; SIGNING_KEY_BYTES];
+    rand::thread_rng().fill_bytes(&mut key);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(&key))
+        .map_err(|e| format!("Failed to store audit log key: {}", e))?;
+
+    Ok(key)
+}
+
+fn signing_key() -> Result<&'static [u8], String> {
+    SIGNING_KEY
+        .get()
+        .map(Vec::as_slice)
+        .ok_or_else(|| "Audit log signing key is not set".to_string())
+}
+
+/// Appends an entry linked to the previous one by hash.
+pub fn append(
+    conn: &Connection,
+    user_id: Option<&str>,
+    action: &str,
+    target: Option<&str>,
+    outcome: &str,
+    detail: Option<&str>,
+) -> Result<(), String> {
+    let prev_hash: String = conn
+        .query_row("SELECT hash FROM audit_log ORDER BY id DESC LIMIT 1", [], |row| row.get(0))
+        .optional()
+        .map_err(|e| format!("Failed to read audit chain head: {}", e))?
+        .unwrap_or_else(|| GENESIS_HASH.to_string());
+    let client = client_info();
+    let created_at = current_timestamp();
+    let hash = entry_hash(signing_key()?, &prev_hash, user_id, action, target, outcome, detail, &client, created_at);
+
+    conn.execute(
+        "INSERT INTO audit_log (user_id, action, target, outcome, detail, client_info, created_at, prev_hash, hash)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![user_id, action, target, outcome, detail, client, created_at, prev_hash, hash],
+    )
+    .map_err(|e| format!("Failed to write audit log: {}", e))?;
+
+    Ok(())
+}
+
+/// Records how an operation ended: success, or failure with the error as detail.
+pub fn append_result<T>(
+    conn: &Connection,
+    user_id: Option<&str>,
+    action: &str,
+    target: Option<&str>,
+    result: &Result<T, String>,
+) -> Result<(), String> {
+    match result {
+        Ok(_) => append(conn, user_id, action, target, outcome::SUCCESS, None),
+        Err(err) => append(conn, user_id, action, target, outcome::FAILURE, Some(err)),
+    }
+}
+
+/// The user's entries matching `filter`, newest first.
+pub fn list_entries(conn: &Connection, user_id: &str, filter: &AuditLogFilter) -> Result<Vec<AuditEntry>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, user_id, action, target, outcome, detail, client_info, created_at, hash
+             FROM audit_log
+             WHERE user_id = ?1
+               AND (?2 IS NULL OR action = ?2)
+               AND (?3 IS NULL OR outcome = ?3)
+               AND (?4 IS NULL OR target LIKE '%' || ?4 || '%')
+               AND (?5 IS NULL OR created_at >= ?5)
+               AND (?6 IS NULL OR created_at <= ?6)
+             ORDER BY id DESC
+             LIMIT ?7",
+        )
+        .map_err(|e| format!("Failed to prepare audit log query: {}", e))?;
+
+    let entries = stmt
+        .query_map(
+            params![
+                user_id,
+                filter.action,
+                filter.outcome,
+                filter.target,
+                filter.since,
+                filter.until,
+                filter.limit.unwrap_or(100).clamp(1, 1000)
+            ],
+            |row| {
+                Ok(AuditEntry {
+                    id: row.get(0)?,
+                    user_id: row.get(1)?,
+                    action: row.get(2)?,
+                    target: row.get(3)?,
+                    outcome: row.get(4)?,
+                    detail: row.get(5)?,
+                    client_info: row.get(6)?,
+                    created_at: row.get(7)?,
+                    hash: row.get(8)?,
+                })
+            },
+        )
+        .map_err(|e| format!("Failed to query audit log: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to map audit log: {}", e))?;
+
+    Ok(entries)
+}
+
+/// Recomputes every hash from the start of the chain. Editing, removing or
+/// reordering a row breaks the link at that row, even if the hashes after it
+/// were rewritten, because they cannot be recomputed without the key.
+pub fn verify_chain(conn: &Connection) -> Result<ChainVerification, String> {
+    let key = signing_key()?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, user_id, action, target, outcome, detail, client_info, created_at, prev_hash, hash
+             FROM audit_log ORDER BY id ASC",
+        )
+        .map_err(|e| format!("Failed to prepare audit chain query: {}", e))?;
+    let mut rows = stmt
+        .query([])
+        .map_err(|e| format!("Failed to read audit chain: {}", e))?;
+
+    let mut expected_prev = GENESIS_HASH.to_string();
+    let mut entries_checked = 0;
+    while let Some(row) = rows.next().map_err(|e| format!("Failed to read audit entry: {}", e))? {
+        let read = |e: rusqlite::Error| format!("Failed to read audit entry: {}", e);
+        let id: i64 = row.get(0).map_err(read)?;
+        let user_id: Option<String> = row.get(1).map_err(read)?;
+        let action: String = row.get(2).map_err(read)?;
+        let target: Option<String> = row.get(3).map_err(read)?;
+        let outcome: String = row.get(4).map_err(read)?;
+        let detail: Option<String> = row.get(5).map_err(read)?;
+        let client: String = row.get(6).map_err(read)?;
+        let created_at: i64 = row.get(7).map_err(read)?;
+        let prev_hash: String = row.get(8).map_err(read)?;
+        let hash: String = row.get(9).map_err(read)?;
+        entries_checked += 1;
+
+        let recomputed = entry_hash(
+            key,
+            &prev_hash,
+            user_id.as_deref(),
+            &action,
+            target.as_deref(),
+            &outcome,
+            detail.as_deref(),
+            &client,
+            created_at,
+        );
+        if prev_hash != expected_prev || hash != recomputed {
+            return Ok(ChainVerification {
+                entries_checked,
+                intact: false,
+                first_broken_id: Some(id),
+            });
+        }
+        expected_prev = hash;
+    }
+
+    Ok(ChainVerification {
+        entries_checked,
+        intact: true,
+        first_broken_id: None,
+    })
+}
+
+/// HMAC-SHA256 (RFC 2104) of the entry's fields, hex encoded.
+#[allow(clippy::too_many_arguments)]
+fn entry_hash(
+    key: &[u8],
+    prev_hash: &str,
+    user_id: Option<&str>,
+    action: &str,
+    target: Option<&str>,
+    outcome: &str,
+    detail: Option<&str>,
+    client_info: &str,
+    created_at: i64,
+) -> String {
+    // A JSON array keeps field boundaries and missing values unambiguous
+    let canonical = serde_json::json!([prev_hash, user_id, action, target, outcome, detail, client_info, created_at]);
+
+    let mut block = [0_u8; HMAC_BLOCK_SIZE];
+    if key.len() > HMAC_BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let inner_pad = block.iter().map(|byte| byte ^ 0x36).collect::<Vec<_>>();
+    let outer_pad = block.iter().map(|byte| byte ^ 0x5c).collect::<Vec<_>>();
+    let inner = Sha256::new()
+        .chain_update(&inner_pad)
+        .chain_update(canonical.to_string().as_bytes())
+        .finalize();
+    Sha256::new()
+        .chain_update(&outer_pad)
+        .chain_update(inner)
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn current_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chain_detects_tampering_and_rows_cannot_be_changed() {
+        let config_dir = std::env::temp_dir().join(format!("noddy-audit-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&config_dir).unwrap();
+        init_signing_key(&config_dir).unwrap();
+        let key_path = config_dir.join(SIGNING_KEY_FILE);
+        assert_eq!(load_or_create_key(&key_path).unwrap(), signing_key().unwrap());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&key_path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        let conn = Connection::open_in_memory().unwrap();
+        crate::database::initialize_database(&conn).unwrap();
+
+        append(&conn, Some("u1"), action::LOGIN, Some("a@b.c"), outcome::SUCCESS, None).unwrap();
+        append(&conn, Some("u1"), action::OPEN_APP, Some("notepad"), outcome::DENIED, Some("blocked")).unwrap();
+        append_result::<()>(&conn, None, action::LOGIN, Some("x@y.z"), &Err("Invalid email or password".to_string()))
+            .unwrap();
+        assert!(verify_chain(&conn).unwrap().intact);
+
+        let filter = AuditLogFilter {
+            action: Some(action::OPEN_APP.to_string()),
+            ..Default::default()
+        };
+        let entries = list_entries(&conn, "u1", &filter).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].target.as_deref(), Some("notepad"));
+
+        assert!(conn.execute("UPDATE audit_log SET outcome = 'success' WHERE id = 2", []).is_err());
+        assert!(conn.execute("DELETE FROM audit_log WHERE id = 1", []).is_err());
+
+        // Someone with raw file access can drop the trigger and rewrite the
+        // chain, but without the key the rehashed rows do not verify
+        conn.execute("DROP TRIGGER audit_log_no_update", []).unwrap();
+        conn.execute("UPDATE audit_log SET outcome = 'success', detail = NULL WHERE id = 2", []).unwrap();
+        let mut prev_hash: String = conn
+            .query_row("SELECT hash FROM audit_log WHERE id = 1", [], |row| row.get(0))
+            .unwrap();
+        for id in 2..=3 {
+            let (user_id, action, target, outcome, detail, client, created_at): (
+                Option<String>,
+                String,
+                Option<String>,
+                String,
+                Option<String>,
+                String,
+                i64,
+            ) = conn
+                .query_row(
+                    "SELECT user_id, action, target, outcome, detail, client_info, created_at FROM audit_log WHERE id = ?1",
+                    params![id],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?, row.get(6)?)),
+                )
+                .unwrap();
+            let forged = entry_hash(
+                b"guessed-key",
+                &prev_hash,
+                user_id.as_deref(),
+                &action,
+                target.as_deref(),
+                &outcome,
+                detail.as_deref(),
+                &client,
+                created_at,
+            );
+            conn.execute(
+                "UPDATE audit_log SET prev_hash = ?1, hash = ?2 WHERE id = ?3",
+                params![prev_hash, forged, id],
+            )
+            .unwrap();
+            prev_hash = forged;
+        }
+        let verification = verify_chain(&conn).unwrap();
+        assert!(!verification.intact);
+        assert_eq!(verification.first_broken_id, Some(2));
+    }
+}
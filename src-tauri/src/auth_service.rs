@@ -9,6 +9,9 @@ use uuid::Uuid;
 
 const ACCESS_TOKEN_TTL_SECONDS: i64 = 15 * 60;
 const REFRESH_TOKEN_TTL_SECONDS: i64 = 30 * 24 * 60 * 60;
+/// Failed logins in a row before the email is locked out.
+const MAX_FAILED_LOGINS: i64 = 5;
+const LOCKOUT_SECONDS: i64 = 15 * 60;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct AuthTokens {
@@ -24,6 +27,8 @@ pub struct AuthUser {
     pub id: String,
     pub email: String,
     pub created_at: i64,
+    /// The first account on this install; may inspect other accounts' login lockouts.
+    pub is_admin: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -32,15 +37,45 @@ pub struct AuthResult {
     pub tokens: AuthTokens,
 }
 
+/// Failed-login state for one email.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LoginThrottle {
+    pub failed_attempts: i64,
+    pub last_failed_at: Option<i64>,
+    /// Set while the email is locked out.
+    pub locked_until: Option<i64>,
+}
+
+/// A signed-in device, as listed in account settings.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    pub id: String,
+    pub device_label: Option<String>,
+    pub created_at: i64,
+    pub last_used_at: Option<i64>,
+    pub expires_at: i64,
+    /// The session the request came from.
+    pub current: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct Claims {
     sub: String,
     exp: usize,
     iat: usize,
     jti: String,
+    /// Session the token belongs to; absent from tokens issued before sessions were listed.
+    #[serde(default)]
+    sid: Option<String>,
 }
 
-pub fn signup(conn: &Connection, email: &str, password: &str, jwt_secret: &str) -> Result<AuthResult, String> {
+pub fn signup(
+    conn: &Connection,
+    email: &str,
+    password: &str,
+    jwt_secret: &str,
+    device_label: &str,
+) -> Result<AuthResult, String> {
     validate_email(email)?;
     validate_password(password)?;
 
@@ -62,31 +97,147 @@ pub fn signup(conn: &Connection, email: &str, password: &str, jwt_secret: &str)
 
     let user_id = Uuid::new_v4().to_string();
     let password_hash = hash_password(password)?;
+    let is_admin = !conn
+        .query_row("SELECT EXISTS (SELECT 1 FROM users)", [], |row| row.get::<_, bool>(0))
+        .map_err(|e| format!("Failed to count users: {}", e))?;
 
     conn.execute(
-        "INSERT INTO users (id, email, password_hash, created_at) VALUES (?1, ?2, ?3, ?4)",
-        params![user_id, normalized_email, password_hash, now],
+        "INSERT INTO users (id, email, password_hash, created_at, is_admin) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![user_id, normalized_email, password_hash, now, is_admin],
     )
     .map_err(|e| format!("Failed to create user: {}", e))?;
 
     // Migrate legacy local data created before multi-user support.
     claim_orphaned_local_data(conn, &user_id)?;
 
-    let tokens = issue_tokens(conn, &user_id, jwt_secret)?;
+    let tokens = issue_tokens(conn, &user_id, jwt_secret, device_label)?;
 
     Ok(AuthResult {
         user: AuthUser {
             id: user_id,
             email: normalized_email,
             created_at: now,
+            is_admin,
         },
         tokens,
     })
 }
 
-pub fn login(conn: &Connection, email: &str, password: &str, jwt_secret: &str) -> Result<AuthResult, String> {
+pub fn login(
+    conn: &Connection,
+    email: &str,
+    password: &str,
+    jwt_secret: &str,
+    device_label: &str,
+) -> Result<AuthResult, String> {
     let normalized_email = email.trim().to_lowercase();
+    let now = now_timestamp();
+    check_login_throttle(&login_throttle(conn, &normalized_email)?, now)?;
+
+    let user = match authenticate(conn, &normalized_email, password) {
+        Ok(user) => user,
+        Err(err) => {
+            record_failed_login(conn, &normalized_email, now)?;
+            return Err(err);
+        }
+    };
+    conn.execute(
+        "DELETE FROM login_attempts WHERE email = ?1",
+        params![normalized_email],
+    )
+    .map_err(|e| format!("Failed to reset login attempts: {}", e))?;
+
+    // Ensure previously unowned local rows are attached to this account.
+    claim_orphaned_local_data(conn, &user.0)?;
+
+    let tokens = issue_tokens(conn, &user.0, jwt_secret, device_label)?;
+
+    Ok(AuthResult {
+        user: get_user_by_id(conn, &user.0)?,
+        tokens,
+    })
+}
+
+/// Failed-login state for `email`; an expired lockout reads as a clean slate.
+pub fn login_throttle(conn: &Connection, email: &str) -> Result<LoginThrottle, String> {
+    let throttle = conn
+        .query_row(
+            "SELECT failed_count, last_failed_at, locked_until FROM login_attempts WHERE email = ?1",
+            params![email.trim().to_lowercase()],
+            |row| {
+                Ok(LoginThrottle {
+                    failed_attempts: row.get(0)?,
+                    last_failed_at: row.get(1)?,
+                    locked_until: row.get(2)?,
+                })
+            },
+        )
+        .optional()
+        .map_err(|e| format!("Failed to load login attempts: {}", e))?
+        .unwrap_or_default();
+
+    match throttle.locked_until {
+        Some(locked_until) if locked_until <= now_timestamp() => Ok(LoginThrottle::default()),
+        _ => Ok(throttle),
+    }
+}
+
+/// Failed-login state for any account's `email`, for admins only.
+pub fn login_throttle_as_admin(conn: &Connection, admin_id: &str, email: &str) -> Result<LoginThrottle, String> {
+    if !get_user_by_id(conn, admin_id)?.is_admin {
+        return Err("Only an administrator can view login lockouts".to_string());
+    }
+    login_throttle(conn, email)
+}
 
+/// Refuses attempts during a lockout, and until the back-off after the last
+/// failure (1s, 2s, 4s, ...) has passed.
+fn check_login_throttle(throttle: &LoginThrottle, now: i64) -> Result<(), String> {
+    if let Some(locked_until) = throttle.locked_until.filter(|locked_until| *locked_until > now) {
+        let minutes = ((locked_until - now) as u64).div_ceil(60);
+        return Err(format!(
+            "Too many failed login attempts. Try again in {} minute{}.",
+            minutes,
+            if minutes == 1 { "" } else { "s" }
+        ));
+    }
+
+    if let Some(last_failed_at) = throttle.last_failed_at.filter(|_| throttle.failed_attempts > 0) {
+        let delay = 1_i64 << (throttle.failed_attempts - 1).min(10);
+        let retry_at = last_failed_at + delay;
+        if now < retry_at {
+            let seconds = retry_at - now;
+            return Err(format!(
+                "Too many login attempts. Wait {} second{} before trying again.",
+                seconds,
+                if seconds == 1 { "" } else { "s" }
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn record_failed_login(conn: &Connection, email: &str, now: i64) -> Result<(), String> {
+    let failed_attempts = login_throttle(conn, email)?.failed_attempts + 1;
+    let locked_until = (failed_attempts >= MAX_FAILED_LOGINS).then_some(now + LOCKOUT_SECONDS);
+
+    conn.execute(
+        "INSERT INTO login_attempts (email, failed_count, last_failed_at, locked_until)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(email) DO UPDATE SET
+            failed_count = excluded.failed_count,
+            last_failed_at = excluded.last_failed_at,
+            locked_until = excluded.locked_until",
+        params![email, failed_attempts, now, locked_until],
+    )
+    .map_err(|e| format!("Failed to record login attempt: {}", e))?;
+
+    Ok(())
+}
+
+/// Looks up the account and checks the password; returns (id, email, hash, created_at).
+fn authenticate(conn: &Connection, normalized_email: &str, password: &str) -> Result<(String, String, String, i64), String> {
     let user = conn
         .query_row(
             "SELECT id, email, password_hash, created_at FROM users WHERE email = ?1",
@@ -105,36 +256,23 @@ pub fn login(conn: &Connection, email: &str, password: &str, jwt_secret: &str) -
         .ok_or_else(|| "Invalid email or password".to_string())?;
 
     verify_password(&user.2, password)?;
-
-    // Ensure previously unowned local rows are attached to this account.
-    claim_orphaned_local_data(conn, &user.0)?;
-
-    let tokens = issue_tokens(conn, &user.0, jwt_secret)?;
-
-    Ok(AuthResult {
-        user: AuthUser {
-            id: user.0,
-            email: user.1,
-            created_at: user.3,
-        },
-        tokens,
-    })
+    Ok(user)
 }
 
 pub fn refresh(conn: &Connection, refresh_token: &str, jwt_secret: &str) -> Result<AuthTokens, String> {
     let user_id = verify_jwt(refresh_token, jwt_secret)?;
     let now = now_timestamp();
 
-    let session_exists: Option<i64> = conn
+    let session_exists: Option<(String, i64)> = conn
         .query_row(
-            "SELECT expires_at FROM sessions WHERE refresh_token = ?1 AND user_id = ?2",
+            "SELECT id, expires_at FROM sessions WHERE refresh_token = ?1 AND user_id = ?2",
             params![refresh_token, user_id],
-            |row| row.get(0),
+            |row| Ok((row.get(0)?, row.get(1)?)),
         )
         .optional()
         .map_err(|e| format!("Failed to validate refresh session: {}", e))?;
 
-    let expires_at = session_exists.ok_or_else(|| "Refresh session not found".to_string())?;
+    let (session_id, expires_at) = session_exists.ok_or_else(|| "Refresh session not found".to_string())?;
 
     if expires_at <= now {
         conn.execute(
@@ -145,37 +283,158 @@ pub fn refresh(conn: &Connection, refresh_token: &str, jwt_secret: &str) -> Resu
         return Err("Refresh token expired".to_string());
     }
 
+    // The session row is kept so its device label and age survive rotation
+    let tokens = sign_session_tokens(&user_id, &session_id, jwt_secret)?;
     conn.execute(
-        "DELETE FROM sessions WHERE refresh_token = ?1",
-        params![refresh_token],
+        "UPDATE sessions SET refresh_token = ?1, expires_at = ?2, last_used_at = ?3 WHERE id = ?4",
+        params![tokens.refresh_token, now + REFRESH_TOKEN_TTL_SECONDS, now, session_id],
     )
     .map_err(|e| format!("Failed to rotate refresh token: {}", e))?;
 
-    issue_tokens(conn, &user_id, jwt_secret)
+    Ok(tokens)
 }
 
-pub fn logout(conn: &Connection, refresh_token: &str) -> Result<(), String> {
+/// Ends the session and returns the user it belonged to, if it still existed.
+pub fn logout(conn: &Connection, refresh_token: &str) -> Result<Option<String>, String> {
+    let user_id: Option<String> = conn
+        .query_row(
+            "SELECT user_id FROM sessions WHERE refresh_token = ?1",
+            params![refresh_token],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to look up session: {}", e))?;
     conn.execute(
         "DELETE FROM sessions WHERE refresh_token = ?1",
         params![refresh_token],
     )
     .map_err(|e| format!("Failed to logout session: {}", e))?;
+    Ok(user_id)
+}
+
+/// Checks the signature and, for tokens that name a session, that the session
+/// has not been revoked or signed out since the token was issued.
+pub fn verify_access_token(conn: &Connection, token: &str, jwt_secret: &str) -> Result<String, String> {
+    let claims = decode_claims(token, jwt_secret)?;
+    if let Some(session_id) = &claims.sid {
+        let active: Option<i64> = conn
+            .query_row(
+                "SELECT 1 FROM sessions WHERE id = ?1 AND user_id = ?2 AND expires_at > ?3",
+                params![session_id, claims.sub, now_timestamp()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to validate session: {}", e))?;
+        if active.is_none() {
+            return Err("Session has been signed out".to_string());
+        }
+    }
+    Ok(claims.sub)
+}
+
+/// The session an access token was issued for, when it names one.
+pub fn session_id_from_token(token: &str, jwt_secret: &str) -> Option<String> {
+    decode_claims(token, jwt_secret).ok().and_then(|claims| claims.sid)
+}
+
+/// Unexpired sessions of the user, most recently used first.
+pub fn list_sessions(conn: &Connection, user_id: &str, current_session: Option<&str>) -> Result<Vec<SessionInfo>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, device_label, created_at, last_used_at, expires_at
+             FROM sessions
+             WHERE user_id = ?1 AND expires_at > ?2
+             ORDER BY COALESCE(last_used_at, created_at) DESC",
+        )
+        .map_err(|e| format!("Failed to prepare session query: {}", e))?;
+
+    let sessions = stmt
+        .query_map(params![user_id, now_timestamp()], |row| {
+            let id: String = row.get(0)?;
+            Ok(SessionInfo {
+                current: current_session == Some(id.as_str()),
+                id,
+                device_label: row.get(1)?,
+                created_at: row.get(2)?,
+                last_used_at: row.get(3)?,
+                expires_at: row.get(4)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query sessions: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to map sessions: {}", e))?;
+
+    Ok(sessions)
+}
+
+/// Signs one device out. Its access token stays valid until it expires, at
+/// most `ACCESS_TOKEN_TTL_SECONDS`, but it can no longer be refreshed.
+pub fn revoke_session(conn: &Connection, user_id: &str, session_id: &str) -> Result<(), String> {
+    let removed = conn
+        .execute(
+            "DELETE FROM sessions WHERE id = ?1 AND user_id = ?2",
+            params![session_id, user_id],
+        )
+        .map_err(|e| format!("Failed to revoke session: {}", e))?;
+
+    if removed == 0 {
+        return Err("Session not found".to_string());
+    }
     Ok(())
 }
 
-pub fn verify_access_token(token: &str, jwt_secret: &str) -> Result<String, String> {
-    verify_jwt(token, jwt_secret)
+/// Signs every device out, this one included. Returns how many sessions ended.
+pub fn revoke_all_sessions(conn: &Connection, user_id: &str) -> Result<usize, String> {
+    conn.execute("DELETE FROM sessions WHERE user_id = ?1", params![user_id])
+        .map_err(|e| format!("Failed to revoke sessions: {}", e))
+}
+
+/// Replaces the password after checking the current one. Every existing session
+/// is revoked; the caller gets a fresh session so it stays signed in.
+pub fn change_password(
+    conn: &Connection,
+    user_id: &str,
+    current_password: &str,
+    new_password: &str,
+    jwt_secret: &str,
+    device_label: &str,
+) -> Result<AuthTokens, String> {
+    let stored_hash: String = conn
+        .query_row(
+            "SELECT password_hash FROM users WHERE id = ?1",
+            params![user_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to load user: {}", e))?
+        .ok_or_else(|| "User not found".to_string())?;
+
+    verify_password(&stored_hash, current_password).map_err(|_| "Current password is incorrect".to_string())?;
+    validate_password(new_password)?;
+    if new_password == current_password {
+        return Err("New password must be different from the current one".to_string());
+    }
+
+    conn.execute(
+        "UPDATE users SET password_hash = ?1 WHERE id = ?2",
+        params![hash_password(new_password)?, user_id],
+    )
+    .map_err(|e| format!("Failed to update password: {}", e))?;
+    revoke_all_sessions(conn, user_id)?;
+
+    issue_tokens(conn, user_id, jwt_secret, device_label)
 }
 
 pub fn get_user_by_id(conn: &Connection, user_id: &str) -> Result<AuthUser, String> {
     conn.query_row(
-        "SELECT id, email, created_at FROM users WHERE id = ?1",
+        "SELECT id, email, created_at, COALESCE(is_admin, 0) FROM users WHERE id = ?1",
         params![user_id],
         |row| {
             Ok(AuthUser {
                 id: row.get(0)?,
                 email: row.get(1)?,
                 created_at: row.get(2)?,
+                is_admin: row.get(3)?,
             })
         },
     )
@@ -184,22 +443,49 @@ pub fn get_user_by_id(conn: &Connection, user_id: &str) -> Result<AuthUser, Stri
     .ok_or_else(|| "User not found".to_string())
 }
 
+/// The account registered under `email`, if any.
+pub fn user_id_for_email(conn: &Connection, email: &str) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT id FROM users WHERE email = ?1",
+        params![email.trim().to_lowercase()],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| format!("Failed to look up user: {}", e))
+}
+
 pub fn claim_orphaned_data_for_user(conn: &Connection, user_id: &str) -> Result<(), String> {
     claim_orphaned_local_data(conn, user_id)
 }
 
-fn issue_tokens(conn: &Connection, user_id: &str, jwt_secret: &str) -> Result<AuthTokens, String> {
+fn issue_tokens(conn: &Connection, user_id: &str, jwt_secret: &str, device_label: &str) -> Result<AuthTokens, String> {
     let now = now_timestamp();
-    let access_token = sign_jwt(user_id, now + ACCESS_TOKEN_TTL_SECONDS, jwt_secret)?;
-    let refresh_token = sign_jwt(user_id, now + REFRESH_TOKEN_TTL_SECONDS, jwt_secret)?;
-
     let session_id = Uuid::new_v4().to_string();
+    let tokens = sign_session_tokens(user_id, &session_id, jwt_secret)?;
+
     conn.execute(
-        "INSERT INTO sessions (id, user_id, refresh_token, created_at, expires_at) VALUES (?1, ?2, ?3, ?4, ?5)",
-        params![session_id, user_id, refresh_token, now, now + REFRESH_TOKEN_TTL_SECONDS],
+        "INSERT INTO sessions (id, user_id, refresh_token, created_at, expires_at, device_label, last_used_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            session_id,
+            user_id,
+            tokens.refresh_token,
+            now,
+            now + REFRESH_TOKEN_TTL_SECONDS,
+            device_label,
+            now
+        ],
     )
     .map_err(|e| format!("Failed to persist refresh session: {}", e))?;
 
+    Ok(tokens)
+}
+
+fn sign_session_tokens(user_id: &str, session_id: &str, jwt_secret: &str) -> Result<AuthTokens, String> {
+    let now = now_timestamp();
+    let access_token = sign_jwt(user_id, session_id, now + ACCESS_TOKEN_TTL_SECONDS, jwt_secret)?;
+    let refresh_token = sign_jwt(user_id, session_id, now + REFRESH_TOKEN_TTL_SECONDS, jwt_secret)?;
+
     Ok(AuthTokens {
         access_token,
         refresh_token,
@@ -209,13 +495,14 @@ fn issue_tokens(conn: &Connection, user_id: &str, jwt_secret: &str) -> Result<Au
     })
 }
 
-fn sign_jwt(user_id: &str, exp: i64, jwt_secret: &str) -> Result<String, String> {
+fn sign_jwt(user_id: &str, session_id: &str, exp: i64, jwt_secret: &str) -> Result<String, String> {
     let now = now_timestamp();
     let claims = Claims {
         sub: user_id.to_string(),
         exp: exp as usize,
         iat: now as usize,
         jti: Uuid::new_v4().to_string(),
+        sid: Some(session_id.to_string()),
     };
 
     encode(
@@ -227,14 +514,17 @@ fn sign_jwt(user_id: &str, exp: i64, jwt_secret: &str) -> Result<String, String>
 }
 
 fn verify_jwt(token: &str, jwt_secret: &str) -> Result<String, String> {
-    let decoded = decode::<Claims>(
+    decode_claims(token, jwt_secret).map(|claims| claims.sub)
+}
+
+fn decode_claims(token: &str, jwt_secret: &str) -> Result<Claims, String> {
+    decode::<Claims>(
         token,
         &DecodingKey::from_secret(jwt_secret.as_bytes()),
         &Validation::default(),
     )
-    .map_err(|_| "Invalid or expired token".to_string())?;
-
-    Ok(decoded.claims.sub)
+    .map(|decoded| decoded.claims)
+    .map_err(|_| "Invalid or expired token".to_string())
 }
 
 fn hash_password(password: &str) -> Result<String, String> {
@@ -302,5 +592,134 @@ fn claim_orphaned_local_data(conn: &Connection, user_id: &str) -> Result<(), Str
     )
     .map_err(|e| format!("Failed to migrate legacy edges: {}", e))?;
 
+    // Unowned tags whose label the user already has are merged into the user's tag
+    conn.execute(
+        "UPDATE OR IGNORE memory_tag_links
+         SET tag_id = (
+            SELECT owned.id FROM memory_tags owned
+            WHERE owned.user_id = ?1
+              AND owned.tag = (SELECT orphan.tag FROM memory_tags orphan WHERE orphan.id = memory_tag_links.tag_id)
+         )
+         WHERE tag_id IN (
+            SELECT orphan.id FROM memory_tags orphan
+            WHERE orphan.user_id IS NULL
+              AND EXISTS (SELECT 1 FROM memory_tags owned WHERE owned.user_id = ?1 AND owned.tag = orphan.tag)
+         )",
+        params![user_id],
+    )
+    .map_err(|e| format!("Failed to merge legacy tag links: {}", e))?;
+
+    conn.execute(
+        "DELETE FROM memory_tags
+         WHERE user_id IS NULL AND tag IN (SELECT tag FROM memory_tags WHERE user_id = ?1)",
+        params![user_id],
+    )
+    .map_err(|e| format!("Failed to merge legacy tags: {}", e))?;
+
+    conn.execute(
+        "UPDATE memory_tags SET user_id = ?1 WHERE user_id IS NULL",
+        params![user_id],
+    )
+    .map_err(|e| format!("Failed to migrate legacy tags: {}", e))?;
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_failures_lock_the_email_until_the_lockout_ends() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::database::initialize_database(&conn).unwrap();
+        signup(&conn, "sam@example.com", "correct horse", "secret", "laptop").unwrap();
+        // Steps past the back-off so each attempt is actually checked
+        let skip_delay = |conn: &Connection| {
+            conn.execute("UPDATE login_attempts SET last_failed_at = last_failed_at - 3600", [])
+                .unwrap();
+        };
+
+        let err = login(&conn, "sam@example.com", "wrong", "secret", "laptop").err().unwrap();
+        assert_eq!(err, "Invalid email or password");
+        let throttle = login_throttle(&conn, "sam@example.com").unwrap();
+        let failed_at = throttle.last_failed_at.unwrap();
+        let err = check_login_throttle(&throttle, failed_at).err().unwrap();
+        assert_eq!(err, "Too many login attempts. Wait 1 second before trying again.");
+        assert!(check_login_throttle(&throttle, failed_at + 1).is_ok());
+
+        skip_delay(&conn);
+        login(&conn, "Sam@example.com ", "correct horse", "secret", "laptop").unwrap();
+        assert_eq!(login_throttle(&conn, "sam@example.com").unwrap().failed_attempts, 0);
+
+        for _ in 0..MAX_FAILED_LOGINS {
+            skip_delay(&conn);
+            assert!(login(&conn, "sam@example.com", "wrong", "secret", "laptop").is_err());
+        }
+        let throttle = login_throttle(&conn, "sam@example.com").unwrap();
+        assert_eq!(throttle.failed_attempts, MAX_FAILED_LOGINS);
+        assert!(throttle.locked_until.is_some());
+        let err = login(&conn, "sam@example.com", "correct horse", "secret", "laptop").err().unwrap();
+        assert!(err.starts_with("Too many failed login attempts."), "{}", err);
+
+        conn.execute("UPDATE login_attempts SET locked_until = locked_until - ?1", params![LOCKOUT_SECONDS])
+            .unwrap();
+        skip_delay(&conn);
+        login(&conn, "sam@example.com", "correct horse", "secret", "laptop").unwrap();
+    }
+
+    #[test]
+    fn only_the_first_account_can_view_other_lockouts() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::database::initialize_database(&conn).unwrap();
+        let admin = signup(&conn, "sam@example.com", "correct horse", "secret", "laptop").unwrap().user;
+        let other = signup(&conn, "alex@example.com", "battery staple", "secret", "laptop").unwrap().user;
+        assert!(admin.is_admin);
+        assert!(!other.is_admin);
+
+        assert!(login(&conn, "alex@example.com", "wrong", "secret", "laptop").is_err());
+
+        let throttle = login_throttle_as_admin(&conn, &admin.id, "Alex@example.com").unwrap();
+        assert_eq!(throttle.failed_attempts, 1);
+        assert_eq!(
+            login_throttle_as_admin(&conn, &other.id, "sam@example.com").err().unwrap(),
+            "Only an administrator can view login lockouts"
+        );
+    }
+
+    #[test]
+    fn refresh_keeps_the_session_and_a_password_change_revokes_every_session() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::database::initialize_database(&conn).unwrap();
+        let laptop = signup(&conn, "sam@example.com", "correct horse", "secret", "laptop").unwrap();
+        let phone = login(&conn, "sam@example.com", "correct horse", "secret", "phone").unwrap();
+        let user_id = laptop.user.id.clone();
+
+        let refreshed = refresh(&conn, &phone.tokens.refresh_token, "secret").unwrap();
+        let current = session_id_from_token(&refreshed.access_token, "secret");
+        assert_eq!(current, session_id_from_token(&phone.tokens.access_token, "secret"));
+        let sessions = list_sessions(&conn, &user_id, current.as_deref()).unwrap();
+        assert_eq!(sessions.len(), 2);
+        let phone_session = sessions.iter().find(|session| session.current).unwrap();
+        assert_eq!(phone_session.device_label.as_deref(), Some("phone"));
+
+        let laptop_session = sessions.iter().find(|session| !session.current).unwrap();
+        assert_eq!(verify_access_token(&conn, &laptop.tokens.access_token, "secret").unwrap(), user_id);
+        revoke_session(&conn, &user_id, &laptop_session.id).unwrap();
+        assert!(refresh(&conn, &laptop.tokens.refresh_token, "secret").is_err());
+        assert_eq!(
+            verify_access_token(&conn, &laptop.tokens.access_token, "secret").unwrap_err(),
+            "Session has been signed out"
+        );
+        assert_eq!(revoke_session(&conn, &user_id, &laptop_session.id).unwrap_err(), "Session not found");
+
+        let err = change_password(&conn, &user_id, "wrong", "battery staple", "secret", "phone").unwrap_err();
+        assert_eq!(err, "Current password is incorrect");
+        let tokens = change_password(&conn, &user_id, "correct horse", "battery staple", "secret", "phone").unwrap();
+        assert!(refresh(&conn, &refreshed.refresh_token, "secret").is_err());
+        assert!(verify_access_token(&conn, &refreshed.access_token, "secret").is_err());
+        assert_eq!(list_sessions(&conn, &user_id, None).unwrap().len(), 1);
+        assert!(refresh(&conn, &tokens.refresh_token, "secret").is_ok());
+        assert!(login(&conn, "sam@example.com", "battery staple", "secret", "tablet").is_ok());
+    }
+}
@@ -15,12 +15,23 @@ pub fn create_message(
     user_id: &str,
     role: &str,
     content: String,
+) -> Result<String, String> {
+    create_message_with_id(conn, &Uuid::new_v4().to_string(), user_id, role, content)
+}
+
+/// Same as `create_message`, for callers that handed out the id beforehand
+/// (e.g. streamed replies tagged with it).
+pub fn create_message_with_id(
+    conn: &Connection,
+    id: &str,
+    user_id: &str,
+    role: &str,
+    content: String,
 ) -> Result<String, String> {
     if role != "user" && role != "assistant" {
         return Err(format!("Invalid chat role: {}", role));
     }
 
-    let id = Uuid::new_v4().to_string();
     let now = current_timestamp();
 
     conn.execute(
@@ -30,7 +41,7 @@ pub fn create_message(
     )
     .map_err(|e| format!("Failed to create chat message: {}", e))?;
 
-    Ok(id)
+    Ok(id.to_string())
 }
 
 pub fn get_messages(
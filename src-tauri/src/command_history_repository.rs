@@ -15,6 +15,8 @@ pub struct CommandRecord {
     pub created_at: i64,
     pub status: String,
     pub error_message: Option<String>,
+    /// JSON of the tool outcome (message, data, follow-ups) when one was recorded.
+    pub metadata: Option<String>,
 }
 
 /// Aggregated usage statistics for a single user.
@@ -32,6 +34,15 @@ pub struct CommandStats {
 // WRITE
 // ============================================================================
 
+/// What a command produced, stored next to its record.
+#[derive(Debug, Clone, Default)]
+pub struct CommandResult {
+    /// Overrides the default "completed"/"failed" status.
+    pub status: Option<String>,
+    pub error_message: Option<String>,
+    pub metadata: Option<String>,
+}
+
 /// Persist a single command execution record to the database.
 /// Returns the generated UUID for the new row.
 pub fn insert_command_record(
@@ -41,15 +52,18 @@ pub fn insert_command_record(
     intent_name: &str,
     success: bool,
     duration_ms: u128,
+    result: CommandResult,
 ) -> Result<String, String> {
     let id = Uuid::new_v4().to_string();
     let now = now_unix();
-    let status = if success { "completed" } else { "failed" };
+    let status = result
+        .status
+        .unwrap_or_else(|| if success { "completed" } else { "failed" }.to_string());
 
     conn.execute(
         "INSERT INTO command_history
-         (id, user_id, command_text, intent_name, success, duration_ms, timestamp, status)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+         (id, user_id, command_text, intent_name, success, duration_ms, timestamp, status, error_message, metadata)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
         params![
             id,
             user_id,
@@ -59,6 +73,8 @@ pub fn insert_command_record(
             duration_ms as i64,
             now,
             status,
+            result.error_message,
+            result.metadata,
         ],
     )
     .map_err(|e| format!("Failed to insert command record: {}", e))?;
@@ -79,7 +95,7 @@ pub fn get_recent_commands(
     let mut stmt = conn
         .prepare(
             "SELECT id, user_id, command_text, intent_name, success,
-                    duration_ms, timestamp, status, error_message
+                    duration_ms, timestamp, status, error_message, metadata
              FROM command_history
              WHERE user_id = ?1
              ORDER BY timestamp DESC
@@ -99,6 +115,7 @@ pub fn get_recent_commands(
                 created_at: row.get(6)?,
                 status: row.get(7)?,
                 error_message: row.get(8)?,
+                metadata: row.get(9)?,
             })
         })
         .map_err(|e| format!("Failed to execute query: {}", e))?
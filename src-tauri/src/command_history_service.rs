@@ -1,6 +1,7 @@
 use rusqlite::Connection;
 
-use crate::command_history_repository::{self, CommandRecord};
+use crate::ai::tool_outcome::ToolOutcome;
+use crate::command_history_repository::{self, CommandRecord, CommandResult};
 
 // ============================================================================
 // WRITE
@@ -25,6 +26,44 @@ pub fn record_command_execution(
         intent_name,
         success,
         duration_ms,
+        CommandResult::default(),
+    )?;
+    Ok(())
+}
+
+/// Record a tool call together with what it returned. The outcome's status
+/// becomes the record status and its JSON is kept in `metadata`.
+pub fn record_tool_outcome(
+    conn: &Connection,
+    user_id: &str,
+    intent_name: &str,
+    command_text: &str,
+    outcome: Result<&ToolOutcome, &str>,
+    duration_ms: u128,
+) -> Result<(), String> {
+    let result = match outcome {
+        Ok(outcome) => CommandResult {
+            status: Some(outcome.status.as_str().to_string()),
+            error_message: None,
+            metadata: Some(
+                serde_json::to_string(outcome).map_err(|e| format!("Failed to serialize tool outcome: {}", e))?,
+            ),
+        },
+        Err(error) => CommandResult {
+            status: None,
+            error_message: Some(error.to_string()),
+            metadata: None,
+        },
+    };
+
+    command_history_repository::insert_command_record(
+        conn,
+        user_id,
+        command_text,
+        intent_name,
+        outcome.is_ok(),
+        duration_ms,
+        result,
     )?;
     Ok(())
 }
@@ -1,13 +1,231 @@
-use rusqlite::{Connection, Result as SqliteResult};
+use rusqlite::{params, Connection, Result as SqliteResult};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
 
 /// Database initialization and migration management
 /// Handles creation of all tables and indexes for the modular memory architecture
 
-pub fn initialize_database(conn: &Connection) -> SqliteResult<()> {
+/// A numbered schema change. Each one runs in its own transaction together
+/// with its `schema_migrations` row, so a failure leaves the previous version intact.
+struct Migration {
+    version: i64,
+    name: &'static str,
+    up: fn(&Connection) -> SqliteResult<()>,
+}
+
+/// Every schema change in apply order. Append new entries; never edit or
+/// renumber one that has shipped.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        up: migration_001_initial_schema,
+    },
+    Migration {
+        version: 2,
+        name: "memories_fts",
+        up: create_memories_fts_table,
+    },
+    Migration {
+        version: 3,
+        name: "scope_tags_per_user",
+        up: migration_003_scope_tags_per_user,
+    },
+    Migration {
+        version: 4,
+        name: "reminder_recurrence",
+        up: migration_004_reminder_recurrence,
+    },
+    Migration {
+        version: 5,
+        name: "background_task_retries",
+        up: migration_005_background_task_retries,
+    },
+    Migration {
+        version: 6,
+        name: "reminder_fire_tracking",
+        up: migration_006_reminder_fire_tracking,
+    },
+    Migration {
+        version: 7,
+        name: "pending_plans",
+        up: migration_007_pending_plans,
+    },
+    Migration {
+        version: 8,
+        name: "action_journal",
+        up: migration_008_action_journal,
+    },
+    Migration {
+        version: 9,
+        name: "pending_plan_choices",
+        up: migration_009_pending_plan_choices,
+    },
+    Migration {
+        version: 10,
+        name: "permission_policies",
+        up: migration_010_permission_policies,
+    },
+    Migration {
+        version: 11,
+        name: "audit_log",
+        up: migration_011_audit_log,
+    },
+    Migration {
+        version: 12,
+        name: "login_attempts",
+        up: migration_012_login_attempts,
+    },
+    Migration {
+        version: 13,
+        name: "admin_users",
+        up: migration_013_admin_users,
+    },
+    Migration {
+        version: 14,
+        name: "session_devices",
+        up: migration_014_session_devices,
+    },
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingMigration {
+    pub version: i64,
+    pub name: String,
+}
+
+/// Dry-run view of the schema: what is applied and what would run next.
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationPlan {
+    pub current_version: i64,
+    pub latest_version: i64,
+    pub pending: Vec<PendingMigration>,
+}
+
+pub fn latest_schema_version() -> i64 {
+    MIGRATIONS.last().map(|migration| migration.version).unwrap_or(0)
+}
+
+pub fn initialize_database(conn: &Connection) -> Result<(), String> {
+    // Refuses to continue if the file was written by a newer build
+    let plan = plan_migrations(conn)?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at INTEGER NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create schema_migrations table: {}", e))?;
+
+    // Table rebuilds (DROP + RENAME) must not fire ON DELETE CASCADE, so
+    // migrations run with enforcement off. It cannot be toggled inside a transaction.
+    conn.execute("PRAGMA foreign_keys = OFF", [])
+        .map_err(|e| format!("Failed to disable foreign keys: {}", e))?;
+
+    let result = plan
+        .pending
+        .iter()
+        .filter_map(|pending| MIGRATIONS.iter().find(|m| m.version == pending.version))
+        .try_for_each(|migration| apply_migration(conn, migration));
+
     // Enable foreign keys
-    conn.execute("PRAGMA foreign_keys = ON", [])?;
-    
-    // Run all migrations in order
+    conn.execute("PRAGMA foreign_keys = ON", [])
+        .map_err(|e| format!("Failed to enable foreign keys: {}", e))?;
+    result?;
+
+    println!("✓ Database schema at version {}", plan.latest_version);
+    Ok(())
+}
+
+/// Report pending migrations without touching the database.
+pub fn plan_migrations(conn: &Connection) -> Result<MigrationPlan, String> {
+    let applied = applied_migration_versions(conn)?;
+    let latest_version = latest_schema_version();
+    let current_version = applied.iter().copied().max().unwrap_or(0);
+
+    if current_version > latest_version {
+        return Err(format!(
+            "Database schema version {} is newer than this build supports (version {}). Update Noddy before opening this database.",
+            current_version, latest_version
+        ));
+    }
+
+    let pending = MIGRATIONS
+        .iter()
+        .filter(|migration| !applied.contains(&migration.version))
+        .map(|migration| PendingMigration {
+            version: migration.version,
+            name: migration.name.to_string(),
+        })
+        .collect();
+
+    Ok(MigrationPlan {
+        current_version,
+        latest_version,
+        pending,
+    })
+}
+
+fn applied_migration_versions(conn: &Connection) -> Result<HashSet<i64>, String> {
+    let table_exists: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'schema_migrations'",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to inspect schema_migrations: {}", e))?;
+
+    if table_exists == 0 {
+        return Ok(HashSet::new());
+    }
+
+    let mut stmt = conn
+        .prepare("SELECT version FROM schema_migrations")
+        .map_err(|e| format!("Failed to read schema_migrations: {}", e))?;
+
+    let versions = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| format!("Failed to read schema_migrations: {}", e))?
+        .collect::<Result<HashSet<i64>, _>>()
+        .map_err(|e| format!("Failed to read schema_migrations: {}", e))?;
+
+    Ok(versions)
+}
+
+fn apply_migration(conn: &Connection, migration: &Migration) -> Result<(), String> {
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Failed to start migration {}: {}", migration.version, e))?;
+
+    (migration.up)(&tx).map_err(|e| {
+        format!(
+            "Migration {} ({}) failed and was rolled back: {}",
+            migration.version, migration.name, e
+        )
+    })?;
+
+    tx.execute(
+        "INSERT INTO schema_migrations (version, name, applied_at) VALUES (?1, ?2, ?3)",
+        params![migration.version, migration.name, current_timestamp()],
+    )
+    .map_err(|e| format!("Failed to record migration {}: {}", migration.version, e))?;
+
+    tx.commit()
+        .map_err(|e| format!("Failed to commit migration {}: {}", migration.version, e))?;
+
+    println!("✓ Applied migration {:03}_{}", migration.version, migration.name);
+    Ok(())
+}
+
+/// Version 1: the schema as it existed before migrations were tracked.
+/// Databases created by older builds are adopted here, so every step must be
+/// safe to run against a partially upgraded file.
+fn migration_001_initial_schema(conn: &Connection) -> SqliteResult<()> {
     create_memories_table(conn)?;
     create_memory_tags_table(conn)?;
     create_memory_tag_links_table(conn)?;
@@ -22,11 +240,7 @@ pub fn initialize_database(conn: &Connection) -> SqliteResult<()> {
     create_sessions_table(conn)?;
     create_integrations_table(conn)?;
     migrate_user_ownership_columns(conn)?;
-    
-    // Create all indexes
     create_indexes(conn)?;
-    
-    println!("✓ Database schema initialized with 7 tables and 15+ indexes");
     Ok(())
 }
 
@@ -210,7 +424,7 @@ fn create_chat_messages_table(conn: &Connection) -> SqliteResult<()> {
     Ok(())
 }
 
-/// Memory embeddings table: Reserved for future semantic search
+/// Memory embeddings table: Per-memory vectors used for semantic search
 fn create_memory_embeddings_table(conn: &Connection) -> SqliteResult<()> {
     conn.execute(
         "CREATE TABLE IF NOT EXISTS memory_embeddings (
@@ -278,6 +492,338 @@ fn create_integrations_table(conn: &Connection) -> SqliteResult<()> {
     Ok(())
 }
 
+/// Version 2 - Memories full-text index: FTS5 mirror of memory content kept in sync by triggers
+fn create_memories_fts_table(conn: &Connection) -> SqliteResult<()> {
+    let already_exists: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'memories_fts'",
+        [],
+        |row| row.get(0),
+    )?;
+
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS memories_fts USING fts5(
+            content,
+            memory_id UNINDEXED,
+            user_id UNINDEXED,
+            tokenize = 'porter unicode61'
+        )",
+        [],
+    )?;
+
+    conn.execute_batch(
+        "CREATE TRIGGER IF NOT EXISTS memories_fts_after_insert AFTER INSERT ON memories BEGIN
+            INSERT INTO memories_fts (content, memory_id, user_id)
+            VALUES (new.content, CAST(new.id AS TEXT), new.user_id);
+         END;
+
+         CREATE TRIGGER IF NOT EXISTS memories_fts_after_delete AFTER DELETE ON memories BEGIN
+            DELETE FROM memories_fts WHERE memory_id = CAST(old.id AS TEXT);
+         END;
+
+         CREATE TRIGGER IF NOT EXISTS memories_fts_after_update AFTER UPDATE OF content, user_id ON memories BEGIN
+            DELETE FROM memories_fts WHERE memory_id = CAST(old.id AS TEXT);
+            INSERT INTO memories_fts (content, memory_id, user_id)
+            VALUES (new.content, CAST(new.id AS TEXT), new.user_id);
+         END;",
+    )?;
+
+    // Backfill rows written before the index existed
+    if already_exists == 0 {
+        conn.execute(
+            "INSERT INTO memories_fts (content, memory_id, user_id)
+             SELECT content, CAST(id AS TEXT), user_id FROM memories WHERE content IS NOT NULL",
+            [],
+        )?;
+    }
+
+    println!("✓ memories_fts index ready");
+    Ok(())
+}
+
+/// Version 3 - Per-user tags: rebuilds memory_tags with UNIQUE(user_id, tag).
+/// A tag label shared by several accounts is split into one row per owner.
+fn migration_003_scope_tags_per_user(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE memory_tags_scoped (
+            id TEXT PRIMARY KEY,
+            user_id TEXT,
+            tag TEXT NOT NULL,
+            color TEXT,
+            created_at INTEGER,
+            UNIQUE (user_id, tag)
+        )",
+        [],
+    )?;
+
+    let tags: Vec<(String, String, Option<String>, Option<i64>)> = {
+        let mut stmt = conn.prepare("SELECT id, tag, color, created_at FROM memory_tags")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?;
+        rows.collect::<SqliteResult<Vec<_>>>()?
+    };
+
+    for (tag_id, tag, color, created_at) in tags {
+        let owners: Vec<Option<String>> = {
+            let mut stmt = conn.prepare(
+                "SELECT DISTINCT m.user_id
+                 FROM memory_tag_links l
+                 JOIN memories m ON CAST(m.id AS TEXT) = l.memory_id
+                 WHERE l.tag_id = ?1
+                 ORDER BY m.user_id",
+            )?;
+            let rows = stmt.query_map(params![tag_id], |row| row.get(0))?;
+            rows.collect::<SqliteResult<Vec<_>>>()?
+        };
+
+        // Unused tags stay unowned until an account claims local data
+        if owners.is_empty() {
+            conn.execute(
+                "INSERT INTO memory_tags_scoped (id, user_id, tag, color, created_at) VALUES (?1, NULL, ?2, ?3, ?4)",
+                params![tag_id, tag, color, created_at],
+            )?;
+            continue;
+        }
+
+        for (index, owner) in owners.iter().enumerate() {
+            let scoped_id = if index == 0 {
+                tag_id.clone()
+            } else {
+                Uuid::new_v4().to_string()
+            };
+
+            conn.execute(
+                "INSERT INTO memory_tags_scoped (id, user_id, tag, color, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![scoped_id, owner, tag, color, created_at],
+            )?;
+
+            if index > 0 {
+                conn.execute(
+                    "UPDATE memory_tag_links SET tag_id = ?1
+                     WHERE tag_id = ?2
+                       AND memory_id IN (SELECT CAST(id AS TEXT) FROM memories WHERE user_id IS ?3)",
+                    params![scoped_id, tag_id, owner],
+                )?;
+            }
+        }
+    }
+
+    conn.execute_batch(
+        "DROP TABLE memory_tags;
+         ALTER TABLE memory_tags_scoped RENAME TO memory_tags;",
+    )?;
+
+    println!("✓ memory_tags scoped per user");
+    Ok(())
+}
+
+/// Version 4 - Recurring reminders: JSON repeat rule, NULL for one-off reminders.
+fn migration_004_reminder_recurrence(conn: &Connection) -> SqliteResult<()> {
+    ensure_column(conn, "reminders", "recurrence", "TEXT")
+}
+
+/// Version 5 - Retry bookkeeping for background_tasks. Tasks that already
+/// failed under the old single-shot policy become dead letters.
+fn migration_005_background_task_retries(conn: &Connection) -> SqliteResult<()> {
+    ensure_column(conn, "background_tasks", "attempts", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "background_tasks", "last_error", "TEXT")?;
+    ensure_column(conn, "background_tasks", "next_attempt_at", "INTEGER")?;
+
+    conn.execute(
+        "UPDATE background_tasks SET status = 'dead_letter' WHERE status = 'failed'",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_background_tasks_status_next_attempt_at ON background_tasks(status, next_attempt_at ASC)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Version 6 - When a reminder actually fired and how far past trigger_at that was.
+fn migration_006_reminder_fire_tracking(conn: &Connection) -> SqliteResult<()> {
+    ensure_column(conn, "reminders", "fired_at", "INTEGER")?;
+    ensure_column(conn, "reminders", "lateness_seconds", "INTEGER")
+}
+
+/// Version 7 - Action plans paused on a step that needs the user's confirmation.
+fn migration_007_pending_plans(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS pending_plans (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            user_message TEXT NOT NULL,
+            plan_json TEXT NOT NULL,
+            next_step INTEGER NOT NULL,
+            responses_json TEXT NOT NULL DEFAULT '[]',
+            status TEXT NOT NULL DEFAULT 'pending',
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_pending_plans_user_status ON pending_plans(user_id, status)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Version 8 - Inverse operations for undoing AI-executed actions, and
+/// soft-deleted memories that stay restorable for a retention window.
+fn migration_008_action_journal(conn: &Connection) -> SqliteResult<()> {
+    ensure_column(conn, "memories", "deleted_at", "INTEGER")?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS action_journal (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            plan_id TEXT NOT NULL,
+            intent TEXT NOT NULL,
+            inverse_json TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            undone_at INTEGER,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_action_journal_user_created ON action_journal(user_id, created_at DESC)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_memories_user_deleted ON memories(user_id, deleted_at)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Version 9 - Candidates a paused plan step offers when its target was ambiguous.
+fn migration_009_pending_plan_choices(conn: &Connection) -> SqliteResult<()> {
+    ensure_column(conn, "pending_plans", "choices_json", "TEXT")
+}
+
+/// Version 10 - Per-user allow/deny/ask policy for each capability, and an
+/// audit trail of every permission decision.
+fn migration_010_permission_policies(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS permission_policies (
+            user_id TEXT NOT NULL,
+            capability TEXT NOT NULL,
+            policy TEXT NOT NULL CHECK (policy IN ('allow', 'deny', 'ask')),
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (user_id, capability),
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS permission_decisions (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            capability TEXT NOT NULL,
+            decision TEXT NOT NULL,
+            source TEXT NOT NULL,
+            intent TEXT,
+            created_at INTEGER NOT NULL,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_permission_decisions_user_created
+         ON permission_decisions(user_id, created_at DESC)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Version 11 - Security audit trail. Rows are hash-chained and triggers refuse
+/// updates and deletes; there is no foreign key so entries outlive the account
+/// they name.
+fn migration_011_audit_log(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id TEXT,
+            action TEXT NOT NULL,
+            target TEXT,
+            outcome TEXT NOT NULL,
+            detail TEXT,
+            client_info TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            prev_hash TEXT NOT NULL,
+            hash TEXT NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_audit_log_user_created
+         ON audit_log(user_id, created_at DESC)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TRIGGER IF NOT EXISTS audit_log_no_update
+         BEFORE UPDATE ON audit_log
+         BEGIN SELECT RAISE(ABORT, 'audit_log is append-only'); END",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TRIGGER IF NOT EXISTS audit_log_no_delete
+         BEFORE DELETE ON audit_log
+         BEGIN SELECT RAISE(ABORT, 'audit_log is append-only'); END",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Version 12 - Failed logins are tracked per email, whether or not an account
+/// exists, so throttling does not reveal which emails are registered.
+fn migration_012_login_attempts(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS login_attempts (
+            email TEXT PRIMARY KEY,
+            failed_count INTEGER NOT NULL DEFAULT 0,
+            last_failed_at INTEGER,
+            locked_until INTEGER
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Version 13 - Marks the earliest account as the admin who can view other
+/// accounts' login lockouts.
+fn migration_013_admin_users(conn: &Connection) -> SqliteResult<()> {
+    ensure_column(conn, "users", "is_admin", "INTEGER NOT NULL DEFAULT 0")?;
+    conn.execute(
+        "UPDATE users SET is_admin = 1
+         WHERE id = (SELECT id FROM users ORDER BY created_at ASC, rowid ASC LIMIT 1)
+           AND NOT EXISTS (SELECT 1 FROM users WHERE is_admin = 1)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Version 14 - Device label and last use for each session, so users can see
+/// where they are signed in.
+fn migration_014_session_devices(conn: &Connection) -> SqliteResult<()> {
+    ensure_column(conn, "sessions", "device_label", "TEXT")?;
+    ensure_column(conn, "sessions", "last_used_at", "INTEGER")?;
+    conn.execute(
+        "UPDATE sessions SET last_used_at = created_at WHERE last_used_at IS NULL",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Pre-versioning databases may already have some of these columns
 fn migrate_user_ownership_columns(conn: &Connection) -> SqliteResult<()> {
     ensure_column(conn, "memories", "user_id", "TEXT")?;
     ensure_column(conn, "memories", "access_count", "INTEGER DEFAULT 0")?;
@@ -547,9 +1093,11 @@ pub fn verify_database(conn: &Connection) -> SqliteResult<()> {
         "reminders",
         "command_history",
         "memory_embeddings",
+        "memories_fts",
         "users",
         "sessions",
         "integrations",
+        "schema_migrations",
     ];
     
     for table in expected_tables {
@@ -567,3 +1115,121 @@ pub fn verify_database(conn: &Connection) -> SqliteResult<()> {
     println!("✓ Database integrity verified");
     Ok(())
 }
+
+fn current_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorded_versions(conn: &Connection) -> Vec<i64> {
+        let mut stmt = conn
+            .prepare("SELECT version FROM schema_migrations ORDER BY version")
+            .unwrap();
+        let versions = stmt
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<Vec<i64>, _>>()
+            .unwrap();
+        versions
+    }
+
+    fn table_exists(conn: &Connection, name: &str) -> bool {
+        conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            params![name],
+            |row| row.get::<_, i64>(0),
+        )
+        .unwrap()
+            > 0
+    }
+
+    #[test]
+    fn baseline_database_runs_every_migration_once() {
+        let conn = Connection::open_in_memory().unwrap();
+        // Shape written by builds from before migrations were tracked
+        conn.execute_batch(
+            "CREATE TABLE memories (
+                id TEXT PRIMARY KEY,
+                user_id TEXT,
+                content TEXT NOT NULL,
+                created_at INTEGER,
+                updated_at INTEGER,
+                importance REAL DEFAULT 0.5,
+                access_count INTEGER DEFAULT 0,
+                last_accessed_at INTEGER,
+                source TEXT,
+                tags TEXT,
+                metadata TEXT
+            );
+            CREATE TABLE users (
+                id TEXT PRIMARY KEY,
+                email TEXT UNIQUE NOT NULL,
+                password_hash TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            );
+            INSERT INTO users (id, email, password_hash, created_at) VALUES ('u1', 'u1@example.com', 'hash', 0);
+            INSERT INTO memories (id, user_id, content, created_at) VALUES ('m1', 'u1', 'Water the ferns', 0);",
+        )
+        .unwrap();
+
+        initialize_database(&conn).unwrap();
+        initialize_database(&conn).unwrap();
+
+        let expected: Vec<i64> = MIGRATIONS.iter().map(|migration| migration.version).collect();
+        assert_eq!(recorded_versions(&conn), expected);
+        assert!(plan_migrations(&conn).unwrap().pending.is_empty());
+        let indexed: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM memories_fts WHERE memories_fts MATCH 'ferns'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexed, 1);
+    }
+
+    fn broken_migration(conn: &Connection) -> SqliteResult<()> {
+        conn.execute("CREATE TABLE half_done (id INTEGER PRIMARY KEY)", [])?;
+        conn.execute("INSERT INTO missing_table (id) VALUES (1)", [])?;
+        Ok(())
+    }
+
+    #[test]
+    fn failing_migration_is_rolled_back() {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_database(&conn).unwrap();
+        let broken = Migration {
+            version: latest_schema_version() + 1,
+            name: "broken",
+            up: broken_migration,
+        };
+
+        let error = apply_migration(&conn, &broken).unwrap_err();
+
+        assert!(error.contains("rolled back"), "{}", error);
+        assert!(!table_exists(&conn, "half_done"));
+        assert_eq!(recorded_versions(&conn).last(), Some(&latest_schema_version()));
+    }
+
+    #[test]
+    fn newer_database_is_refused() {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_database(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO schema_migrations (version, name, applied_at) VALUES (?1, 'from_the_future', 0)",
+            params![latest_schema_version() + 1],
+        )
+        .unwrap();
+
+        let error = initialize_database(&conn).unwrap_err();
+
+        assert!(error.contains("newer"), "{}", error);
+        assert!(plan_migrations(&conn).is_err());
+    }
+}
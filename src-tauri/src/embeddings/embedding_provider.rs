@@ -0,0 +1,154 @@
+use std::collections::HashSet;
+use std::sync::{Arc, OnceLock};
+
+/// Produces fixed-size vector representations of memory text.
+/// Implementations must be deterministic so stored vectors stay comparable.
+pub trait EmbeddingProvider: Send + Sync {
+    /// Identifier persisted next to every stored vector.
+    fn model_name(&self) -> &str;
+    fn dimensions(&self) -> usize;
+    fn embed(&self, text: &str) -> Vec<f32>;
+}
+
+const HASHED_BOW_DIMENSIONS: usize = 512;
+
+/// Offline default: feature-hashed bag of words with light stemming.
+/// Needs no model files and works on every install.
+pub struct HashedBagOfWordsProvider {
+    dimensions: usize,
+}
+
+impl HashedBagOfWordsProvider {
+    pub fn new() -> Self {
+        Self {
+            dimensions: HASHED_BOW_DIMENSIONS,
+        }
+    }
+}
+
+impl EmbeddingProvider for HashedBagOfWordsProvider {
+    fn model_name(&self) -> &str {
+        "hashed-bow-512-v1"
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn embed(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0_f32; self.dimensions];
+        let terms = embedding_terms(text);
+
+        for term in &terms {
+            let hash = fnv1a(term.as_bytes());
+            let bucket = (hash % self.dimensions as u64) as usize;
+            let sign = if (hash >> 63) & 1 == 0 { 1.0 } else { -1.0 };
+            vector[bucket] += sign;
+        }
+
+        // Adjacent word pairs keep phrases like "big data" closer than the words alone.
+        for pair in terms.windows(2) {
+            let hash = fnv1a(format!("{} {}", pair[0], pair[1]).as_bytes());
+            let bucket = (hash % self.dimensions as u64) as usize;
+            let sign = if (hash >> 63) & 1 == 0 { 0.5 } else { -0.5 };
+            vector[bucket] += sign;
+        }
+
+        normalize(&mut vector);
+        vector
+    }
+}
+
+/// Returns the provider used for all memory embeddings.
+pub fn active_provider() -> Arc<dyn EmbeddingProvider> {
+    static PROVIDER: OnceLock<Arc<dyn EmbeddingProvider>> = OnceLock::new();
+    PROVIDER
+        .get_or_init(|| Arc::new(HashedBagOfWordsProvider::new()))
+        .clone()
+}
+
+pub fn cosine_similarity(left: &[f32], right: &[f32]) -> f64 {
+    if left.is_empty() || left.len() != right.len() {
+        return 0.0;
+    }
+
+    let mut dot = 0.0_f64;
+    let mut left_norm = 0.0_f64;
+    let mut right_norm = 0.0_f64;
+    for (l, r) in left.iter().zip(right.iter()) {
+        dot += (*l as f64) * (*r as f64);
+        left_norm += (*l as f64) * (*l as f64);
+        right_norm += (*r as f64) * (*r as f64);
+    }
+
+    if left_norm == 0.0 || right_norm == 0.0 {
+        0.0
+    } else {
+        dot / (left_norm.sqrt() * right_norm.sqrt())
+    }
+}
+
+pub fn is_zero_vector(vector: &[f32]) -> bool {
+    vector.iter().all(|value| *value == 0.0)
+}
+
+fn embedding_terms(text: &str) -> Vec<String> {
+    let stopwords: HashSet<&'static str> = [
+        "a", "about", "an", "and", "are", "as", "at", "be", "by", "did", "do", "does", "for",
+        "from", "has", "have", "i", "in", "is", "it", "me", "my", "of", "on", "or", "said",
+        "say", "tell", "that", "the", "this", "to", "told", "was", "we", "what", "when",
+        "where", "which", "who", "why", "with", "you", "your",
+    ]
+    .into_iter()
+    .collect();
+
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| token.len() >= 2 && !stopwords.contains(token))
+        .map(stem)
+        .collect()
+}
+
+fn stem(token: &str) -> String {
+    let len = token.len();
+    if len > 5 {
+        if let Some(base) = token.strip_suffix("ies") {
+            return format!("{}y", base);
+        }
+        if let Some(base) = token.strip_suffix("ing") {
+            return base.to_string();
+        }
+    }
+    if len > 4 {
+        if let Some(base) = token.strip_suffix("ed") {
+            return base.to_string();
+        }
+        if let Some(base) = token.strip_suffix("es") {
+            if ["s", "x", "z", "ch", "sh"].iter().any(|end| base.ends_with(end)) {
+                return base.to_string();
+            }
+        }
+    }
+    if len > 3 && token.ends_with('s') && !token.ends_with("ss") {
+        return token[..len - 1].to_string();
+    }
+    token.to_string()
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf29ce484222325_u64;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for value in vector.iter_mut() {
+            *value /= norm;
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use rusqlite::{params, Connection};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::embedding_provider::{self, EmbeddingProvider};
+
+const EMBEDDING_VERSION: i64 = 1;
+
+/// A memory row paired with its stored vector (if the vector matches the active model).
+#[derive(Debug, Clone)]
+pub struct EmbeddedMemory {
+    pub id: String,
+    pub content: String,
+    pub created_at: i64,
+    pub importance: f64,
+    pub source: String,
+    pub embedding: Option<Vec<f32>>,
+}
+
+/// Compute and persist the embedding for a memory using the active provider.
+pub fn upsert_memory_embedding(conn: &Connection, memory_id: &str, content: &str) -> Result<(), String> {
+    let provider = embedding_provider::active_provider();
+    let vector = provider.embed(content);
+    write_embedding(conn, memory_id, &vector, provider.as_ref())
+}
+
+/// Load every memory for a user together with any embedding produced by the active model.
+/// Missing or stale vectors are recomputed and stored so later searches stay cheap.
+pub fn load_user_embeddings(conn: &Connection, user_id: &str) -> Result<Vec<EmbeddedMemory>, String> {
+    let provider = embedding_provider::active_provider();
+    let mut stmt = conn
+        .prepare(
+            "SELECT CAST(m.id AS TEXT),
+                    m.content,
+                    COALESCE(m.created_at, CAST(strftime('%s', 'now') AS INTEGER)),
+                    COALESCE(m.importance, 0.5),
+                    COALESCE(m.source, 'user_input'),
+                    e.embedding,
+                    e.model
+             FROM memories m
+             LEFT JOIN memory_embeddings e ON e.memory_id = CAST(m.id AS TEXT)
+             WHERE m.user_id = ?1 AND m.content IS NOT NULL AND m.deleted_at IS NULL",
+        )
+        .map_err(|e| format!("Failed to prepare embedding query: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![user_id], |row| {
+            Ok((
+                EmbeddedMemory {
+                    id: row.get(0)?,
+                    content: row.get(1)?,
+                    created_at: row.get(2)?,
+                    importance: row.get(3)?,
+                    source: row.get(4)?,
+                    embedding: None,
+                },
+                row.get::<_, Option<Vec<u8>>>(5)?,
+                row.get::<_, Option<String>>(6)?,
+            ))
+        })
+        .map_err(|e| format!("Failed to query memory embeddings: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to map memory embeddings: {}", e))?;
+
+    let mut memories = Vec::with_capacity(rows.len());
+    for (mut memory, blob, model) in rows {
+        let stored = match (blob, model) {
+            (Some(bytes), Some(model)) if model == provider.model_name() => {
+                Some(decode_vector(&bytes)).filter(|v| v.len() == provider.dimensions())
+            }
+            _ => None,
+        };
+
+        memory.embedding = match stored {
+            Some(vector) => Some(vector),
+            None => {
+                let vector = provider.embed(&memory.content);
+                write_embedding(conn, &memory.id, &vector, provider.as_ref())?;
+                Some(vector)
+            }
+        };
+        memories.push(memory);
+    }
+
+    Ok(memories)
+}
+
+pub fn embed_query(query: &str) -> Vec<f32> {
+    embedding_provider::active_provider().embed(query)
+}
+
+fn write_embedding(
+    conn: &Connection,
+    memory_id: &str,
+    vector: &[f32],
+    provider: &dyn EmbeddingProvider,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO memory_embeddings (memory_id, embedding, model, created_at, version)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(memory_id) DO UPDATE SET
+            embedding = excluded.embedding,
+            model = excluded.model,
+            created_at = excluded.created_at,
+            version = excluded.version",
+        params![
+            memory_id,
+            encode_vector(vector),
+            provider.model_name(),
+            current_timestamp(),
+            EMBEDDING_VERSION
+        ],
+    )
+    .map_err(|e| format!("Failed to store memory embedding: {}", e))?;
+
+    Ok(())
+}
+
+fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
+fn current_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_store;
+
+    fn database_with_memories(user_id: &str, contents: &[&str]) -> (Connection, Vec<String>) {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::database::initialize_database(&conn).unwrap();
+        let mut ids = Vec::new();
+        for (index, content) in contents.iter().enumerate() {
+            let id = format!("m{}", index);
+            conn.execute(
+                "INSERT INTO memories (id, user_id, content, created_at, updated_at, source)
+                 VALUES (?1, ?2, ?3, 0, 0, 'user_input')",
+                params![id, user_id, content],
+            )
+            .unwrap();
+            upsert_memory_embedding(&conn, &id, content).unwrap();
+            ids.push(id);
+        }
+        (conn, ids)
+    }
+
+    fn stored_vector(conn: &Connection, memory_id: &str) -> Vec<f32> {
+        let blob: Vec<u8> = conn
+            .query_row(
+                "SELECT embedding FROM memory_embeddings WHERE memory_id = ?1",
+                params![memory_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        decode_vector(&blob)
+    }
+
+    #[test]
+    fn related_memory_ranks_above_unrelated_one() {
+        let (conn, ids) = database_with_memories(
+            "u1",
+            &["Buy milk and eggs", "My database exam is on March 12"],
+        );
+
+        let results =
+            memory_store::search_memories(&conn, "u1", "what did I say about the exams".into(), 5).unwrap();
+
+        assert_eq!(results.first().map(|memory| memory.id.as_str()), Some(ids[1].as_str()));
+        assert!(results.iter().all(|memory| memory.id != ids[0]));
+    }
+
+    #[test]
+    fn edited_memory_gets_a_fresh_embedding() {
+        let (conn, ids) = database_with_memories("u1", &["Buy milk and eggs"]);
+        let before = stored_vector(&conn, &ids[0]);
+
+        memory_store::update_memory_content(&conn, "u1", &ids[0], "Dentist appointment on Friday").unwrap();
+
+        let after = stored_vector(&conn, &ids[0]);
+        assert_ne!(before, after);
+        assert_eq!(after, embed_query("Dentist appointment on Friday"));
+    }
+}
@@ -0,0 +1,2 @@
+pub mod embedding_provider;
+pub mod embedding_store;
@@ -12,16 +12,25 @@ mod database;
 
 // Service modules for CRUD operations
 mod memory_store;
+mod memory_tag_service;
 mod reminder_store;
+mod reminder_recurrence;
 mod history_store;
 mod chat_history_store;
+mod pending_plan_store;
+mod action_journal;
+mod permission_policy_store;
+mod audit_log;
 mod memory_graph_repository;
 mod memory_intelligence_service;
 mod plugin_interface;
 mod plugin_registry;
 mod scheduler;
+mod task_registry;
+mod maintenance_tasks;
 mod worker;
 mod auth_service;
+mod user_data_archive;
 
 // Command History architecture (repository + service layers)
 mod command_history_repository;
@@ -33,6 +42,9 @@ mod system;
 // Proactive suggestion engine layer
 mod suggestions;
 
+// Embedding providers and vector storage for semantic memory search
+mod embeddings;
+
 // AI module for LLM chat integration
 mod ai;
 
@@ -160,7 +172,8 @@ fn create_telemetry_subscriber() -> impl Fn(&Event) + Send + Sync + 'static {
 // ============================================================================
 // INFRASTRUCTURE LAYER 2: CAPABILITY PERMISSION LAYER
 // ============================================================================
-// Defines what actions are allowed (future: safe mode, plugin restrictions).
+// Defines what actions are allowed. Capabilities covered by the user-facing
+// permissions file follow it; the rest are always allowed.
 // ============================================================================
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -171,29 +184,148 @@ enum Capability {
     MemoryRead,
     MemoryWrite,
     ReminderSchedule,
+    PluginAccess,
+    RunningApps,
+    BackgroundSuggestions,
+}
+
+impl Capability {
+    const ALL: [Capability; 9] = [
+        Capability::OpenApp,
+        Capability::KillProcess,
+        Capability::WebSearch,
+        Capability::MemoryRead,
+        Capability::MemoryWrite,
+        Capability::ReminderSchedule,
+        Capability::PluginAccess,
+        Capability::RunningApps,
+        Capability::BackgroundSuggestions,
+    ];
+
+    /// Stable name used in the database and by the frontend.
+    fn as_str(&self) -> &'static str {
+        match self {
+            Capability::OpenApp => "open_app",
+            Capability::KillProcess => "kill_process",
+            Capability::WebSearch => "web_search",
+            Capability::MemoryRead => "memory_read",
+            Capability::MemoryWrite => "memory_write",
+            Capability::ReminderSchedule => "reminder_schedule",
+            Capability::PluginAccess => "plugin_access",
+            Capability::RunningApps => "running_apps",
+            Capability::BackgroundSuggestions => "background_suggestions",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Capability> {
+        Capability::ALL.into_iter().find(|capability| capability.as_str() == name)
+    }
 }
 
+/// Shared by every clone, so `update_user_permission` takes effect for all
+/// holders without a restart.
 #[derive(Clone)]
 struct PermissionManager {
-    allowed: std::collections::HashSet<Capability>,
+    allowed: std::sync::Arc<std::sync::RwLock<std::collections::HashSet<Capability>>>,
+    /// "Allow for this session" answers to ask-policy prompts, until logout or restart.
+    session_grants: std::sync::Arc<std::sync::RwLock<std::collections::HashSet<(String, Capability)>>>,
 }
 
 impl PermissionManager {
+    /// Every capability allowed; for tests.
+    #[cfg(test)]
     fn default_permissions() -> Self {
         let mut allowed = std::collections::HashSet::new();
-        // Default: all capabilities allowed
         allowed.insert(Capability::OpenApp);
         allowed.insert(Capability::KillProcess);
         allowed.insert(Capability::WebSearch);
         allowed.insert(Capability::MemoryRead);
         allowed.insert(Capability::MemoryWrite);
         allowed.insert(Capability::ReminderSchedule);
-        
-        PermissionManager { allowed }
+        allowed.insert(Capability::PluginAccess);
+        allowed.insert(Capability::RunningApps);
+        allowed.insert(Capability::BackgroundSuggestions);
+
+        PermissionManager {
+            allowed: std::sync::Arc::new(std::sync::RwLock::new(allowed)),
+            session_grants: Default::default(),
+        }
     }
-    
+
+    fn from_user_permissions(user_permissions: &permissions_service::UserPermissions) -> Self {
+        let manager = PermissionManager {
+            allowed: Default::default(),
+            session_grants: Default::default(),
+        };
+        manager.apply_user_permissions(user_permissions);
+        manager
+    }
+
+    /// Replaces the allowed set with what the permissions file grants.
+    fn apply_user_permissions(&self, user_permissions: &permissions_service::UserPermissions) {
+        let mut allowed = std::collections::HashSet::new();
+        allowed.insert(Capability::MemoryRead);
+        allowed.insert(Capability::MemoryWrite);
+        allowed.insert(Capability::ReminderSchedule);
+        if user_permissions.launch_apps {
+            allowed.insert(Capability::OpenApp);
+            allowed.insert(Capability::KillProcess);
+        }
+        if user_permissions.network_access {
+            allowed.insert(Capability::WebSearch);
+        }
+        if user_permissions.plugin_access {
+            allowed.insert(Capability::PluginAccess);
+        }
+        if user_permissions.access_running_apps {
+            allowed.insert(Capability::RunningApps);
+        }
+        if user_permissions.background_suggestions {
+            allowed.insert(Capability::BackgroundSuggestions);
+        }
+
+        if let Ok(mut current) = self.allowed.write() {
+            *current = allowed;
+        }
+    }
+
+    #[cfg(test)]
+    fn revoke(&self, capability: Capability) {
+        if let Ok(mut allowed) = self.allowed.write() {
+            allowed.remove(&capability);
+        }
+    }
+
+    fn grant_for_session(&self, user_id: &str, capability: Capability) {
+        if let Ok(mut grants) = self.session_grants.write() {
+            grants.insert((user_id.to_string(), capability));
+        }
+    }
+
+    fn has_session_grant(&self, user_id: &str, capability: Capability) -> bool {
+        self.session_grants
+            .read()
+            .map(|grants| grants.contains(&(user_id.to_string(), capability)))
+            .unwrap_or(false)
+    }
+
+    fn clear_session_grant(&self, user_id: &str, capability: Capability) {
+        if let Ok(mut grants) = self.session_grants.write() {
+            grants.remove(&(user_id.to_string(), capability));
+        }
+    }
+
+    fn clear_session_grants(&self, user_id: &str) {
+        if let Ok(mut grants) = self.session_grants.write() {
+            grants.retain(|(grant_user, _)| grant_user != user_id);
+        }
+    }
+
     fn allows(&self, capability: Capability) -> bool {
-        self.allowed.contains(&capability)
+        self.allowed
+            .read()
+            .map(|allowed| allowed.contains(&capability))
+            .unwrap_or(false)
     }
     
     fn check_permission(&self, capability: Capability) -> Result<(), String> {
@@ -302,8 +434,22 @@ fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
-fn require_user_from_access_token(access_token: &str, auth: &AuthConfig) -> Result<String, String> {
-    auth_service::verify_access_token(access_token, &auth.jwt_secret)
+/// Must be called before the command takes the database lock.
+fn require_user_from_access_token(
+    access_token: &str,
+    auth: &AuthConfig,
+    memory_store: &MemoryStore,
+) -> Result<String, String> {
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    auth_service::verify_access_token(&conn, access_token, &auth.jwt_secret)
+}
+
+/// Label shown in the session list; the machine name unless the client sends one.
+fn device_label_or_default(device_label: Option<String>) -> String {
+    device_label
+        .map(|label| label.trim().to_string())
+        .filter(|label| !label.is_empty())
+        .unwrap_or_else(|| format!("{} ({})", audit_log::host_name(), std::env::consts::OS))
 }
 
 #[tauri::command]
@@ -312,9 +458,14 @@ fn signup(
     auth_config: tauri::State<AuthConfig>,
     email: String,
     password: String,
+    device_label: Option<String>,
 ) -> Result<serde_json::Value, String> {
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
-    let result = auth_service::signup(&conn, &email, &password, &auth_config.jwt_secret)?;
+    let device_label = device_label_or_default(device_label);
+    let result = auth_service::signup(&conn, &email, &password, &auth_config.jwt_secret, &device_label);
+    let user_id = result.as_ref().ok().map(|result| result.user.id.as_str());
+    audit_log::append_result(&conn, user_id, audit_log::action::SIGNUP, Some(email.trim()), &result)?;
+    let result = result?;
 
     Ok(serde_json::json!({
         "user": result.user,
@@ -328,9 +479,18 @@ fn login(
     auth_config: tauri::State<AuthConfig>,
     email: String,
     password: String,
+    device_label: Option<String>,
 ) -> Result<serde_json::Value, String> {
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
-    let result = auth_service::login(&conn, &email, &password, &auth_config.jwt_secret)?;
+    let device_label = device_label_or_default(device_label);
+    let result = auth_service::login(&conn, &email, &password, &auth_config.jwt_secret, &device_label);
+    // Failed attempts are filed under the account they targeted, when it exists
+    let user_id = match &result {
+        Ok(result) => Some(result.user.id.clone()),
+        Err(_) => auth_service::user_id_for_email(&conn, &email)?,
+    };
+    audit_log::append_result(&conn, user_id.as_deref(), audit_log::action::LOGIN, Some(email.trim()), &result)?;
+    let result = result?;
 
     Ok(serde_json::json!({
         "user": result.user,
@@ -345,7 +505,10 @@ fn refresh_token(
     refresh_token: String,
 ) -> Result<serde_json::Value, String> {
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
-    let tokens = auth_service::refresh(&conn, &refresh_token, &auth_config.jwt_secret)?;
+    let tokens = auth_service::refresh(&conn, &refresh_token, &auth_config.jwt_secret);
+    let user_id = tokens.as_ref().ok().map(|tokens| tokens.user_id.as_str());
+    audit_log::append_result(&conn, user_id, audit_log::action::REFRESH, None, &tokens)?;
+    let tokens = tokens?;
 
     Ok(serde_json::json!({ "tokens": tokens }))
 }
@@ -353,24 +516,179 @@ fn refresh_token(
 #[tauri::command]
 fn logout(
     memory_store: tauri::State<MemoryStore>,
+    permissions: tauri::State<PermissionManager>,
     refresh_token: String,
 ) -> Result<String, String> {
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
-    auth_service::logout(&conn, &refresh_token)?;
+    let user_id = auth_service::logout(&conn, &refresh_token);
+    let audited_user = user_id.as_ref().ok().and_then(|user_id| user_id.as_deref());
+    audit_log::append_result(&conn, audited_user, audit_log::action::LOGOUT, None, &user_id)?;
+    if let Some(user_id) = user_id? {
+        permissions.clear_session_grants(&user_id);
+    }
     Ok("Logged out".to_string())
 }
 
+/// Checks the current password, sets the new one and signs out every session.
+/// Returns tokens for a new session on this device.
+#[tauri::command]
+fn change_password(
+    memory_store: tauri::State<MemoryStore>,
+    permissions: tauri::State<PermissionManager>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+    current_password: String,
+    new_password: String,
+    device_label: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let device_label = device_label_or_default(device_label);
+    let tokens = auth_service::change_password(
+        &conn,
+        &user_id,
+        &current_password,
+        &new_password,
+        &auth_config.jwt_secret,
+        &device_label,
+    );
+    audit_log::append_result(&conn, Some(&user_id), audit_log::action::CHANGE_PASSWORD, None, &tokens)?;
+    let tokens = tokens?;
+    permissions.clear_session_grants(&user_id);
+
+    Ok(serde_json::json!({ "tokens": tokens }))
+}
+
+#[tauri::command]
+fn list_sessions(
+    memory_store: tauri::State<MemoryStore>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+) -> Result<Vec<auth_service::SessionInfo>, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let current_session = auth_service::session_id_from_token(&access_token, &auth_config.jwt_secret);
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    auth_service::list_sessions(&conn, &user_id, current_session.as_deref())
+}
+
+#[tauri::command]
+fn revoke_session(
+    memory_store: tauri::State<MemoryStore>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+    session_id: String,
+) -> Result<String, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let result = auth_service::revoke_session(&conn, &user_id, &session_id);
+    audit_log::append_result(&conn, Some(&user_id), audit_log::action::REVOKE_SESSION, Some(&session_id), &result)?;
+    result?;
+    Ok("Session revoked".to_string())
+}
+
+/// Signs out every device, including the one making the request.
+#[tauri::command]
+fn revoke_all_sessions(
+    memory_store: tauri::State<MemoryStore>,
+    permissions: tauri::State<PermissionManager>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+) -> Result<String, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let result = auth_service::revoke_all_sessions(&conn, &user_id);
+    audit_log::append_result(&conn, Some(&user_id), audit_log::action::REVOKE_ALL_SESSIONS, None, &result)?;
+    let revoked = result?;
+    permissions.clear_session_grants(&user_id);
+    Ok(format!("Signed out of {} session{}", revoked, if revoked == 1 { "" } else { "s" }))
+}
+
+/// The signed-in user's allow/deny/ask policy for every capability.
+#[tauri::command]
+fn get_permission_policies(
+    memory_store: tauri::State<MemoryStore>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+) -> Result<Vec<permission_policy_store::CapabilityPolicy>, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    permission_policy_store::list_policies(&conn, &user_id)
+}
+
+#[tauri::command]
+fn set_permission_policy(
+    memory_store: tauri::State<MemoryStore>,
+    permissions: tauri::State<PermissionManager>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+    capability: String,
+    policy: String,
+) -> Result<Vec<permission_policy_store::CapabilityPolicy>, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let capability = Capability::parse(&capability).ok_or_else(|| format!("Unknown capability: {}", capability))?;
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    permission_policy_store::set_policy(&conn, &user_id, capability, &policy)?;
+    // A new policy replaces any "allow for this session" answer
+    permissions.clear_session_grant(&user_id, capability);
+    permission_policy_store::list_policies(&conn, &user_id)
+}
+
+/// Audit trail of allow/deny/ask decisions, newest first.
+#[tauri::command]
+fn get_permission_decisions(
+    memory_store: tauri::State<MemoryStore>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+    limit: Option<i64>,
+) -> Result<Vec<permission_policy_store::PermissionDecisionRecord>, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    permission_policy_store::list_decisions(&conn, &user_id, limit.unwrap_or(100))
+}
+
+/// The signed-in user's security audit entries, newest first.
+#[tauri::command]
+fn get_audit_log(
+    memory_store: tauri::State<MemoryStore>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+    filter: Option<audit_log::AuditLogFilter>,
+) -> Result<Vec<audit_log::AuditEntry>, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    audit_log::list_entries(&conn, &user_id, &filter.unwrap_or_default())
+}
+
+/// Checks the audit log's hash chain for edited or removed entries.
+#[tauri::command]
+fn verify_audit_log(
+    memory_store: tauri::State<MemoryStore>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+) -> Result<audit_log::ChainVerification, String> {
+    require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    audit_log::verify_chain(&conn)
+}
+
 #[tauri::command]
 fn get_current_user(
     memory_store: tauri::State<MemoryStore>,
     auth_config: tauri::State<AuthConfig>,
     access_token: String,
+    email: Option<String>,
 ) -> Result<serde_json::Value, String> {
-    let user_id = require_user_from_access_token(&access_token, &auth_config)?;
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
     auth_service::claim_orphaned_data_for_user(&conn, &user_id)?;
     let user = auth_service::get_user_by_id(&conn, &user_id)?;
-    Ok(serde_json::json!({ "user": user }))
+    if !user.is_admin && email.is_none() {
+        return Ok(serde_json::json!({ "user": user }));
+    }
+    // Admins see recent failed logins and any lockout for `email`, or their own account
+    let email = email.unwrap_or_else(|| user.email.clone());
+    let login_throttle = auth_service::login_throttle_as_admin(&conn, &user_id, &email)?;
+    Ok(serde_json::json!({ "user": user, "login_throttle": login_throttle }))
 }
 
 struct InstalledApp {
@@ -401,7 +719,7 @@ fn get_plugins(
     auth_config: tauri::State<AuthConfig>,
     access_token: String,
 ) -> Result<Vec<plugin_registry::PluginRecord>, String> {
-    let _ = require_user_from_access_token(&access_token, &auth_config)?;
+    let _ = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
     plugin_registry::get_plugins(&conn, &plugin_registry)
 }
@@ -413,7 +731,7 @@ fn get_active_plugins(
     auth_config: tauri::State<AuthConfig>,
     access_token: String,
 ) -> Result<Vec<plugin_registry::PluginRecord>, String> {
-    let _ = require_user_from_access_token(&access_token, &auth_config)?;
+    let _ = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
     plugin_registry::get_active_plugins(&conn, &plugin_registry)
 }
@@ -426,7 +744,7 @@ fn enable_plugin(
     access_token: String,
     plugin_id: String,
 ) -> Result<plugin_registry::PluginRecord, String> {
-    let _ = require_user_from_access_token(&access_token, &auth_config)?;
+    let _ = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
     plugin_registry::enable_plugin(&conn, &plugin_registry, &plugin_id)
 }
@@ -438,7 +756,7 @@ fn disable_plugin(
     access_token: String,
     plugin_id: String,
 ) -> Result<String, String> {
-    let _ = require_user_from_access_token(&access_token, &auth_config)?;
+    let _ = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
     plugin_registry::disable_plugin(&conn, &plugin_id)?;
     Ok("Plugin disabled".to_string())
@@ -453,7 +771,7 @@ fn update_plugin_config(
     plugin_id: String,
     config_json: String,
 ) -> Result<plugin_registry::PluginRecord, String> {
-    let _ = require_user_from_access_token(&access_token, &auth_config)?;
+    let _ = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
     plugin_registry::update_plugin_config(&conn, &plugin_registry, &plugin_id, config_json)
 }
@@ -462,13 +780,15 @@ fn update_plugin_config(
 fn execute_plugin_command(
     memory_store: tauri::State<MemoryStore>,
     plugin_registry: tauri::State<plugin_registry::PluginRegistry>,
+    permissions: tauri::State<PermissionManager>,
     auth_config: tauri::State<AuthConfig>,
     access_token: String,
     plugin_id: String,
     command: String,
 ) -> Result<serde_json::Value, String> {
-    let _ = require_user_from_access_token(&access_token, &auth_config)?;
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    permission_policy_store::authorize(&conn, &permissions, &user_id, Capability::PluginAccess, "plugin_command", true)?;
     plugin_registry::execute_plugin_command(&conn, &plugin_registry, &plugin_id, &command)
 }
 
@@ -1255,16 +1575,26 @@ fn normalize_app_name(name: &str) -> String {
 }
 
 // Memory operations
-fn save_memory(memory_store: &MemoryStore, user_id: &str, content: &str) -> Result<(), String> {
+/// Returns the saved memory, which may be an existing near-duplicate.
+fn save_memory(memory_store: &MemoryStore, user_id: &str, content: &str) -> Result<memory_store::SavedMemory, String> {
+    save_memory_with_tags(memory_store, user_id, content, None)
+}
+
+fn save_memory_with_tags(
+    memory_store: &MemoryStore,
+    user_id: &str,
+    content: &str,
+    tags: Option<Vec<String>>,
+) -> Result<memory_store::SavedMemory, String> {
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
     
-    // Use memory_store service to create memory
+    // Use memory_store service to create memory (hashtags in content become tags too)
     let content_string = content.to_string();
-    let memory_id = memory_store::create_memory(&conn, user_id, content_string, None)?;
-    memory_intelligence_service::link_related_memories(&conn, user_id, &memory_id)?;
-    memory_intelligence_service::calculate_memory_importance(&conn, user_id, &memory_id)?;
+    let saved = memory_store::create_memory(&conn, user_id, content_string, tags)?;
+    memory_intelligence_service::link_related_memories(&conn, user_id, &saved.id)?;
+    memory_intelligence_service::calculate_memory_importance(&conn, user_id, &saved.id)?;
     
-    Ok(())
+    Ok(saved)
 }
 
 fn search_memories(memory_store: &MemoryStore, user_id: &str, keyword: &str) -> Result<Vec<String>, String> {
@@ -1279,7 +1609,8 @@ fn search_memories(memory_store: &MemoryStore, user_id: &str, keyword: &str) ->
     Ok(memories)
 }
 
-fn set_reminder(memory_store: &MemoryStore, user_id: &str, json_value: &str) -> Result<(), String> {
+/// Returns the new reminder's id.
+fn set_reminder(memory_store: &MemoryStore, user_id: &str, json_value: &str) -> Result<String, String> {
     // Parse JSON
     let parsed: serde_json::Value = serde_json::from_str(json_value)
         .map_err(|e| format!("Invalid JSON: {}", e))?;
@@ -1292,13 +1623,32 @@ fn set_reminder(memory_store: &MemoryStore, user_id: &str, json_value: &str) ->
     let trigger_at = parsed["trigger_at"]
         .as_i64()
         .ok_or("Missing or invalid 'trigger_at' field")?;
+
+    // Optional repeat rule; the first occurrence is trigger_at itself
+    let recurrence = match parsed.get("recurrence").filter(|value| !value.is_null()) {
+        Some(value) => {
+            let mut rule: reminder_recurrence::Recurrence = serde_json::from_value(value.clone())
+                .map_err(|e| format!("Invalid 'recurrence' field: {}", e))?;
+            rule.validate()?;
+            rule.anchor_at(trigger_at);
+            Some(rule)
+        }
+        None => None,
+    };
     
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
     
     println!("📝 Storing reminder: '{}' at timestamp {}", content, trigger_at);
     
     // Use reminder_store service to create reminder
-    let reminder_id = reminder_store::create_reminder(&conn, user_id, content.clone(), trigger_at, None)?;
+    let reminder_id = reminder_store::create_reminder(
+        &conn,
+        user_id,
+        content.clone(),
+        trigger_at,
+        None,
+        recurrence.as_ref(),
+    )?;
 
     let reminder = reminder_store::Reminder {
         id: reminder_id.clone(),
@@ -1308,23 +1658,26 @@ fn set_reminder(memory_store: &MemoryStore, user_id: &str, json_value: &str) ->
         status: reminder_store::status::PENDING.to_string(),
         source: "user_request".to_string(),
         memory_id: None,
+        recurrence,
     };
     scheduler::schedule_reminder(&conn, &reminder)?;
     
     println!("✓ Reminder stored successfully with ID: {}", reminder_id);
     
-    Ok(())
+    Ok(reminder_id)
 }
 
 #[tauri::command]
 fn delete_memory(
     memory_store: tauri::State<MemoryStore>,
+    permissions: tauri::State<PermissionManager>,
     auth_config: tauri::State<AuthConfig>,
     access_token: String,
     memory_id: String,
 ) -> Result<String, String> {
-    let user_id = require_user_from_access_token(&access_token, &auth_config)?;
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    permission_policy_store::authorize(&conn, &permissions, &user_id, Capability::MemoryWrite, "delete_memory", true)?;
     memory_store::delete_memory(&conn, &user_id, &memory_id)?;
     Ok("Memory deleted".to_string())
 }
@@ -1332,12 +1685,14 @@ fn delete_memory(
 #[tauri::command]
 fn finish_reminder(
     memory_store: tauri::State<MemoryStore>,
+    permissions: tauri::State<PermissionManager>,
     auth_config: tauri::State<AuthConfig>,
     access_token: String,
     reminder_id: String,
 ) -> Result<String, String> {
-    let user_id = require_user_from_access_token(&access_token, &auth_config)?;
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    permission_policy_store::authorize(&conn, &permissions, &user_id, Capability::ReminderSchedule, "finish_reminder", true)?;
     
     // Delete reminder using service layer
     scheduler::cancel_reminder_task(&conn, &reminder_id)?;
@@ -1348,13 +1703,15 @@ fn finish_reminder(
 #[tauri::command]
 fn snooze_reminder(
     memory_store: tauri::State<MemoryStore>,
+    permissions: tauri::State<PermissionManager>,
     auth_config: tauri::State<AuthConfig>,
     access_token: String,
     reminder_id: String,
     snooze_minutes: i64,
 ) -> Result<String, String> {
-    let user_id = require_user_from_access_token(&access_token, &auth_config)?;
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    permission_policy_store::authorize(&conn, &permissions, &user_id, Capability::ReminderSchedule, "snooze_reminder", true)?;
     
     // Snooze reminder using service layer
     reminder_store::snooze_reminder(&conn, &user_id, &reminder_id, snooze_minutes)?;
@@ -1390,7 +1747,7 @@ async fn execute_action(
     auth_config: tauri::State<'_, AuthConfig>,
 ) -> Result<ActionResponse, String> {
     let start_time = std::time::Instant::now();
-    let user_id = require_user_from_access_token(&access_token, &auth_config)?;
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     
     // Deserialize JSON string into typed Intent enum
     let intent: Intent = serde_json::from_str(&intent_json)
@@ -1418,6 +1775,13 @@ async fn execute_action(
         Intent::AiQuery { .. } => "ai_query",
     };
     
+    // Explicit actions are the user's own request, so an ask policy counts as
+    // answered; a deny policy still refuses them. Every decision is audited.
+    let check_capability = |capability: Capability| -> Result<(), String> {
+        let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+        permission_policy_store::authorize(&conn, &permissions, &user_id, capability, intent_name, true)
+    };
+
     // Type-safe dispatch using enum matching (no string comparisons)
     let response = match intent {
         Intent::ListApps => {
@@ -1439,8 +1803,17 @@ async fn execute_action(
         
         Intent::OpenApp { target } => {
             // Check permission before executing
-            if let Err(perm_err) = permissions.check_permission(Capability::OpenApp) {
+            if let Err(perm_err) = check_capability(Capability::OpenApp) {
                 event_bus.emit(&Event::ErrorOccurred(perm_err.clone()));
+                let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+                audit_log::append(
+                    &conn,
+                    Some(&user_id),
+                    audit_log::action::OPEN_APP,
+                    Some(&target),
+                    audit_log::outcome::DENIED,
+                    Some(&perm_err),
+                )?;
                 return Ok(ActionResponse {
                     success: false,
                     message: perm_err,
@@ -1451,7 +1824,12 @@ async fn execute_action(
                 });
             }
             
-            match open_app_internal(&target, &registry) {
+            let opened = open_app_internal(&target, &registry);
+            {
+                let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+                audit_log::append_result(&conn, Some(&user_id), audit_log::action::OPEN_APP, Some(&target), &opened)?;
+            }
+            match opened {
                 Ok(()) => {
                     let duration_ms = start_time.elapsed().as_millis();
                     event_bus.emit(&Event::IntentExecuted {
@@ -1487,7 +1865,7 @@ async fn execute_action(
         
         Intent::OpenUrl { url } => {
             // Check permission before executing
-            if let Err(perm_err) = permissions.check_permission(Capability::WebSearch) {
+            if let Err(perm_err) = check_capability(Capability::WebSearch) {
                 event_bus.emit(&Event::ErrorOccurred(perm_err.clone()));
                 return Ok(ActionResponse {
                     success: false,
@@ -1543,7 +1921,7 @@ async fn execute_action(
         
         Intent::SearchWeb { url } => {
             // Check permission before executing
-            if let Err(perm_err) = permissions.check_permission(Capability::WebSearch) {
+            if let Err(perm_err) = check_capability(Capability::WebSearch) {
                 event_bus.emit(&Event::ErrorOccurred(perm_err.clone()));
                 return Ok(ActionResponse {
                     success: false,
@@ -1601,7 +1979,7 @@ async fn execute_action(
         
         Intent::AiQuery { query } => {
             // Check permission before executing web search equivalent
-            if let Err(perm_err) = permissions.check_permission(Capability::WebSearch) {
+            if let Err(perm_err) = check_capability(Capability::WebSearch) {
                 event_bus.emit(&Event::ErrorOccurred(perm_err.clone()));
                 return Ok(ActionResponse {
                     success: false,
@@ -1617,7 +1995,9 @@ async fn execute_action(
                 &serde_json::json!({ "query": query }),
                 &query,
                 &user_id,
+                &app_handle,
                 &memory_store,
+                &permissions,
             ).await;
             
             match result {
@@ -1629,7 +2009,7 @@ async fn execute_action(
                     });
                     ActionResponse {
                         success: true,
-                        message: answer,
+                        message: answer.message,
                         requires_confirmation: false,
                         fallback_action: None,
                         fallback_value: None,
@@ -1652,8 +2032,17 @@ async fn execute_action(
         
         Intent::KillProcess { process } => {
             // Check permission before executing
-            if let Err(perm_err) = permissions.check_permission(Capability::KillProcess) {
+            if let Err(perm_err) = check_capability(Capability::KillProcess) {
                 event_bus.emit(&Event::ErrorOccurred(perm_err.clone()));
+                let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+                audit_log::append(
+                    &conn,
+                    Some(&user_id),
+                    audit_log::action::KILL_PROCESS,
+                    Some(&process),
+                    audit_log::outcome::DENIED,
+                    Some(&perm_err),
+                )?;
                 return Ok(ActionResponse {
                     success: false,
                     message: perm_err,
@@ -1664,21 +2053,26 @@ async fn execute_action(
                 });
             }
             
-            match kill_process_internal(process) {
-                Ok(()) => {
-                    let duration_ms = start_time.elapsed().as_millis();
-                    event_bus.emit(&Event::IntentExecuted {
-                        intent_name: "kill_process".to_string(),
-                        duration_ms,
-                    });
-                    ActionResponse {
-                        success: true,
-                        message: "Process terminated".to_string(),
-                        requires_confirmation: false,
-                        fallback_action: None,
-                        fallback_value: None,
-                        data: None,
-                    }
+            // Killing a process is destructive: park it as a one-step plan and
+            // let the frontend confirm it through `confirm_plan_step`.
+            let plan = ai::planner::action_plan::ActionPlan {
+                actions: vec![ai::planner::action_plan::ActionStep {
+                    intent: "kill_process".to_string(),
+                    parameters: serde_json::json!({ "process": process }),
+                    requires_confirmation: true,
+                }],
+                reasoning: None,
+            };
+            let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+            let plan_id = uuid::Uuid::new_v4().to_string();
+            match pending_plan_store::create_pending_plan(&conn, &plan_id, &user_id, &intent_json, &plan, 0, &[]) {
+                Ok(()) => ActionResponse {
+                    success: true,
+                    message: format!("Terminate {}? This needs your confirmation.", process),
+                    requires_confirmation: true,
+                    fallback_action: None,
+                    fallback_value: None,
+                    data: Some(vec![plan_id]),
                 },
                 Err(err) => {
                     event_bus.emit(&Event::ErrorOccurred(err.clone()));
@@ -1696,7 +2090,7 @@ async fn execute_action(
         
         Intent::Remember { content } => {
             // Check permission before executing
-            if let Err(perm_err) = permissions.check_permission(Capability::MemoryWrite) {
+            if let Err(perm_err) = check_capability(Capability::MemoryWrite) {
                 event_bus.emit(&Event::ErrorOccurred(perm_err.clone()));
                 return Ok(ActionResponse {
                     success: false,
@@ -1709,7 +2103,7 @@ async fn execute_action(
             }
             
             match save_memory(&memory_store, &user_id, &content) {
-                Ok(()) => {
+                Ok(_) => {
                     let duration_ms = start_time.elapsed().as_millis();
                     event_bus.emit(&Event::MemorySaved(content));
                     let _ = app_handle.emit("memory_saved", serde_json::json!({
@@ -1745,7 +2139,7 @@ async fn execute_action(
         
         Intent::RecallMemory => {
             // Check permission before executing
-            if let Err(perm_err) = permissions.check_permission(Capability::MemoryRead) {
+            if let Err(perm_err) = check_capability(Capability::MemoryRead) {
                 event_bus.emit(&Event::ErrorOccurred(perm_err.clone()));
                 return Ok(ActionResponse {
                     success: false,
@@ -1789,7 +2183,7 @@ async fn execute_action(
         
         Intent::SearchMemory { keyword } => {
             // Check permission before executing
-            if let Err(perm_err) = permissions.check_permission(Capability::MemoryRead) {
+            if let Err(perm_err) = check_capability(Capability::MemoryRead) {
                 event_bus.emit(&Event::ErrorOccurred(perm_err.clone()));
                 return Ok(ActionResponse {
                     success: false,
@@ -1833,7 +2227,7 @@ async fn execute_action(
         
         Intent::SetReminder { content, trigger_at } => {
             // Check permission before executing
-            if let Err(perm_err) = permissions.check_permission(Capability::ReminderSchedule) {
+            if let Err(perm_err) = check_capability(Capability::ReminderSchedule) {
                 event_bus.emit(&Event::ErrorOccurred(perm_err.clone()));
                 return Ok(ActionResponse {
                     success: false,
@@ -1851,7 +2245,7 @@ async fn execute_action(
             }).to_string();
             
             match set_reminder(&memory_store, &user_id, &reminder_json) {
-                Ok(()) => {
+                Ok(_) => {
                     let duration_ms = start_time.elapsed().as_millis();
                     event_bus.emit(&Event::ReminderScheduled(content.clone()));
                     let _ = app_handle.emit("reminder_scheduled", serde_json::json!({
@@ -1931,31 +2325,57 @@ fn get_memories(
     auth_config: tauri::State<AuthConfig>,
     access_token: String,
     limit: Option<i64>,
+    query: Option<String>,
 ) -> Result<Vec<serde_json::Value>, String> {
     let limit = limit.unwrap_or(10) as i32;
-    let user_id = require_user_from_access_token(&access_token, &auth_config)?;
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    // A non-empty query switches to ranked full-text + semantic search
+    if let Some(query) = query.filter(|q| !q.trim().is_empty()) {
+        let hits = memory_store::search_memories_ranked(&conn, &user_id, &query, limit)?;
+        let mut json_hits = Vec::with_capacity(hits.len());
+        for hit in &hits {
+            json_hits.push(serde_json::json!({
+                "id": hit.memory.id,
+                "content": hit.memory.content,
+                "timestamp": format_timestamp(hit.memory.created_at),
+                "importance": hit.memory.importance,
+                "source": hit.memory.source,
+                "tags": memory_tag_names(&conn, &user_id, &hit.memory.id)?,
+                "snippet": hit.snippet,
+                "score": hit.score
+            }));
+        }
+        return Ok(json_hits);
+    }
     
     // Use memory_store service to retrieve memories
     let memories_list = memory_store::get_memories(&conn, &user_id, limit, 0)?;
     
     // Convert Memory structs to JSON response
-    let json_memories = memories_list
-        .iter()
-        .map(|memory| {
-            serde_json::json!({
-                "id": memory.id,
-                "content": memory.content,
-                "timestamp": format_timestamp(memory.created_at),
-                "importance": memory.importance,
-                "source": memory.source
-            })
-        })
-        .collect();
+    let mut json_memories = Vec::with_capacity(memories_list.len());
+    for memory in &memories_list {
+        json_memories.push(serde_json::json!({
+            "id": memory.id,
+            "content": memory.content,
+            "timestamp": format_timestamp(memory.created_at),
+            "importance": memory.importance,
+            "source": memory.source,
+            "tags": memory_tag_names(&conn, &user_id, &memory.id)?
+        }));
+    }
     
     Ok(json_memories)
 }
 
+fn memory_tag_names(conn: &Connection, user_id: &str, memory_id: &str) -> Result<Vec<String>, String> {
+    Ok(memory_tag_service::get_memory_tags(conn, user_id, memory_id)?
+        .into_iter()
+        .map(|tag| tag.tag)
+        .collect())
+}
+
 #[tauri::command]
 fn get_reminders(
     memory_store: tauri::State<MemoryStore>,
@@ -1964,7 +2384,7 @@ fn get_reminders(
     limit: Option<i64>,
 ) -> Result<Vec<serde_json::Value>, String> {
     let _limit = limit.unwrap_or(10);
-    let user_id = require_user_from_access_token(&access_token, &auth_config)?;
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
     
     // Use reminder_store service to retrieve pending reminders
@@ -1981,7 +2401,11 @@ fn get_reminders(
                 "time": format_timestamp(reminder.trigger_at),
                 "status": reminder.status,
                 "source": reminder.source,
-                "memory_id": reminder.memory_id
+                "memory_id": reminder.memory_id,
+                "recurrence": reminder.recurrence.as_ref().map(|rule| serde_json::json!({
+                    "rule": rule,
+                    "description": rule.describe(),
+                }))
             })
         })
         .collect();
@@ -1997,7 +2421,7 @@ fn get_command_history(
     limit: Option<i64>,
 ) -> Result<Vec<serde_json::Value>, String> {
     let limit = limit.unwrap_or(50) as i32;
-    let user_id = require_user_from_access_token(&access_token, &auth_config)?;
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
     let records = command_history_service::fetch_recent_history(&conn, &user_id, limit)?;
 
@@ -2012,7 +2436,11 @@ fn get_command_history(
                 "success": record.success,
                 "duration": record.duration_ms,
                 "status": record.status,
-                "error_message": record.error_message
+                "error_message": record.error_message,
+                "result": record
+                    .metadata
+                    .as_deref()
+                    .and_then(|metadata| serde_json::from_str::<serde_json::Value>(metadata).ok())
             })
         })
         .collect())
@@ -2027,13 +2455,97 @@ fn check_reminders_now(
     access_token: String,
     app: tauri::AppHandle
 ) -> Result<String, String> {
-    let _ = require_user_from_access_token(&access_token, &auth_config)?;
+    let _ = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     println!("🔍 Manual reminder check triggered");
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
     let executed = worker::run_pending_tasks_once(&conn, &event_bus, Some(&app))?;
     Ok(format!("Reminder check completed, executed {} task(s)", executed))
 }
 
+// Background tasks that ran out of retries
+#[tauri::command]
+fn get_dead_letter_tasks(
+    memory_store: tauri::State<MemoryStore>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+) -> Result<Vec<scheduler::DeadLetterTask>, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    scheduler::get_dead_letter_tasks(&conn, &user_id)
+}
+
+// Requeue one dead-lettered task, or all of them when task_id is omitted
+#[tauri::command]
+fn requeue_dead_letter_tasks(
+    memory_store: tauri::State<MemoryStore>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+    task_id: Option<String>,
+) -> Result<String, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let requeued = scheduler::requeue_dead_letter_tasks(&conn, &user_id, task_id.as_deref())?;
+    Ok(format!("Requeued {} task(s)", requeued))
+}
+
+// Task types the worker can run, with the payload fields each one expects
+#[tauri::command]
+fn get_background_task_types(
+    memory_store: tauri::State<MemoryStore>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+) -> Result<Vec<task_registry::TaskTypeInfo>, String> {
+    let _ = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    Ok(task_registry::registered_task_types()
+        .into_iter()
+        .filter(|info| scheduler::task_type::USER_SCHEDULABLE.contains(&info.task_type.as_str()))
+        .collect())
+}
+
+// Queue a background task for the current user; the payload's user_id is always the caller
+#[tauri::command]
+fn schedule_background_task(
+    memory_store: tauri::State<MemoryStore>,
+    permissions: tauri::State<PermissionManager>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+    task_type: String,
+    payload: Option<serde_json::Value>,
+    execute_at: Option<i64>,
+) -> Result<String, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    if !scheduler::task_type::USER_SCHEDULABLE.contains(&task_type.as_str()) {
+        return Err(format!("Task type {} cannot be scheduled directly", task_type));
+    }
+
+    let mut payload = payload.unwrap_or_else(|| serde_json::json!({}));
+    let object = payload
+        .as_object_mut()
+        .ok_or_else(|| "Task payload must be a JSON object".to_string())?;
+    object.insert("user_id".to_string(), serde_json::Value::String(user_id.clone()));
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    if task_type == scheduler::task_type::PLUGIN_SYNC {
+        permission_policy_store::authorize(&conn, &permissions, &user_id, Capability::PluginAccess, &task_type, true)?;
+    }
+    scheduler::register_task(
+        &conn,
+        scheduler::ScheduledTask {
+            task_id: scheduler::generate_task_id(),
+            task_type,
+            payload: payload.to_string(),
+            execute_at: execute_at.unwrap_or(now),
+            status: scheduler::status::PENDING.to_string(),
+            attempts: 0,
+        },
+    )
+}
+
 // Graph command to rebuild memory relationship graph
 #[tauri::command]
 fn rebuild_memory_graph(
@@ -2041,7 +2553,7 @@ fn rebuild_memory_graph(
     auth_config: tauri::State<AuthConfig>,
     access_token: String,
 ) -> Result<serde_json::Value, String> {
-    let user_id = require_user_from_access_token(&access_token, &auth_config)?;
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
     
     println!("🔗 Rebuilding memory relationship graph...");
@@ -2066,7 +2578,7 @@ fn get_related_memories(
     memory_id: String,
     min_weight: Option<f64>
 ) -> Result<Vec<serde_json::Value>, String> {
-    let user_id = require_user_from_access_token(&access_token, &auth_config)?;
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
     let min_weight = min_weight.unwrap_or(0.0);
     
@@ -2093,7 +2605,7 @@ fn get_graph_stats(
     auth_config: tauri::State<AuthConfig>,
     access_token: String,
 ) -> Result<serde_json::Value, String> {
-    let user_id = require_user_from_access_token(&access_token, &auth_config)?;
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
     
     let stats = memory_intelligence_service::get_graph_stats(&conn, &user_id)?;
@@ -2108,6 +2620,19 @@ fn get_graph_stats(
     }))
 }
 
+// Dry-run report of schema migrations (applied version and anything pending)
+#[tauri::command]
+fn get_schema_status(
+    memory_store: tauri::State<MemoryStore>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+) -> Result<database::MigrationPlan, String> {
+    require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    database::plan_migrations(&conn)
+}
+
 #[tauri::command]
 fn track_memory_access(
     memory_store: tauri::State<MemoryStore>,
@@ -2115,7 +2640,7 @@ fn track_memory_access(
     access_token: String,
     memory_id: String,
 ) -> Result<serde_json::Value, String> {
-    let user_id = require_user_from_access_token(&access_token, &auth_config)?;
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
     let updated_importance = memory_intelligence_service::record_access_and_refresh(&conn, &user_id, &memory_id)?;
 
@@ -2133,7 +2658,7 @@ fn get_graph_data(
     access_token: String,
     limit: Option<i64>,
 ) -> Result<serde_json::Value, String> {
-    let user_id = require_user_from_access_token(&access_token, &auth_config)?;
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
     let limit = (limit.unwrap_or(100) as i32).max(1).min(1000);
 
@@ -2153,7 +2678,7 @@ fn get_memory_graph(
     access_token: String,
     limit: Option<i64>
 ) -> Result<serde_json::Value, String> {
-    let user_id = require_user_from_access_token(&access_token, &auth_config)?;
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
     let limit = (limit.unwrap_or(100) as i32).max(1).min(1000);
     
@@ -2225,14 +2750,236 @@ fn log_intent_dispatch(intent_json: &str, success: bool) {
     println!("{} Intent dispatched: {}", status, intent_json);
 }
 
+// ============================================================================
+// TAG COMMANDS
+// ============================================================================
+
+#[tauri::command]
+fn get_tags(
+    memory_store: tauri::State<MemoryStore>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+) -> Result<Vec<serde_json::Value>, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    let mut json_tags = Vec::new();
+    for tag in memory_tag_service::get_all_tags(&conn, &user_id)? {
+        json_tags.push(serde_json::json!({
+            "id": tag.id,
+            "tag": tag.tag,
+            "color": tag.color,
+            "created_at": tag.created_at,
+            "memory_count": memory_tag_service::get_tag_memory_count(&conn, &user_id, &tag.tag)?
+        }));
+    }
+
+    Ok(json_tags)
+}
+
+#[tauri::command]
+fn get_popular_tags(
+    memory_store: tauri::State<MemoryStore>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+    limit: Option<i32>,
+) -> Result<Vec<serde_json::Value>, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    let popular = memory_tag_service::get_popular_tags(&conn, &user_id, limit.unwrap_or(10))?;
+    Ok(popular
+        .into_iter()
+        .map(|(tag, count)| serde_json::json!({ "tag": tag, "memory_count": count }))
+        .collect())
+}
+
+#[tauri::command]
+fn add_memory_tags(
+    memory_store: tauri::State<MemoryStore>,
+    permissions: tauri::State<PermissionManager>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+    memory_id: String,
+    tags: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    permission_policy_store::authorize(&conn, &permissions, &user_id, Capability::MemoryWrite, "add_memory_tags", true)?;
+
+    memory_tag_service::attach_tags_by_name(&conn, &user_id, &memory_id, &tags)?;
+    memory_tag_names(&conn, &user_id, &memory_id)
+}
+
+#[tauri::command]
+fn remove_memory_tag(
+    memory_store: tauri::State<MemoryStore>,
+    permissions: tauri::State<PermissionManager>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+    memory_id: String,
+    tag: String,
+) -> Result<Vec<String>, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    permission_policy_store::authorize(&conn, &permissions, &user_id, Capability::MemoryWrite, "remove_memory_tag", true)?;
+
+    let tag_name = memory_tag_service::normalize_tag_name(&tag)
+        .ok_or_else(|| "Tag name is empty".to_string())?;
+    if let Some(existing) = memory_tag_service::get_memory_tags(&conn, &user_id, &memory_id)?
+        .into_iter()
+        .find(|t| t.tag == tag_name)
+    {
+        memory_tag_service::detach_tag_from_memory(&conn, &user_id, &memory_id, &existing.id)?;
+    }
+
+    memory_tag_names(&conn, &user_id, &memory_id)
+}
+
+#[tauri::command]
+fn get_memories_by_tag(
+    memory_store: tauri::State<MemoryStore>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+    tag: String,
+    limit: Option<i32>,
+) -> Result<Vec<serde_json::Value>, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    let memories = memories_with_tag(&conn, &user_id, &tag, limit.unwrap_or(50))?;
+    let mut json_memories = Vec::with_capacity(memories.len());
+    for memory in &memories {
+        json_memories.push(serde_json::json!({
+            "id": memory.id,
+            "content": memory.content,
+            "timestamp": format_timestamp(memory.created_at),
+            "importance": memory.importance,
+            "tags": memory_tag_names(&conn, &user_id, &memory.id)?
+        }));
+    }
+
+    Ok(json_memories)
+}
+
+#[tauri::command]
+fn update_tag_color(
+    memory_store: tauri::State<MemoryStore>,
+    permissions: tauri::State<PermissionManager>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+    tag_id: String,
+    color: Option<String>,
+) -> Result<(), String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    permission_policy_store::authorize(&conn, &permissions, &user_id, Capability::MemoryWrite, "update_tag_color", true)?;
+
+    memory_tag_service::update_tag_color(&conn, &user_id, &tag_id, color)
+}
+
+#[tauri::command]
+fn delete_tag(
+    memory_store: tauri::State<MemoryStore>,
+    permissions: tauri::State<PermissionManager>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+    tag_id: String,
+) -> Result<(), String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    permission_policy_store::authorize(&conn, &permissions, &user_id, Capability::MemoryWrite, "delete_tag", true)?;
+
+    memory_tag_service::delete_tag(&conn, &user_id, &tag_id)
+}
+
+/// Memories carrying `tag` that belong to `user_id`, newest first
+fn memories_with_tag(
+    conn: &Connection,
+    user_id: &str,
+    tag: &str,
+    limit: i32,
+) -> Result<Vec<memory_graph_repository::MemoryRecord>, String> {
+    let tag_name = match memory_tag_service::normalize_tag_name(tag) {
+        Some(tag_name) => tag_name,
+        None => return Ok(Vec::new()),
+    };
+
+    Ok(memory_tag_service::get_memories_by_tag(conn, user_id, &tag_name, limit)?
+        .iter()
+        .filter_map(|memory_id| memory_graph_repository::get_memory(conn, user_id, memory_id).ok())
+        .collect())
+}
+
+// ============================================================================
+// DATA EXPORT / IMPORT COMMANDS
+// ============================================================================
+
+/// Write the current user's data to a JSON-lines archive under `<app data>/exports/`
+/// and return its path. `file_name` must be a bare file name.
+#[tauri::command]
+fn export_user_data(
+    app: tauri::AppHandle,
+    memory_store: tauri::State<MemoryStore>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+    file_name: Option<String>,
+) -> Result<String, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+
+    let archive = {
+        let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+        user_data_archive::export_user_data(&conn, &user_id)?
+    };
+
+    let exports_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?
+        .join("exports");
+    let target = user_data_archive::export_path(&exports_dir, file_name.as_deref())?;
+
+    std::fs::write(&target, archive)
+        .map_err(|e| format!("Failed to write export archive: {}", e))?;
+
+    println!("✓ Exported user data to {}", target.display());
+    Ok(target.to_string_lossy().to_string())
+}
+
+/// Import an archive produced by `export_user_data` into the current user's account.
+#[tauri::command]
+fn import_user_data(
+    memory_store: tauri::State<MemoryStore>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+    path: String,
+) -> Result<user_data_archive::ImportSummary, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let archive = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read import archive: {}", e))?;
+
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let summary = user_data_archive::import_user_data(&conn, &user_id, &archive)?;
+
+    println!(
+        "✓ Imported {} memories, {} reminders, {} chat messages from {}",
+        summary.memories, summary.reminders, summary.chat_messages, path
+    );
+    Ok(summary)
+}
+
 // ============================================================================
 // AI CHAT COMMAND
 // ============================================================================
 
-/// Tauri command to handle chat messages with the Gemini LLM.
+/// Tauri command to handle chat messages with the configured LLM.
 /// 
 /// # Arguments
 /// * `message` - The user's chat message
+/// * `message_id` - Optional id for the assistant reply; generated when absent
+/// 
+/// Streamed answer text is emitted as `chat_token` events and the final reply
+/// as `chat_done`, both carrying `message_id`. The reply is stored under that id.
 /// 
 /// # Returns
 /// * `Ok(String)` - The AI assistant's response
@@ -2240,6 +2987,7 @@ fn log_intent_dispatch(intent_json: &str, success: bool) {
 #[tauri::command]
 async fn chat_with_ai(
     message: String,
+    message_id: Option<String>,
     access_token: String,
     app_handle: tauri::AppHandle,
     registry: tauri::State<'_, AppRegistry>,
@@ -2249,7 +2997,11 @@ async fn chat_with_ai(
     permissions: tauri::State<'_, PermissionManager>,
     auth_config: tauri::State<'_, AuthConfig>,
 ) -> Result<String, String> {
-    let user_id = require_user_from_access_token(&access_token, &auth_config)?;
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let message_id = message_id
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
 
     {
         let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
@@ -2258,6 +3010,7 @@ async fn chat_with_ai(
 
     let ai_result = ai::handle_chat(
         message,
+        &message_id,
         &user_id,
         &app_handle,
         &registry,
@@ -2268,21 +3021,106 @@ async fn chat_with_ai(
     )
     .await;
 
+    let (content, success) = match &ai_result {
+        Ok(response) => (response.clone(), true),
+        Err(error) => (format!("Error: {}", error), false),
+    };
+    let _ = app_handle.emit(
+        "chat_done",
+        serde_json::json!({
+            "message_id": message_id,
+            "content": content,
+            "success": success,
+        }),
+    );
+
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
     match ai_result {
         Ok(response) => {
-            let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
-            chat_history_store::create_message(&conn, &user_id, "assistant", response.clone())?;
+            chat_history_store::create_message_with_id(&conn, &message_id, &user_id, "assistant", response.clone())?;
             Ok(response)
         }
         Err(error) => {
-            let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
-            let persisted_error = format!("Error: {}", error);
-            let _ = chat_history_store::create_message(&conn, &user_id, "assistant", persisted_error);
+            let _ = chat_history_store::create_message_with_id(&conn, &message_id, &user_id, "assistant", content);
             Err(error)
         }
     }
 }
 
+/// Approves or cancels the step a chat plan paused on (see the
+/// `plan_confirmation_required` event). When the step offered `choices`,
+/// `choice` is the index of the one the user picked. When the step asked for
+/// permission, `grant` is "once", "session" or "always". The outcome is added
+/// to the chat history.
+#[tauri::command]
+async fn confirm_plan_step(
+    plan_id: String,
+    approve: bool,
+    choice: Option<usize>,
+    grant: Option<permission_policy_store::GrantScope>,
+    access_token: String,
+    app_handle: tauri::AppHandle,
+    registry: tauri::State<'_, AppRegistry>,
+    memory_store: tauri::State<'_, MemoryStore>,
+    plugin_registry: tauri::State<'_, plugin_registry::PluginRegistry>,
+    event_bus: tauri::State<'_, EventBus>,
+    permissions: tauri::State<'_, PermissionManager>,
+    auth_config: tauri::State<'_, AuthConfig>,
+) -> Result<ai::planner::plan_executor::PlanProgress, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+
+    let progress = ai::planner::plan_executor::resume_action_plan(
+        &plan_id,
+        approve,
+        choice,
+        grant,
+        &user_id,
+        &app_handle,
+        &registry,
+        &memory_store,
+        &plugin_registry,
+        &event_bus,
+        &permissions,
+    )
+    .await?;
+
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    chat_history_store::create_message(&conn, &user_id, "assistant", progress.message.clone())?;
+    Ok(progress)
+}
+
+/// Reverts every change made by the most recent chat request, newest first.
+/// The summary is added to the chat history.
+#[tauri::command]
+fn undo_last_action(
+    memory_store: tauri::State<MemoryStore>,
+    event_bus: tauri::State<EventBus>,
+    permissions: tauri::State<PermissionManager>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+) -> Result<String, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    let outcome = ai::tool_executor::execute_undo(&user_id, &memory_store, &event_bus, &permissions)?;
+
+    let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
+    chat_history_store::create_message(&conn, &user_id, "assistant", outcome.message.clone())?;
+    Ok(outcome.message)
+}
+
+/// Shows the planning prompt a chat message would produce and how many
+/// estimated tokens each context section used, without calling the model.
+#[tauri::command]
+fn debug_prompt_composition(
+    memory_store: tauri::State<MemoryStore>,
+    permissions: tauri::State<PermissionManager>,
+    auth_config: tauri::State<AuthConfig>,
+    access_token: String,
+    message: String,
+) -> Result<ai::prompt_templates::PromptComposition, String> {
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
+    ai::orchestrator::preview_planning_prompt(&message, &user_id, &memory_store, &permissions)
+}
+
 #[tauri::command]
 fn get_chat_history(
     memory_store: tauri::State<MemoryStore>,
@@ -2290,7 +3128,7 @@ fn get_chat_history(
     access_token: String,
     limit: Option<i64>,
 ) -> Result<Vec<serde_json::Value>, String> {
-    let user_id = require_user_from_access_token(&access_token, &auth_config)?;
+    let user_id = require_user_from_access_token(&access_token, &auth_config, &memory_store)?;
     let safe_limit = limit.unwrap_or(100) as i32;
     let conn = memory_store.conn.lock().map_err(|e| format!("Lock error: {}", e))?;
     let history = chat_history_store::get_messages(&conn, &user_id, safe_limit)?;
@@ -2370,7 +3208,8 @@ pub fn run() {
 
     // Initialize infrastructure layers
     let event_bus = EventBus::new();
-    let permissions = PermissionManager::default_permissions();
+    // Replaced by the saved permissions file once the config dir is known in setup
+    let permissions = PermissionManager::from_user_permissions(&permissions_service::UserPermissions::default());
     
     // Register telemetry subscriber
     let telemetry_fn = create_telemetry_subscriber();
@@ -2380,12 +3219,13 @@ pub fn run() {
     
     println!("✓ Phase 2 Infrastructure Activated:");
     println!("  • EventBus initialized");
-    println!("  • PermissionManager initialized (default: all capabilities allowed)");
+    println!("  • PermissionManager initialized (follows the user permissions file)");
     println!("  • TelemetryEvent subscriber registered");
     println!("  • Execution lifecycle instrumentation enabled");
 
     // Clone event_bus for use in setup closure
     let event_bus_for_setup = event_bus.clone();
+    let permissions_for_setup = permissions.clone();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
@@ -2415,9 +3255,20 @@ pub fn run() {
             // Load settings and permissions from disk (or create defaults)
             let settings_state = settings_service::SettingsState::load(&config_dir);
             let permissions_state = permissions_service::PermissionsState::load(&config_dir);
+            if let Err(error) = audit_log::init_signing_key(&config_dir) {
+                eprintln!("⚠️  Audit log signing disabled: {}", error);
+            }
 
             println!("✓ Settings loaded from: {}", config_dir.display());
 
+            if let Ok(user_permissions) = permissions_state.permissions.lock() {
+                permissions_for_setup.apply_user_permissions(&user_permissions);
+            }
+
+            if let Ok(settings) = settings_state.settings.lock() {
+                ai::llm_provider::configure_from_settings(&settings);
+            }
+
             let db_path = app_data_dir.join("noddy.db");
             
             let conn = Connection::open(&db_path)
@@ -2442,7 +3293,11 @@ pub fn run() {
 
             let db_path_for_plugins = db_path.clone();
             let plugin_registry_for_events = plugin_registry.clone();
+            let permissions_for_plugins = permissions_for_setup.clone();
             event_bus_for_setup.register(move |event| {
+                if !permissions_for_plugins.allows(Capability::PluginAccess) {
+                    return;
+                }
                 if let Some(plugin_event) = plugin_registry::plugin_event_from_core_event(event) {
                     match Connection::open(&db_path_for_plugins) {
                         Ok(conn) => {
@@ -2482,6 +3337,10 @@ pub fn run() {
             refresh_token,
             logout,
             get_current_user,
+            change_password,
+            list_sessions,
+            revoke_session,
+            revoke_all_sessions,
             execute_action,
             get_memories,
             get_reminders,
@@ -2493,17 +3352,39 @@ pub fn run() {
             update_plugin_config,
             execute_plugin_command,
             check_reminders_now,
+            get_dead_letter_tasks,
+            requeue_dead_letter_tasks,
+            get_background_task_types,
+            schedule_background_task,
             delete_memory,
             finish_reminder,
             snooze_reminder,
             rebuild_memory_graph,
             get_related_memories,
             get_graph_stats,
+            get_schema_status,
+            get_tags,
+            get_popular_tags,
+            add_memory_tags,
+            remove_memory_tag,
+            get_memories_by_tag,
+            update_tag_color,
+            delete_tag,
+            export_user_data,
+            import_user_data,
             track_memory_access,
             get_graph_data,
             get_memory_graph,
             get_chat_history,
             chat_with_ai,
+            confirm_plan_step,
+            undo_last_action,
+            debug_prompt_composition,
+            get_permission_policies,
+            set_permission_policy,
+            get_permission_decisions,
+            get_audit_log,
+            verify_audit_log,
             settings_service::get_settings,
             settings_service::update_settings,
             settings_service::get_app_data_path,
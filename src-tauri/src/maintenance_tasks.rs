@@ -0,0 +1,176 @@
+use crate::plugin_registry::{self, PluginRegistry};
+use crate::scheduler::{task_type, ScheduledTask};
+use crate::task_registry::{FieldKind, PayloadField, TaskContext, TaskHandler, TaskOutcome};
+use crate::{
+    memory_graph_repository, memory_intelligence_service, permission_policy_store, user_data_archive, Capability,
+    PermissionManager,
+};
+use serde::Deserialize;
+use tauri::Manager;
+
+const USER_TASK_SCHEMA: &[PayloadField] = &[PayloadField::required("user_id", FieldKind::String)];
+
+const PLUGIN_SYNC_SCHEMA: &[PayloadField] = &[
+    PayloadField::required("user_id", FieldKind::String),
+    PayloadField::required("plugin_id", FieldKind::String),
+    PayloadField::optional("command", FieldKind::String),
+];
+
+const DATA_EXPORT_SCHEMA: &[PayloadField] = &[
+    PayloadField::required("user_id", FieldKind::String),
+    PayloadField::optional("file_name", FieldKind::String),
+];
+
+#[derive(Debug, Deserialize)]
+struct UserTaskPayload {
+    user_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginSyncPayload {
+    user_id: String,
+    plugin_id: String,
+    #[serde(default)]
+    command: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DataExportPayload {
+    user_id: String,
+    #[serde(default)]
+    file_name: Option<String>,
+}
+
+fn parse_payload<'de, T: Deserialize<'de>>(task: &'de ScheduledTask) -> Result<T, String> {
+    serde_json::from_str(&task.payload)
+        .map_err(|e| format!("Failed to deserialize {} task payload: {}", task.task_type, e))
+}
+
+/// Recomputes keyword links between a user's memories.
+pub struct GraphRebuildHandler;
+
+impl TaskHandler for GraphRebuildHandler {
+    fn task_type(&self) -> &'static str {
+        task_type::GRAPH_REBUILD
+    }
+
+    fn payload_schema(&self) -> &'static [PayloadField] {
+        USER_TASK_SCHEMA
+    }
+
+    fn execute(&self, ctx: &TaskContext<'_>, task: &ScheduledTask) -> Result<TaskOutcome, String> {
+        let payload: UserTaskPayload = parse_payload(task)?;
+        let (cleared, created) = memory_intelligence_service::rebuild_memory_links(ctx.conn, &payload.user_id)?;
+        println!(
+            "✓ Background graph rebuild: cleared {} edges, created {} edges",
+            cleared, created
+        );
+        Ok(TaskOutcome::Completed)
+    }
+}
+
+/// Re-scores importance so recency decay applies to memories nobody touched.
+pub struct MemoryDecayHandler;
+
+impl TaskHandler for MemoryDecayHandler {
+    fn task_type(&self) -> &'static str {
+        task_type::MEMORY_DECAY
+    }
+
+    fn payload_schema(&self) -> &'static [PayloadField] {
+        USER_TASK_SCHEMA
+    }
+
+    fn execute(&self, ctx: &TaskContext<'_>, task: &ScheduledTask) -> Result<TaskOutcome, String> {
+        let payload: UserTaskPayload = parse_payload(task)?;
+        let memories = memory_graph_repository::list_all_memories(ctx.conn, &payload.user_id)?;
+        for memory in &memories {
+            memory_intelligence_service::calculate_memory_importance(ctx.conn, &payload.user_id, &memory.id)?;
+        }
+        println!("✓ Background decay recompute: {} memories re-scored", memories.len());
+        Ok(TaskOutcome::Completed)
+    }
+}
+
+/// Runs a command (default "sync") on an enabled plugin.
+pub struct PluginSyncHandler;
+
+impl TaskHandler for PluginSyncHandler {
+    fn task_type(&self) -> &'static str {
+        task_type::PLUGIN_SYNC
+    }
+
+    fn payload_schema(&self) -> &'static [PayloadField] {
+        PLUGIN_SYNC_SCHEMA
+    }
+
+    fn execute(&self, ctx: &TaskContext<'_>, task: &ScheduledTask) -> Result<TaskOutcome, String> {
+        let payload: PluginSyncPayload = parse_payload(task)?;
+        // Fails closed: without the permission manager there is nothing to check against
+        let permitted = ctx
+            .app_handle
+            .and_then(|app| app.try_state::<PermissionManager>())
+            .ok_or_else(|| "Permission denied for capability: PluginAccess".to_string())
+            .and_then(|permissions| {
+                permission_policy_store::authorize(
+                    ctx.conn,
+                    &permissions,
+                    &payload.user_id,
+                    Capability::PluginAccess,
+                    task_type::PLUGIN_SYNC,
+                    false,
+                )
+            });
+        if let Err(reason) = permitted {
+            return Ok(TaskOutcome::Refused(reason));
+        }
+
+        let registry = ctx
+            .app_handle
+            .and_then(|app| app.try_state::<PluginRegistry>())
+            .map(|state| state.inner().clone())
+            .unwrap_or_else(PluginRegistry::new);
+
+        let plugin = plugin_registry::get_plugin(ctx.conn, &registry, &payload.plugin_id)?;
+        if !plugin.enabled {
+            return Err(format!("Plugin {} is disabled", payload.plugin_id));
+        }
+
+        let command = payload.command.as_deref().unwrap_or("sync");
+        plugin_registry::execute_plugin_command(ctx.conn, &registry, &payload.plugin_id, command)?;
+        Ok(TaskOutcome::Completed)
+    }
+}
+
+/// Writes a user archive (see `user_data_archive`) into `<app data>/exports/`.
+pub struct DataExportHandler;
+
+impl TaskHandler for DataExportHandler {
+    fn task_type(&self) -> &'static str {
+        task_type::DATA_EXPORT
+    }
+
+    fn payload_schema(&self) -> &'static [PayloadField] {
+        DATA_EXPORT_SCHEMA
+    }
+
+    fn execute(&self, ctx: &TaskContext<'_>, task: &ScheduledTask) -> Result<TaskOutcome, String> {
+        let payload: DataExportPayload = parse_payload(task)?;
+        let exports_dir = ctx
+            .app_handle
+            .ok_or_else(|| "Exports need the app data directory".to_string())?
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to resolve app data directory: {}", e))?
+            .join("exports");
+        let target = match user_data_archive::export_path(&exports_dir, payload.file_name.as_deref()) {
+            Ok(target) => target,
+            Err(reason) => return Ok(TaskOutcome::Refused(reason)),
+        };
+
+        let archive = user_data_archive::export_user_data(ctx.conn, &payload.user_id)?;
+        std::fs::write(&target, archive).map_err(|e| format!("Failed to write export archive: {}", e))?;
+        println!("✓ Background export written to {}", target.display());
+        Ok(TaskOutcome::Completed)
+    }
+}
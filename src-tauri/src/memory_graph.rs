@@ -150,7 +150,7 @@ pub fn rebuild_memory_graph(conn: &Connection, user_id: &str) -> Result<(i64, i6
     .map_err(|e| format!("Failed to clear existing edges: {}", e))?;
 
     let memory_ids: Vec<String> = conn
-        .prepare("SELECT CAST(id AS TEXT) FROM memories WHERE user_id = ?1 ORDER BY created_at DESC")
+        .prepare("SELECT CAST(id AS TEXT) FROM memories WHERE user_id = ?1 AND deleted_at IS NULL ORDER BY created_at DESC")
         .map_err(|e| format!("Failed to prepare query: {}", e))?
         .query_map(params![user_id], |row| row.get(0))
         .map_err(|e| format!("Failed to query memories: {}", e))?
@@ -281,7 +281,7 @@ pub fn get_memory_graph(conn: &Connection, user_id: &str, limit: i32) -> Result<
                     content,
                     COALESCE(importance, 0.5)
              FROM memories
-             WHERE user_id = ?1
+             WHERE user_id = ?1 AND deleted_at IS NULL
              ORDER BY importance DESC, created_at DESC
              LIMIT ?2",
         )
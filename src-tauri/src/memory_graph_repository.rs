@@ -33,7 +33,7 @@ pub fn get_memory(conn: &Connection, user_id: &str, memory_id: &str) -> Result<M
                 COALESCE(access_count, 0),
                 last_accessed_at
          FROM memories
-         WHERE user_id = ?1 AND CAST(id AS TEXT) = ?2",
+         WHERE user_id = ?1 AND CAST(id AS TEXT) = ?2 AND deleted_at IS NULL",
         params![user_id, memory_id],
         |row| {
             Ok(MemoryRecord {
@@ -58,7 +58,7 @@ pub fn list_memories(conn: &Connection, user_id: &str, limit: i32) -> Result<Vec
                     COALESCE(access_count, 0),
                     last_accessed_at
              FROM memories
-             WHERE user_id = ?1 AND content IS NOT NULL
+             WHERE user_id = ?1 AND content IS NOT NULL AND deleted_at IS NULL
              ORDER BY importance DESC, created_at DESC
              LIMIT ?2",
         )
@@ -89,7 +89,7 @@ pub fn list_all_memories(conn: &Connection, user_id: &str) -> Result<Vec<MemoryR
                     COALESCE(access_count, 0),
                     last_accessed_at
              FROM memories
-             WHERE user_id = ?1 AND content IS NOT NULL
+             WHERE user_id = ?1 AND content IS NOT NULL AND deleted_at IS NULL
              ORDER BY created_at DESC",
         )
         .map_err(|e| format!("Failed to prepare all memories query: {}", e))?;
@@ -127,6 +127,7 @@ pub fn list_candidate_memories(
              WHERE user_id = ?1
                AND CAST(id AS TEXT) != ?2
                AND content IS NOT NULL
+               AND deleted_at IS NULL
              ORDER BY created_at DESC
              LIMIT ?3",
         )
@@ -211,6 +212,11 @@ pub fn get_edges_for_memory(
              FROM memory_edges
              WHERE user_id = ?1
                AND (source_memory_id = ?2 OR target_memory_id = ?2)
+               AND NOT EXISTS (
+                   SELECT 1 FROM memories m
+                   WHERE m.deleted_at IS NOT NULL
+                     AND CAST(m.id AS TEXT) IN (source_memory_id, target_memory_id)
+               )
              ORDER BY weight DESC, created_at DESC",
         )
         .map_err(|e| format!("Failed to prepare edge query: {}", e))?;
@@ -235,6 +241,11 @@ pub fn list_edges(conn: &Connection, user_id: &str) -> Result<Vec<MemoryEdgeReco
             "SELECT source_memory_id, target_memory_id, COALESCE(relationship, 'related'), COALESCE(weight, 0.0)
              FROM memory_edges
              WHERE user_id = ?1
+               AND NOT EXISTS (
+                   SELECT 1 FROM memories m
+                   WHERE m.deleted_at IS NOT NULL
+                     AND CAST(m.id AS TEXT) IN (source_memory_id, target_memory_id)
+               )
              ORDER BY weight DESC, created_at DESC",
         )
         .map_err(|e| format!("Failed to prepare list edges query: {}", e))?;
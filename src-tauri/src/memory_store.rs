@@ -1,7 +1,23 @@
 use rusqlite::{params, Connection};
+use std::collections::HashMap;
 use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
+use crate::embeddings::{embedding_provider, embedding_store};
+use crate::memory_tag_service;
+
+// Search ranking: cosine similarity dominates, BM25 full-text hits are
+// always kept near the top, and importance breaks near-ties.
+const SEMANTIC_WEIGHT: f64 = 0.8;
+const FULL_TEXT_WEIGHT: f64 = 0.3;
+const IMPORTANCE_WEIGHT: f64 = 0.2;
+const MIN_SEMANTIC_SIMILARITY: f64 = 0.12;
+const FULL_TEXT_CANDIDATE_LIMIT: i32 = 50;
+const SEMANTIC_CANDIDATE_LIMIT: usize = 50;
+
+/// How long a deleted memory stays restorable before it is purged for good.
+pub const DELETED_MEMORY_RETENTION_SECONDS: i64 = 7 * 24 * 60 * 60;
+
 #[derive(Debug, Clone)]
 pub struct Memory {
     pub id: String,
@@ -11,14 +27,38 @@ pub struct Memory {
     pub source: String,
 }
 
+/// A ranked search result. `snippet` is set when the memory matched the
+/// full-text index and has matched terms wrapped in `<mark>` tags.
+#[derive(Debug, Clone)]
+pub struct MemorySearchHit {
+    pub memory: Memory,
+    pub snippet: Option<String>,
+    pub score: f64,
+}
+
+/// Id of a saved memory. `merged` is set when the content was folded into a
+/// near-duplicate that already existed instead of creating a new row.
+#[derive(Debug, Clone)]
+pub struct SavedMemory {
+    pub id: String,
+    pub merged: bool,
+}
+
 pub fn create_memory(
     conn: &Connection,
     user_id: &str,
     content: String,
-    _tags: Option<Vec<String>>,
-) -> Result<String, String> {
+    tags: Option<Vec<String>>,
+) -> Result<SavedMemory, String> {
+    let mut tag_names = tags.unwrap_or_default();
+    tag_names.extend(memory_tag_service::extract_hashtags(&content));
+
     if let Some(existing_id) = find_near_duplicate_memory(conn, user_id, &content, 0.85)? {
-        return Ok(existing_id);
+        memory_tag_service::attach_tags_by_name(conn, user_id, &existing_id, &tag_names)?;
+        return Ok(SavedMemory {
+            id: existing_id,
+            merged: true,
+        });
     }
 
     let now = current_timestamp();
@@ -44,7 +84,10 @@ pub fn create_memory(
         generated_id
     };
 
-    Ok(id)
+    embedding_store::upsert_memory_embedding(conn, &id, &content)?;
+    memory_tag_service::attach_tags_by_name(conn, user_id, &id, &tag_names)?;
+
+    Ok(SavedMemory { id, merged: false })
 }
 
 pub fn update_memory_content(
@@ -53,11 +96,24 @@ pub fn update_memory_content(
     memory_id: &str,
     new_content: &str,
 ) -> Result<(), String> {
-    conn.execute(
-        "UPDATE memories SET content = ?1, updated_at = ?2 WHERE id = ?3 AND user_id = ?4",
-        params![new_content, current_timestamp(), memory_id, user_id],
-    )
-    .map_err(|e| format!("Failed to update memory: {}", e))?;
+    let updated = conn
+        .execute(
+            "UPDATE memories SET content = ?1, updated_at = ?2
+             WHERE id = ?3 AND user_id = ?4 AND deleted_at IS NULL",
+            params![new_content, current_timestamp(), memory_id, user_id],
+        )
+        .map_err(|e| format!("Failed to update memory: {}", e))?;
+    if updated == 0 {
+        return Err(format!("Memory {} not found", memory_id));
+    }
+
+    embedding_store::upsert_memory_embedding(conn, memory_id, new_content)?;
+    memory_tag_service::attach_tags_by_name(
+        conn,
+        user_id,
+        memory_id,
+        &memory_tag_service::extract_hashtags(new_content),
+    )?;
     Ok(())
 }
 
@@ -75,7 +131,7 @@ pub fn get_memories(
                     COALESCE(importance, 0.5),
                     COALESCE(source, 'user_input')
              FROM memories
-             WHERE user_id = ?1 AND content IS NOT NULL
+             WHERE user_id = ?1 AND content IS NOT NULL AND deleted_at IS NULL
                ORDER BY importance DESC, created_at DESC
              LIMIT ?2 OFFSET ?3",
         )
@@ -104,97 +160,214 @@ pub fn search_memories(
     query: String,
     limit: i32,
 ) -> Result<Vec<Memory>, String> {
-    let search_pattern = format!("%{}%", query.trim());
-    let mut stmt = conn
-        .prepare(
-            "SELECT CAST(id AS TEXT),
-                    content,
-                    COALESCE(created_at, CAST(strftime('%s', 'now') AS INTEGER)),
-                    COALESCE(importance, 0.5),
-                    COALESCE(source, 'user_input')
-             FROM memories
-             WHERE user_id = ?1 AND content LIKE ?2 AND content IS NOT NULL
-             ORDER BY importance DESC, created_at DESC
-             LIMIT ?3",
-        )
-        .map_err(|e| format!("Failed to prepare search statement: {}", e))?;
+    Ok(search_memories_ranked(conn, user_id, &query, limit)?
+        .into_iter()
+        .map(|hit| hit.memory)
+        .collect())
+}
 
-    let memories = stmt
-        .query_map(params![user_id, search_pattern, limit], |row| {
-            Ok(Memory {
-                id: row.get(0)?,
-                content: row.get(1)?,
-                created_at: row.get(2)?,
-                importance: row.get(3)?,
-                source: row.get(4)?,
-            })
-        })
-        .map_err(|e| format!("Failed to query search results: {}", e))?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to map search results: {}", e))?;
+/// A memory considered by `search_memories_ranked`, before scoring.
+struct SearchCandidate {
+    memory: Memory,
+    snippet: Option<String>,
+    /// BM25 rank scaled to 0..=1 against the best hit; 0 without a full-text match.
+    relevance: f64,
+    similarity: f64,
+}
 
-    if !memories.is_empty() {
-        return Ok(memories);
+/// Hybrid search: BM25 hits from the FTS index merged with embedding similarity.
+pub fn search_memories_ranked(
+    conn: &Connection,
+    user_id: &str,
+    query: &str,
+    limit: i32,
+) -> Result<Vec<MemorySearchHit>, String> {
+    if query.trim().is_empty() {
+        return Ok(Vec::new());
     }
 
-    // Fallback: rank by token overlap so conversational queries still match
-    // memories like "I have Big data analytics class tomorrow".
-    let tokens = extract_search_tokens(&query);
-    if tokens.is_empty() {
-        return Ok(Vec::new());
+    // BM25 scores are negative and unbounded; scale them against the best hit.
+    let full_text_hits = full_text_search(conn, user_id, query, FULL_TEXT_CANDIDATE_LIMIT)?;
+    let best_rank = full_text_hits
+        .iter()
+        .map(|hit| hit.score)
+        .fold(0.0_f64, f64::min);
+    let mut candidates: HashMap<String, SearchCandidate> = full_text_hits
+        .into_iter()
+        .map(|hit| {
+            let relevance = if best_rank < 0.0 { hit.score / best_rank } else { 1.0 };
+            let candidate = SearchCandidate {
+                memory: hit.memory,
+                snippet: hit.snippet,
+                relevance,
+                similarity: 0.0,
+            };
+            (candidate.memory.id.clone(), candidate)
+        })
+        .collect();
+
+    // Embed the stop-word-free, temporally expanded form so conversational
+    // queries ("what did I say about the exam") reduce to their topic.
+    let tokens = extract_search_tokens(query);
+    let query_vector = embedding_store::embed_query(&tokens.join(" "));
+    if !embedding_provider::is_zero_vector(&query_vector) {
+        // Full-text matches get their similarity filled in; otherwise only
+        // the closest few memories become candidates.
+        let mut semantic = Vec::new();
+        for embedded in embedding_store::load_user_embeddings(conn, user_id)? {
+            let Some(vector) = embedded.embedding.as_deref() else {
+                continue;
+            };
+            let similarity = embedding_provider::cosine_similarity(&query_vector, vector);
+            match candidates.get_mut(&embedded.id) {
+                Some(candidate) => candidate.similarity = similarity,
+                None if similarity >= MIN_SEMANTIC_SIMILARITY => semantic.push((similarity, embedded)),
+                None => {}
+            }
+        }
+        semantic.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        semantic.truncate(SEMANTIC_CANDIDATE_LIMIT);
+        for (similarity, embedded) in semantic {
+            let candidate = SearchCandidate {
+                memory: Memory {
+                    id: embedded.id,
+                    content: embedded.content,
+                    created_at: embedded.created_at,
+                    importance: embedded.importance,
+                    source: embedded.source,
+                },
+                snippet: None,
+                relevance: 0.0,
+                similarity,
+            };
+            candidates.insert(candidate.memory.id.clone(), candidate);
+        }
     }
 
-    let mut candidate_stmt = conn
+    let mut ranked: Vec<MemorySearchHit> = candidates
+        .into_values()
+        .map(|candidate| MemorySearchHit {
+            score: SEMANTIC_WEIGHT * candidate.similarity
+                + IMPORTANCE_WEIGHT * candidate.memory.importance
+                + FULL_TEXT_WEIGHT * candidate.relevance,
+            memory: candidate.memory,
+            snippet: candidate.snippet,
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| b.memory.created_at.cmp(&a.memory.created_at))
+    });
+    ranked.truncate(limit.max(0) as usize);
+
+    Ok(ranked)
+}
+
+/// BM25-ranked lookup against the `memories_fts` index. Supports quoted
+/// phrases and `prefix*` terms; plain words are OR-ed together.
+/// `score` is the raw bm25() value (more negative is better).
+pub fn full_text_search(
+    conn: &Connection,
+    user_id: &str,
+    query: &str,
+    limit: i32,
+) -> Result<Vec<MemorySearchHit>, String> {
+    let match_expression = match build_fts_query(query) {
+        Some(expression) => expression,
+        None => return Ok(Vec::new()),
+    };
+
+    let mut stmt = conn
         .prepare(
-            "SELECT CAST(id AS TEXT),
-                    content,
-                    COALESCE(created_at, CAST(strftime('%s', 'now') AS INTEGER)),
-                    COALESCE(importance, 0.5),
-                    COALESCE(source, 'user_input')
-             FROM memories
-             WHERE user_id = ?1 AND content IS NOT NULL
-             ORDER BY created_at DESC
-             LIMIT 250",
+            "SELECT CAST(m.id AS TEXT),
+                    m.content,
+                    COALESCE(m.created_at, CAST(strftime('%s', 'now') AS INTEGER)),
+                    COALESCE(m.importance, 0.5),
+                    COALESCE(m.source, 'user_input'),
+                    bm25(memories_fts),
+                    snippet(memories_fts, 0, '<mark>', '</mark>', '…', 16)
+             FROM memories_fts
+             JOIN memories m ON CAST(m.id AS TEXT) = memories_fts.memory_id
+             WHERE memories_fts MATCH ?1 AND memories_fts.user_id = ?2 AND m.deleted_at IS NULL
+             ORDER BY bm25(memories_fts)
+             LIMIT ?3",
         )
-        .map_err(|e| format!("Failed to prepare fallback search statement: {}", e))?;
-
-    let candidates = candidate_stmt
-        .query_map(params![user_id], |row| {
-            Ok(Memory {
-                id: row.get(0)?,
-                content: row.get(1)?,
-                created_at: row.get(2)?,
-                importance: row.get(3)?,
-                source: row.get(4)?,
+        .map_err(|e| format!("Failed to prepare full-text search: {}", e))?;
+
+    let hits = stmt
+        .query_map(params![match_expression, user_id, limit], |row| {
+            Ok(MemorySearchHit {
+                memory: Memory {
+                    id: row.get(0)?,
+                    content: row.get(1)?,
+                    created_at: row.get(2)?,
+                    importance: row.get(3)?,
+                    source: row.get(4)?,
+                },
+                score: row.get(5)?,
+                snippet: row.get(6)?,
             })
         })
-        .map_err(|e| format!("Failed to query fallback search candidates: {}", e))?
+        .map_err(|e| format!("Failed to run full-text search: {}", e))?
         .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to map fallback search candidates: {}", e))?;
+        .map_err(|e| format!("Failed to map full-text results: {}", e))?;
 
-    let mut ranked: Vec<(usize, Memory)> = candidates
-        .into_iter()
-        .filter_map(|memory| {
-            let score = overlap_score(&memory.content, &tokens);
-            if score > 0 {
-                Some((score, memory))
-            } else {
-                None
+    Ok(hits)
+}
+
+/// Translate free text into an FTS5 MATCH expression. Every term is quoted so
+/// user input can never inject FTS operators.
+fn build_fts_query(query: &str) -> Option<String> {
+    let mut terms = Vec::new();
+    let mut plain_text = String::new();
+
+    for (index, segment) in query.split('"').enumerate() {
+        // Odd segments sit between a pair of double quotes.
+        if index % 2 == 1 {
+            let phrase = fts_words(segment).join(" ");
+            if !phrase.is_empty() {
+                terms.push(format!("\"{}\"", phrase));
             }
-        })
-        .collect();
+            continue;
+        }
 
-    ranked.sort_by(|a, b| {
-        b.0.cmp(&a.0)
-            .then_with(|| b.1.importance.partial_cmp(&a.1.importance).unwrap_or(std::cmp::Ordering::Equal))
-            .then_with(|| b.1.created_at.cmp(&a.1.created_at))
-    });
+        for word in segment.split_whitespace() {
+            match word.strip_suffix('*') {
+                Some(prefix) => {
+                    if let Some(prefix) = fts_words(prefix).pop() {
+                        terms.push(format!("\"{}\"*", prefix));
+                    }
+                }
+                None => {
+                    plain_text.push_str(word);
+                    plain_text.push(' ');
+                }
+            }
+        }
+    }
 
-    Ok(ranked
-        .into_iter()
-        .take(limit.max(0) as usize)
-        .map(|(_, memory)| memory)
-        .collect())
+    terms.extend(
+        extract_search_tokens(&plain_text)
+            .into_iter()
+            .map(|token| format!("\"{}\"", token)),
+    );
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" OR "))
+    }
+}
+
+fn fts_words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_string())
+        .collect()
 }
 
 fn extract_search_tokens(query: &str) -> Vec<String> {
@@ -232,19 +405,6 @@ fn extract_search_tokens(query: &str) -> Vec<String> {
     tokens
 }
 
-fn overlap_score(content: &str, query_tokens: &[String]) -> usize {
-    let normalized = content
-        .to_lowercase()
-        .chars()
-        .map(|c| if c.is_ascii_alphanumeric() || c.is_whitespace() { c } else { ' ' })
-        .collect::<String>();
-
-    query_tokens
-        .iter()
-        .filter(|token| normalized.contains(token.as_str()))
-        .count()
-}
-
 fn find_near_duplicate_memory(
     conn: &Connection,
     user_id: &str,
@@ -260,7 +420,7 @@ fn find_near_duplicate_memory(
         .prepare(
             "SELECT CAST(id AS TEXT), content
              FROM memories
-             WHERE user_id = ?1 AND content IS NOT NULL
+             WHERE user_id = ?1 AND content IS NOT NULL AND deleted_at IS NULL
              ORDER BY created_at DESC
              LIMIT 300",
         )
@@ -324,15 +484,40 @@ fn text_similarity(left: &str, right: &str) -> f64 {
     }
 }
 
+/// Soft-deletes a memory. It disappears from every read path but can be
+/// brought back with `restore_memory` until the retention window passes.
 pub fn delete_memory(conn: &Connection, user_id: &str, memory_id: &str) -> Result<(), String> {
     conn.execute(
-        "DELETE FROM memories WHERE id = ?1 AND user_id = ?2",
-        params![memory_id, user_id],
+        "UPDATE memories SET deleted_at = ?1 WHERE id = ?2 AND user_id = ?3 AND deleted_at IS NULL",
+        params![current_timestamp(), memory_id, user_id],
     )
     .map_err(|e| format!("Failed to delete memory: {}", e))?;
+    purge_deleted_memories(conn, user_id)?;
     Ok(())
 }
 
+pub fn restore_memory(conn: &Connection, user_id: &str, memory_id: &str) -> Result<(), String> {
+    let restored = conn
+        .execute(
+            "UPDATE memories SET deleted_at = NULL WHERE id = ?1 AND user_id = ?2 AND deleted_at IS NOT NULL",
+            params![memory_id, user_id],
+        )
+        .map_err(|e| format!("Failed to restore memory: {}", e))?;
+    if restored == 0 {
+        return Err(format!("Memory {} can no longer be restored", memory_id));
+    }
+    Ok(())
+}
+
+/// Permanently removes memories deleted longer ago than the retention window.
+pub fn purge_deleted_memories(conn: &Connection, user_id: &str) -> Result<usize, String> {
+    conn.execute(
+        "DELETE FROM memories WHERE user_id = ?1 AND deleted_at IS NOT NULL AND deleted_at < ?2",
+        params![user_id, current_timestamp() - DELETED_MEMORY_RETENTION_SECONDS],
+    )
+    .map_err(|e| format!("Failed to purge deleted memories: {}", e))
+}
+
 fn current_timestamp() -> i64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -340,7 +525,7 @@ fn current_timestamp() -> i64 {
         .unwrap_or(0) as i64
 }
 
-fn memories_id_is_integer(conn: &Connection) -> Result<bool, String> {
+pub fn memories_id_is_integer(conn: &Connection) -> Result<bool, String> {
     let mut stmt = conn
         .prepare("PRAGMA table_info(memories)")
         .map_err(|e| format!("Failed to inspect memories schema: {}", e))?;
@@ -366,3 +551,102 @@ fn memories_id_is_integer(conn: &Connection) -> Result<bool, String> {
 
     Ok(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn database_with_memories(contents: &[&str]) -> (Connection, Vec<String>) {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::database::initialize_database(&conn).unwrap();
+        let mut ids = Vec::new();
+        for (index, content) in contents.iter().enumerate() {
+            let id = format!("m{}", index);
+            conn.execute(
+                "INSERT INTO memories (id, user_id, content, created_at, updated_at, source)
+                 VALUES (?1, 'u1', ?2, 0, 0, 'user_input')",
+                params![id, content],
+            )
+            .unwrap();
+            ids.push(id);
+        }
+        (conn, ids)
+    }
+
+    fn hit_ids(conn: &Connection, query: &str) -> Vec<String> {
+        full_text_search(conn, "u1", query, 10)
+            .unwrap()
+            .into_iter()
+            .map(|hit| hit.memory.id)
+            .collect()
+    }
+
+    #[test]
+    fn fts_index_follows_inserts_updates_and_deletes() {
+        let (conn, ids) = database_with_memories(&["Send the quarterly report to Anna"]);
+        assert_eq!(hit_ids(&conn, "quarterly"), vec![ids[0].clone()]);
+
+        update_memory_content(&conn, "u1", &ids[0], "Call the plumber about the leak").unwrap();
+        assert!(hit_ids(&conn, "quarterly").is_empty());
+        assert_eq!(hit_ids(&conn, "plumber"), vec![ids[0].clone()]);
+
+        conn.execute("DELETE FROM memories WHERE id = ?1", params![ids[0]]).unwrap();
+        let indexed: i64 = conn
+            .query_row("SELECT COUNT(*) FROM memories_fts", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(indexed, 0);
+        assert!(hit_ids(&conn, "plumber").is_empty());
+    }
+
+    #[test]
+    fn soft_deleted_memories_leave_full_text_results() {
+        let (conn, ids) = database_with_memories(&["Send the quarterly report to Anna"]);
+
+        delete_memory(&conn, "u1", &ids[0]).unwrap();
+        assert!(hit_ids(&conn, "quarterly").is_empty());
+
+        restore_memory(&conn, "u1", &ids[0]).unwrap();
+        assert_eq!(hit_ids(&conn, "quarterly"), vec![ids[0].clone()]);
+
+        conn.execute("UPDATE memories SET deleted_at = 0 WHERE id = ?1", params![ids[0]]).unwrap();
+        assert_eq!(purge_deleted_memories(&conn, "u1").unwrap(), 1);
+        let indexed: i64 = conn
+            .query_row("SELECT COUNT(*) FROM memories_fts", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(indexed, 0);
+    }
+
+    #[test]
+    fn fts_hits_are_ordered_by_bm25() {
+        let (conn, ids) = database_with_memories(&[
+            "Budget review with the whole team, then lunch, then the offsite planning session",
+            "Budget budget budget",
+        ]);
+
+        let hits = full_text_search(&conn, "u1", "budget", 10).unwrap();
+
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].memory.id, ids[1]);
+        assert!(hits[0].score < hits[1].score);
+    }
+
+    #[test]
+    fn fts_query_quotes_user_input() {
+        assert_eq!(
+            build_fts_query("\"quarterly report\" budg*").as_deref(),
+            Some("\"quarterly report\" OR \"budg\"*")
+        );
+        assert_eq!(build_fts_query("report -draft").as_deref(), Some("\"report\" OR \"draft\""));
+        assert_eq!(
+            build_fts_query("NEAR(report draft)").as_deref(),
+            Some("\"near\" OR \"report\" OR \"draft\"")
+        );
+        assert_eq!(build_fts_query("* \"\" AND OR NOT").as_deref(), Some("\"not\""));
+        assert_eq!(build_fts_query("* \"\""), None);
+
+        let (conn, _) = database_with_memories(&["Send the quarterly report to Anna"]);
+        for query in ["say \"unbalanced", "-report", "NEAR(report anna, 2)", "*", "report\"*", "col:report"] {
+            assert!(full_text_search(&conn, "u1", query, 10).is_ok(), "query {:?} failed", query);
+        }
+    }
+}
@@ -5,6 +5,8 @@ use std::time::{SystemTime, UNIX_EPOCH};
 /// Service module for memory tag operations
 /// Handles creation, linking, and retrieval of memory tags
 
+const MAX_TAG_LENGTH: usize = 40;
+
 #[derive(Debug, Clone)]
 pub struct MemoryTag {
     pub id: String,
@@ -16,6 +18,7 @@ pub struct MemoryTag {
 /// Create a new tag
 pub fn create_tag(
     conn: &Connection,
+    user_id: &str,
     tag_name: String,
     color: Option<String>,
 ) -> Result<String, String> {
@@ -23,9 +26,9 @@ pub fn create_tag(
     let now = current_timestamp();
     
     conn.execute(
-        "INSERT INTO memory_tags (id, tag, color, created_at)
-         VALUES (?1, ?2, ?3, ?4)",
-        params![tag_id, tag_name, color, now],
+        "INSERT INTO memory_tags (id, user_id, tag, color, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![tag_id, user_id, tag_name, color, now],
     )
     .map_err(|e| format!("Failed to create tag: {}", e))?;
     
@@ -36,36 +39,46 @@ pub fn create_tag(
 /// Returns the tag ID (either existing or newly created)
 pub fn get_or_create_tag(
     conn: &Connection,
+    user_id: &str,
     tag_name: String,
     color: Option<String>,
 ) -> Result<String, String> {
     // Check if tag already exists
     let existing: Result<String, _> = conn.query_row(
-        "SELECT id FROM memory_tags WHERE tag = ?1",
-        params![tag_name],
+        "SELECT id FROM memory_tags WHERE user_id = ?1 AND tag = ?2",
+        params![user_id, tag_name],
         |row| row.get(0),
     );
     
     match existing {
         Ok(id) => Ok(id),
-        Err(_) => create_tag(conn, tag_name, color),
+        Err(_) => create_tag(conn, user_id, tag_name, color),
     }
 }
 
 /// Attach a tag to a memory
+/// Both the memory and the tag must belong to the user
 pub fn attach_tag_to_memory(
     conn: &Connection,
+    user_id: &str,
     memory_id: &str,
     tag_id: &str,
 ) -> Result<(), String> {
     let now = current_timestamp();
     
-    conn.execute(
-        "INSERT OR IGNORE INTO memory_tag_links (memory_id, tag_id, created_at)
-         VALUES (?1, ?2, ?3)",
-        params![memory_id, tag_id, now],
-    )
-    .map_err(|e| format!("Failed to link tag to memory: {}", e))?;
+    let linked = conn
+        .execute(
+            "INSERT OR IGNORE INTO memory_tag_links (memory_id, tag_id, created_at)
+             SELECT ?1, ?2, ?3
+             WHERE EXISTS (SELECT 1 FROM memories WHERE CAST(id AS TEXT) = ?1 AND user_id = ?4)
+               AND EXISTS (SELECT 1 FROM memory_tags WHERE id = ?2 AND user_id = ?4)",
+            params![memory_id, tag_id, now, user_id],
+        )
+        .map_err(|e| format!("Failed to link tag to memory: {}", e))?;
+    
+    if linked == 0 && !is_linked(conn, memory_id, tag_id)? {
+        return Err(format!("Memory {} or tag {} not found", memory_id, tag_id));
+    }
     
     Ok(())
 }
@@ -73,23 +86,48 @@ pub fn attach_tag_to_memory(
 /// Attach a tag to a memory by tag name (creates tag if needed)
 pub fn attach_tag_by_name(
     conn: &Connection,
+    user_id: &str,
     memory_id: &str,
     tag_name: String,
 ) -> Result<String, String> {
-    let tag_id = get_or_create_tag(conn, tag_name, None)?;
-    attach_tag_to_memory(conn, memory_id, &tag_id)?;
+    let tag_id = get_or_create_tag(conn, user_id, tag_name, None)?;
+    attach_tag_to_memory(conn, user_id, memory_id, &tag_id)?;
     Ok(tag_id)
 }
 
+/// Attach several tags by name, skipping blanks and duplicates.
+/// Returns the normalized tag names that were attached.
+pub fn attach_tags_by_name(
+    conn: &Connection,
+    user_id: &str,
+    memory_id: &str,
+    tag_names: &[String],
+) -> Result<Vec<String>, String> {
+    let mut attached: Vec<String> = Vec::new();
+    for raw in tag_names {
+        if let Some(tag_name) = normalize_tag_name(raw) {
+            if !attached.contains(&tag_name) {
+                attach_tag_by_name(conn, user_id, memory_id, tag_name.clone())?;
+                attached.push(tag_name);
+            }
+        }
+    }
+    
+    Ok(attached)
+}
+
 /// Detach a tag from a memory
 pub fn detach_tag_from_memory(
     conn: &Connection,
+    user_id: &str,
     memory_id: &str,
     tag_id: &str,
 ) -> Result<(), String> {
     conn.execute(
-        "DELETE FROM memory_tag_links WHERE memory_id = ?1 AND tag_id = ?2",
-        params![memory_id, tag_id],
+        "DELETE FROM memory_tag_links
+         WHERE memory_id = ?1
+           AND tag_id IN (SELECT id FROM memory_tags WHERE id = ?2 AND user_id = ?3)",
+        params![memory_id, tag_id, user_id],
     )
     .map_err(|e| format!("Failed to unlink tag from memory: {}", e))?;
     
@@ -99,6 +137,7 @@ pub fn detach_tag_from_memory(
 /// Get all tags for a specific memory
 pub fn get_memory_tags(
     conn: &Connection,
+    user_id: &str,
     memory_id: &str,
 ) -> Result<Vec<MemoryTag>, String> {
     let mut stmt = conn
@@ -106,13 +145,13 @@ pub fn get_memory_tags(
             "SELECT mt.id, mt.tag, mt.color, mt.created_at
              FROM memory_tags mt
              JOIN memory_tag_links mtl ON mt.id = mtl.tag_id
-             WHERE mtl.memory_id = ?1
+             WHERE mtl.memory_id = ?1 AND mt.user_id = ?2
              ORDER BY mt.tag ASC",
         )
         .map_err(|e| format!("Failed to prepare statement: {}", e))?;
     
     let tags = stmt
-        .query_map(params![memory_id], |row| {
+        .query_map(params![memory_id, user_id], |row| {
             Ok(MemoryTag {
                 id: row.get(0)?,
                 tag: row.get(1)?,
@@ -130,23 +169,24 @@ pub fn get_memory_tags(
 /// Get all memories with a specific tag
 pub fn get_memories_by_tag(
     conn: &Connection,
+    user_id: &str,
     tag_name: &str,
     limit: i32,
 ) -> Result<Vec<String>, String> {
     let mut stmt = conn
         .prepare(
-            "SELECT m.id
+            "SELECT CAST(m.id AS TEXT)
              FROM memories m
              JOIN memory_tag_links mtl ON m.id = mtl.memory_id
              JOIN memory_tags mt ON mtl.tag_id = mt.id
-             WHERE mt.tag = ?1
+             WHERE mt.tag = ?1 AND mt.user_id = ?2 AND m.user_id = ?2 AND m.deleted_at IS NULL
              ORDER BY m.created_at DESC
-             LIMIT ?2",
+             LIMIT ?3",
         )
         .map_err(|e| format!("Failed to prepare statement: {}", e))?;
     
     let memory_ids = stmt
-        .query_map(params![tag_name, limit], |row| row.get(0))
+        .query_map(params![tag_name, user_id, limit], |row| row.get(0))
         .map_err(|e| format!("Failed to query memories by tag: {}", e))?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| format!("Failed to map memory IDs: {}", e))?;
@@ -155,17 +195,18 @@ pub fn get_memories_by_tag(
 }
 
 /// Get all tags
-pub fn get_all_tags(conn: &Connection) -> Result<Vec<MemoryTag>, String> {
+pub fn get_all_tags(conn: &Connection, user_id: &str) -> Result<Vec<MemoryTag>, String> {
     let mut stmt = conn
         .prepare(
             "SELECT id, tag, color, created_at
              FROM memory_tags
+             WHERE user_id = ?1
              ORDER BY tag ASC",
         )
         .map_err(|e| format!("Failed to prepare statement: {}", e))?;
     
     let tags = stmt
-        .query_map([], |row| {
+        .query_map(params![user_id], |row| {
             Ok(MemoryTag {
                 id: row.get(0)?,
                 tag: row.get(1)?,
@@ -181,11 +222,12 @@ pub fn get_all_tags(conn: &Connection) -> Result<Vec<MemoryTag>, String> {
 }
 
 /// Get tag count
-pub fn get_tag_count(conn: &Connection) -> Result<i64, String> {
+#[allow(dead_code)]
+pub fn get_tag_count(conn: &Connection, user_id: &str) -> Result<i64, String> {
     let count: i64 = conn
         .query_row(
-            "SELECT COUNT(*) FROM memory_tags",
-            [],
+            "SELECT COUNT(*) FROM memory_tags WHERE user_id = ?1",
+            params![user_id],
             |row| row.get(0),
         )
         .map_err(|e| format!("Failed to count tags: {}", e))?;
@@ -196,14 +238,16 @@ pub fn get_tag_count(conn: &Connection) -> Result<i64, String> {
 /// Get count of memories for a tag
 pub fn get_tag_memory_count(
     conn: &Connection,
+    user_id: &str,
     tag_name: &str,
 ) -> Result<i64, String> {
     let count: i64 = conn
         .query_row(
             "SELECT COUNT(*) FROM memory_tag_links mtl
              JOIN memory_tags mt ON mtl.tag_id = mt.id
-             WHERE mt.tag = ?1",
-            params![tag_name],
+             JOIN memories m ON m.id = mtl.memory_id
+             WHERE mt.tag = ?1 AND mt.user_id = ?2 AND m.deleted_at IS NULL",
+            params![tag_name, user_id],
             |row| row.get(0),
         )
         .map_err(|e| format!("Failed to count memories for tag: {}", e))?;
@@ -214,11 +258,12 @@ pub fn get_tag_memory_count(
 /// Delete a tag (will also remove all links)
 pub fn delete_tag(
     conn: &Connection,
+    user_id: &str,
     tag_id: &str,
 ) -> Result<(), String> {
     conn.execute(
-        "DELETE FROM memory_tags WHERE id = ?1",
-        params![tag_id],
+        "DELETE FROM memory_tags WHERE id = ?1 AND user_id = ?2",
+        params![tag_id, user_id],
     )
     .map_err(|e| format!("Failed to delete tag: {}", e))?;
     
@@ -226,13 +271,15 @@ pub fn delete_tag(
 }
 
 /// Delete a tag by name
+#[allow(dead_code)]
 pub fn delete_tag_by_name(
     conn: &Connection,
+    user_id: &str,
     tag_name: &str,
 ) -> Result<(), String> {
     conn.execute(
-        "DELETE FROM memory_tags WHERE tag = ?1",
-        params![tag_name],
+        "DELETE FROM memory_tags WHERE tag = ?1 AND user_id = ?2",
+        params![tag_name, user_id],
     )
     .map_err(|e| format!("Failed to delete tag: {}", e))?;
     
@@ -242,12 +289,13 @@ pub fn delete_tag_by_name(
 /// Update tag color
 pub fn update_tag_color(
     conn: &Connection,
+    user_id: &str,
     tag_id: &str,
     color: Option<String>,
 ) -> Result<(), String> {
     conn.execute(
-        "UPDATE memory_tags SET color = ?1 WHERE id = ?2",
-        params![color, tag_id],
+        "UPDATE memory_tags SET color = ?1 WHERE id = ?2 AND user_id = ?3",
+        params![color, tag_id, user_id],
     )
     .map_err(|e| format!("Failed to update tag color: {}", e))?;
     
@@ -257,6 +305,7 @@ pub fn update_tag_color(
 /// Get most frequently used tags
 pub fn get_popular_tags(
     conn: &Connection,
+    user_id: &str,
     limit: i32,
 ) -> Result<Vec<(String, i64)>, String> {
     let mut stmt = conn
@@ -264,14 +313,16 @@ pub fn get_popular_tags(
             "SELECT mt.tag, COUNT(*) as count
              FROM memory_tags mt
              JOIN memory_tag_links mtl ON mt.id = mtl.tag_id
+             JOIN memories m ON m.id = mtl.memory_id
+             WHERE mt.user_id = ?1 AND m.deleted_at IS NULL
              GROUP BY mt.id
              ORDER BY count DESC
-             LIMIT ?1",
+             LIMIT ?2",
         )
         .map_err(|e| format!("Failed to prepare statement: {}", e))?;
     
     let tags = stmt
-        .query_map(params![limit], |row| {
+        .query_map(params![user_id, limit], |row| {
             Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
         })
         .map_err(|e| format!("Failed to query popular tags: {}", e))?
@@ -285,6 +336,64 @@ pub fn get_popular_tags(
 // Helper Functions
 // ============================================================================
 
+/// Canonical tag form: no leading '#', lowercase, words joined by '-'
+pub fn normalize_tag_name(raw: &str) -> Option<String> {
+    let normalized = raw
+        .trim()
+        .trim_start_matches('#')
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+        .take(MAX_TAG_LENGTH)
+        .collect::<String>();
+    
+    let normalized = normalized.trim_matches(|c| c == '-' || c == '_').to_string();
+    if normalized.is_empty() {
+        None
+    } else {
+        Some(normalized)
+    }
+}
+
+/// Pull `#hashtags` out of memory text. A tag must start a word and contain
+/// at least one letter, so "#1" or "C#" are left alone.
+pub fn extract_hashtags(content: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    
+    for word in content.split_whitespace() {
+        let Some(body) = word.strip_prefix('#') else {
+            continue;
+        };
+        let raw: String = body
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+            .collect();
+        if !raw.chars().any(char::is_alphabetic) {
+            continue;
+        }
+        if let Some(tag) = normalize_tag_name(&raw) {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+    }
+    
+    tags
+}
+
+fn is_linked(conn: &Connection, memory_id: &str, tag_id: &str) -> Result<bool, String> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM memory_tag_links WHERE memory_id = ?1 AND tag_id = ?2",
+        params![memory_id, tag_id],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+    .map_err(|e| format!("Failed to check tag link: {}", e))
+}
+
 fn current_timestamp() -> i64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
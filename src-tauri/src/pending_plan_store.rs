@@ -0,0 +1,221 @@
+use crate::ai::planner::action_plan::ActionPlan;
+use crate::ai::tool_outcome::{FollowUp, ToolOutcome};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How long a paused plan waits for the user before it can no longer be resumed.
+pub const PENDING_PLAN_TTL_SECONDS: i64 = 30 * 60;
+
+#[derive(Debug, Clone)]
+pub struct PendingPlan {
+    pub id: String,
+    pub user_message: String,
+    pub plan: ActionPlan,
+    /// Index of the step waiting for confirmation.
+    pub next_step: usize,
+    /// Outputs of the steps that already ran, read by later step placeholders.
+    pub outputs: Vec<ToolOutcome>,
+    /// Memories the paused step could act on; empty when it only needs a yes/no.
+    pub choices: Vec<FollowUp>,
+    pub status: String,
+}
+
+pub mod status {
+    pub const PENDING: &str = "pending";
+    /// Confirmed and currently executing
+    pub const RUNNING: &str = "running";
+    pub const COMPLETED: &str = "completed";
+    pub const CANCELLED: &str = "cancelled";
+    pub const FAILED: &str = "failed";
+    /// Not confirmed within `PENDING_PLAN_TTL_SECONDS`
+    pub const EXPIRED: &str = "expired";
+}
+
+/// Persists a plan under `plan_id`, the same id its steps are journaled with.
+pub fn create_pending_plan(
+    conn: &Connection,
+    plan_id: &str,
+    user_id: &str,
+    user_message: &str,
+    plan: &ActionPlan,
+    next_step: usize,
+    outputs: &[ToolOutcome],
+) -> Result<(), String> {
+    purge_stale_plans(conn)?;
+
+    let now = current_timestamp();
+    let plan_json = serde_json::to_string(plan).map_err(|e| format!("Failed to serialize plan: {}", e))?;
+    let responses_json =
+        serde_json::to_string(outputs).map_err(|e| format!("Failed to serialize plan outputs: {}", e))?;
+
+    conn.execute(
+        "INSERT INTO pending_plans (id, user_id, user_message, plan_json, next_step, responses_json, status, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8)",
+        params![
+            plan_id,
+            user_id,
+            user_message,
+            plan_json,
+            next_step as i64,
+            responses_json,
+            status::PENDING,
+            now
+        ],
+    )
+    .map_err(|e| format!("Failed to create pending plan: {}", e))?;
+
+    Ok(())
+}
+
+/// Moves a pending plan to the next step awaiting confirmation. Any choices
+/// offered for an earlier step are cleared.
+pub fn advance_pending_plan(
+    conn: &Connection,
+    plan_id: &str,
+    next_step: usize,
+    outputs: &[ToolOutcome],
+) -> Result<(), String> {
+    let responses_json =
+        serde_json::to_string(outputs).map_err(|e| format!("Failed to serialize plan outputs: {}", e))?;
+
+    conn.execute(
+        "UPDATE pending_plans
+         SET next_step = ?1, responses_json = ?2, choices_json = NULL, status = ?3, updated_at = ?4
+         WHERE id = ?5",
+        params![
+            next_step as i64,
+            responses_json,
+            status::PENDING,
+            current_timestamp(),
+            plan_id
+        ],
+    )
+    .map_err(|e| format!("Failed to update pending plan: {}", e))?;
+
+    Ok(())
+}
+
+/// Fetches a plan owned by `user_id`. A pending plan past its TTL is marked
+/// expired before it is returned.
+pub fn get_pending_plan(conn: &Connection, user_id: &str, plan_id: &str) -> Result<Option<PendingPlan>, String> {
+    let row = conn
+        .query_row(
+            "SELECT id, user_message, plan_json, next_step, responses_json, status, updated_at, choices_json
+             FROM pending_plans
+             WHERE id = ?1 AND user_id = ?2",
+            params![plan_id, user_id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, String>(5)?,
+                    row.get::<_, i64>(6)?,
+                    row.get::<_, Option<String>>(7)?,
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| format!("Failed to query pending plan: {}", e))?;
+
+    let Some((id, user_message, plan_json, next_step, responses_json, mut plan_status, updated_at, choices_json)) = row
+    else {
+        return Ok(None);
+    };
+
+    if plan_status == status::PENDING && current_timestamp() - updated_at > PENDING_PLAN_TTL_SECONDS {
+        set_plan_status(conn, &id, status::EXPIRED)?;
+        plan_status = status::EXPIRED.to_string();
+    }
+
+    Ok(Some(PendingPlan {
+        plan: serde_json::from_str(&plan_json).map_err(|e| format!("Failed to parse stored plan: {}", e))?,
+        outputs: serde_json::from_str(&responses_json)
+            .map_err(|e| format!("Failed to parse stored plan outputs: {}", e))?,
+        choices: match choices_json {
+            Some(raw) => {
+                serde_json::from_str(&raw).map_err(|e| format!("Failed to parse stored plan choices: {}", e))?
+            }
+            None => Vec::new(),
+        },
+        next_step: next_step.max(0) as usize,
+        id,
+        user_message,
+        status: plan_status,
+    }))
+}
+
+/// Marks a pending plan as running. False when another caller got there first.
+pub fn claim_pending_plan(conn: &Connection, plan_id: &str) -> Result<bool, String> {
+    let updated = conn
+        .execute(
+            "UPDATE pending_plans SET status = ?1, updated_at = ?2 WHERE id = ?3 AND status = ?4",
+            params![status::RUNNING, current_timestamp(), plan_id, status::PENDING],
+        )
+        .map_err(|e| format!("Failed to claim pending plan: {}", e))?;
+
+    Ok(updated == 1)
+}
+
+/// Stores the candidates the paused step offers the user.
+pub fn set_plan_choices(conn: &Connection, plan_id: &str, choices: &[FollowUp]) -> Result<(), String> {
+    let choices_json =
+        serde_json::to_string(choices).map_err(|e| format!("Failed to serialize plan choices: {}", e))?;
+    conn.execute(
+        "UPDATE pending_plans SET choices_json = ?1, updated_at = ?2 WHERE id = ?3",
+        params![choices_json, current_timestamp(), plan_id],
+    )
+    .map_err(|e| format!("Failed to store plan choices: {}", e))?;
+
+    Ok(())
+}
+
+/// The user's most recent plan that is still waiting for them to pick a choice.
+pub fn latest_plan_awaiting_choice(conn: &Connection, user_id: &str) -> Result<Option<PendingPlan>, String> {
+    let plan_id: Option<String> = conn
+        .query_row(
+            "SELECT id FROM pending_plans
+             WHERE user_id = ?1 AND status = ?2 AND choices_json IS NOT NULL
+             ORDER BY updated_at DESC
+             LIMIT 1",
+            params![user_id, status::PENDING],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to query pending plan choices: {}", e))?;
+
+    match plan_id {
+        Some(plan_id) => Ok(get_pending_plan(conn, user_id, &plan_id)?
+            .filter(|plan| plan.status == status::PENDING && !plan.choices.is_empty())),
+        None => Ok(None),
+    }
+}
+
+pub fn set_plan_status(conn: &Connection, plan_id: &str, new_status: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE pending_plans SET status = ?1, updated_at = ?2 WHERE id = ?3",
+        params![new_status, current_timestamp(), plan_id],
+    )
+    .map_err(|e| format!("Failed to update pending plan status: {}", e))?;
+
+    Ok(())
+}
+
+/// Drops plans untouched for longer than the TTL, whatever their status.
+fn purge_stale_plans(conn: &Connection) -> Result<usize, String> {
+    let cutoff = current_timestamp() - PENDING_PLAN_TTL_SECONDS;
+    conn.execute(
+        "DELETE FROM pending_plans WHERE updated_at < ?1",
+        params![cutoff],
+    )
+    .map_err(|e| format!("Failed to purge pending plans: {}", e))
+}
+
+fn current_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0) as i64
+}
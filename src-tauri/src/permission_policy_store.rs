@@ -0,0 +1,306 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+use crate::{Capability, PermissionManager};
+
+pub mod policy {
+    pub const ALLOW: &str = "allow";
+    pub const DENY: &str = "deny";
+    pub const ASK: &str = "ask";
+}
+
+/// Why a permission decision came out the way it did.
+pub mod source {
+    /// The capability is switched off in the permissions file
+    pub const SETTINGS: &str = "settings";
+    /// The user's stored policy for the capability
+    pub const POLICY: &str = "policy";
+    /// An earlier "allow for this session" answer
+    pub const SESSION_GRANT: &str = "session_grant";
+    /// The user's answer to an approval prompt
+    pub const USER: &str = "user";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Allow,
+    Deny,
+    /// Pause and ask the user before using the capability.
+    Ask,
+}
+
+impl Decision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Decision::Allow => policy::ALLOW,
+            Decision::Deny => policy::DENY,
+            Decision::Ask => policy::ASK,
+        }
+    }
+}
+
+/// How long an approval given at an ask prompt lasts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GrantScope {
+    Once,
+    Session,
+    /// Stores an allow policy, so the user is not asked again.
+    Always,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CapabilityPolicy {
+    pub capability: String,
+    pub policy: String,
+    /// None while the capability still has the default policy.
+    pub updated_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionDecisionRecord {
+    pub id: String,
+    pub capability: String,
+    pub decision: String,
+    pub source: String,
+    pub intent: Option<String>,
+    pub created_at: i64,
+}
+
+/// The user's policy for `capability`; allow when none is stored.
+pub fn get_policy(conn: &Connection, user_id: &str, capability: Capability) -> Result<String, String> {
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT policy FROM permission_policies WHERE user_id = ?1 AND capability = ?2",
+            params![user_id, capability.as_str()],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to query permission policy: {}", e))?;
+
+    Ok(stored.unwrap_or_else(|| policy::ALLOW.to_string()))
+}
+
+/// One entry per capability, defaults included.
+pub fn list_policies(conn: &Connection, user_id: &str) -> Result<Vec<CapabilityPolicy>, String> {
+    let mut stmt = conn
+        .prepare("SELECT capability, policy, updated_at FROM permission_policies WHERE user_id = ?1")
+        .map_err(|e| format!("Failed to prepare permission policy query: {}", e))?;
+    let stored = stmt
+        .query_map(params![user_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?))
+        })
+        .map_err(|e| format!("Failed to query permission policies: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to map permission policies: {}", e))?;
+
+    Ok(Capability::ALL
+        .iter()
+        .map(|capability| {
+            let row = stored.iter().find(|(name, _, _)| name == capability.as_str());
+            CapabilityPolicy {
+                capability: capability.as_str().to_string(),
+                policy: row
+                    .map(|(_, policy, _)| policy.clone())
+                    .unwrap_or_else(|| policy::ALLOW.to_string()),
+                updated_at: row.map(|(_, _, updated_at)| *updated_at),
+            }
+        })
+        .collect())
+}
+
+pub fn set_policy(conn: &Connection, user_id: &str, capability: Capability, new_policy: &str) -> Result<(), String> {
+    if ![policy::ALLOW, policy::DENY, policy::ASK].contains(&new_policy) {
+        return Err(format!("Unknown permission policy: {}", new_policy));
+    }
+
+    conn.execute(
+        "INSERT INTO permission_policies (user_id, capability, policy, updated_at)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(user_id, capability) DO UPDATE SET policy = excluded.policy, updated_at = excluded.updated_at",
+        params![user_id, capability.as_str(), new_policy, current_timestamp()],
+    )
+    .map_err(|e| format!("Failed to store permission policy: {}", e))?;
+
+    Ok(())
+}
+
+/// Combines the permissions file, the user's policy and session grants. The
+/// file wins: a capability switched off there is denied whatever the policy.
+pub fn evaluate(
+    conn: &Connection,
+    permissions: &PermissionManager,
+    user_id: &str,
+    capability: Capability,
+) -> Result<(Decision, &'static str), String> {
+    if !permissions.allows(capability) {
+        return Ok((Decision::Deny, source::SETTINGS));
+    }
+
+    match get_policy(conn, user_id, capability)?.as_str() {
+        policy::DENY => Ok((Decision::Deny, source::POLICY)),
+        policy::ASK if permissions.has_session_grant(user_id, capability) => {
+            Ok((Decision::Allow, source::SESSION_GRANT))
+        }
+        policy::ASK => Ok((Decision::Ask, source::POLICY)),
+        _ => Ok((Decision::Allow, source::POLICY)),
+    }
+}
+
+/// Checks and records one use of `capability` outside the plan executor.
+/// With `user_present` the request itself comes from the user and answers an
+/// ask policy; background work has nobody to ask, so ask refuses it.
+pub fn authorize(
+    conn: &Connection,
+    permissions: &PermissionManager,
+    user_id: &str,
+    capability: Capability,
+    intent: &str,
+    user_present: bool,
+) -> Result<(), String> {
+    let (decision, decided_by) = evaluate(conn, permissions, user_id, capability)?;
+    let asked = decision == Decision::Ask;
+    let decision = match decision {
+        Decision::Ask if user_present => Decision::Allow,
+        Decision::Ask => Decision::Deny,
+        other => other,
+    };
+    record_decision(conn, user_id, capability, decision, decided_by, Some(intent))?;
+
+    match decision {
+        Decision::Deny if decided_by == source::SETTINGS => permissions.check_permission(capability),
+        Decision::Deny if asked => Err(format!(
+            "Using {} needs your approval, which a background task cannot ask for.",
+            capability.as_str()
+        )),
+        Decision::Deny => Err(format!("You've blocked Noddy from using {}.", capability.as_str())),
+        _ => Ok(()),
+    }
+}
+
+/// Stores the user's answer to an ask prompt and remembers it for `scope`.
+pub fn answer_prompt(
+    conn: &Connection,
+    permissions: &PermissionManager,
+    user_id: &str,
+    capability: Capability,
+    approved: bool,
+    scope: GrantScope,
+    intent: Option<&str>,
+) -> Result<(), String> {
+    let decision = if approved { Decision::Allow } else { Decision::Deny };
+    record_decision(conn, user_id, capability, decision, source::USER, intent)?;
+    if approved {
+        match scope {
+            GrantScope::Once => {}
+            GrantScope::Session => permissions.grant_for_session(user_id, capability),
+            GrantScope::Always => set_policy(conn, user_id, capability, policy::ALLOW)?,
+        }
+    }
+    Ok(())
+}
+
+pub fn record_decision(
+    conn: &Connection,
+    user_id: &str,
+    capability: Capability,
+    decision: Decision,
+    decided_by: &str,
+    intent: Option<&str>,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO permission_decisions (id, user_id, capability, decision, source, intent, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            Uuid::new_v4().to_string(),
+            user_id,
+            capability.as_str(),
+            decision.as_str(),
+            decided_by,
+            intent,
+            current_timestamp()
+        ],
+    )
+    .map_err(|e| format!("Failed to record permission decision: {}", e))?;
+
+    Ok(())
+}
+
+/// Most recent decisions first.
+pub fn list_decisions(conn: &Connection, user_id: &str, limit: i64) -> Result<Vec<PermissionDecisionRecord>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, capability, decision, source, intent, created_at
+             FROM permission_decisions
+             WHERE user_id = ?1
+             ORDER BY created_at DESC, rowid DESC
+             LIMIT ?2",
+        )
+        .map_err(|e| format!("Failed to prepare permission decision query: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![user_id, limit.clamp(1, 500)], |row| {
+            Ok(PermissionDecisionRecord {
+                id: row.get(0)?,
+                capability: row.get(1)?,
+                decision: row.get(2)?,
+                source: row.get(3)?,
+                intent: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query permission decisions: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to map permission decisions: {}", e))?;
+
+    Ok(rows)
+}
+
+fn current_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn direct_requests_follow_the_user_policy_and_are_recorded() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::database::initialize_database(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO users (id, email, password_hash, created_at) VALUES ('u1', 'a@b.c', 'x', 0)",
+            [],
+        )
+        .unwrap();
+        let permissions = PermissionManager::default_permissions();
+
+        set_policy(&conn, "u1", Capability::MemoryWrite, policy::DENY).unwrap();
+        let err = authorize(&conn, &permissions, "u1", Capability::MemoryWrite, "delete_memory", true).unwrap_err();
+        assert_eq!(err, "You've blocked Noddy from using memory_write.");
+
+        set_policy(&conn, "u1", Capability::PluginAccess, policy::ASK).unwrap();
+        authorize(&conn, &permissions, "u1", Capability::PluginAccess, "plugin_command", true).unwrap();
+        assert!(authorize(&conn, &permissions, "u1", Capability::PluginAccess, "plugin_sync", false).is_err());
+
+        let trail = list_decisions(&conn, "u1", 10)
+            .unwrap()
+            .into_iter()
+            .rev()
+            .map(|record| format!("{}:{}:{}", record.intent.unwrap_or_default(), record.capability, record.decision))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            trail,
+            vec![
+                "delete_memory:memory_write:deny",
+                "plugin_command:plugin_access:allow",
+                "plugin_sync:plugin_access:deny"
+            ]
+        );
+    }
+}
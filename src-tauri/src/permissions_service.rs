@@ -95,6 +95,7 @@ pub fn get_user_permissions(
 #[tauri::command]
 pub fn update_user_permission(
     state: tauri::State<PermissionsState>,
+    manager: tauri::State<crate::PermissionManager>,
     permission: String,
     value: bool,
 ) -> Result<UserPermissions, String> {
@@ -111,5 +112,6 @@ pub fn update_user_permission(
     }
     state.save()?;
     let permissions = state.permissions.lock().map_err(|e| e.to_string())?;
+    manager.apply_user_permissions(&permissions);
     Ok(permissions.clone())
 }
@@ -1,4 +1,6 @@
+use crate::task_registry::TaskHandler;
 use serde_json::Value;
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub enum PluginEvent {
@@ -16,4 +18,8 @@ pub trait Plugin: Send + Sync {
     fn initialize(&self, config_json: Option<&str>) -> Result<(), String>;
     fn handle_event(&self, event: &PluginEvent, config_json: Option<&str>) -> Result<(), String>;
     fn execute_command(&self, command: &str, config_json: Option<&str>) -> Result<Value, String>;
+    /// Background task types this plugin can run, registered with the worker on load.
+    fn task_handlers(&self) -> Vec<Arc<dyn TaskHandler>> {
+        Vec::new()
+    }
 }
\ No newline at end of file
@@ -234,6 +234,9 @@ fn get_plugin_config(conn: &Connection, plugin_id: &str) -> Result<Option<String
 }
 
 fn register_builtin_plugin(handlers: &mut HashMap<String, Arc<dyn Plugin>>, plugin: Arc<dyn Plugin>) {
+    for task_handler in plugin.task_handlers() {
+        crate::task_registry::register_handler(task_handler);
+    }
     handlers.insert(plugin.id().to_string(), plugin);
 }
 
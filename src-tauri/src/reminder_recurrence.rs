@@ -0,0 +1,399 @@
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Upper bound on how far ahead we look for the next matching day.
+const MAX_SEARCH_DAYS: i64 = 366 * 2;
+
+const TIME_OF_DAY_FORMAT: &str = "%H:%M:%S";
+
+/// Longest clock jump we step over when a wall-clock time doesn't exist.
+const MAX_DST_GAP_MINUTES: i64 = 24 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Frequency {
+    Hourly,
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// RRULE-style repeat rule stored as JSON in `reminders.recurrence`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    /// Every N hours/days/weeks/months
+    #[serde(default = "default_interval")]
+    pub interval: u32,
+    /// Weekly rules only: "mon".."sun". Empty means the weekday of the first occurrence.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub weekdays: Vec<String>,
+    /// Monthly rules only: day of month, clamped to the month's length.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub month_day: Option<u32>,
+    /// Local "HH:MM:SS" every occurrence aims for, even after a DST gap moved one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_of_day: Option<String>,
+    /// No occurrence is scheduled after this timestamp.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<i64>,
+    /// Total number of occurrences, including the first one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<u32>,
+    /// Occurrences fired so far.
+    #[serde(default)]
+    pub occurrences: u32,
+    /// Nominal time of the pending occurrence. Snoozing moves `trigger_at`
+    /// but not this, so later occurrences keep their original time of day.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduled_at: Option<i64>,
+}
+
+fn default_interval() -> u32 {
+    1
+}
+
+impl Recurrence {
+    pub fn new(frequency: Frequency) -> Self {
+        Self {
+            frequency,
+            interval: 1,
+            weekdays: Vec::new(),
+            month_day: None,
+            time_of_day: None,
+            until: None,
+            count: None,
+            occurrences: 0,
+            scheduled_at: None,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.interval == 0 {
+            return Err("Recurrence interval must be at least 1".to_string());
+        }
+
+        for day in &self.weekdays {
+            if parse_weekday_code(day).is_none() {
+                return Err(format!("Invalid recurrence weekday: {}", day));
+            }
+        }
+
+        if let Some(day) = self.month_day {
+            if !(1..=31).contains(&day) {
+                return Err(format!("Invalid recurrence month day: {}", day));
+            }
+        }
+
+        if let Some(time) = &self.time_of_day {
+            if parse_time_of_day(time).is_none() {
+                return Err(format!("Invalid recurrence time of day: {}", time));
+            }
+        }
+
+        if self.count == Some(0) {
+            return Err("Recurrence count must be at least 1".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Pins the rule to its first occurrence so later ones keep that weekday,
+    /// day of month and time of day.
+    pub fn anchor_at(&mut self, trigger_at: i64) {
+        self.anchor_in(&Local, trigger_at)
+    }
+
+    fn anchor_in<Tz: TimeZone>(&mut self, tz: &Tz, trigger_at: i64) {
+        self.scheduled_at = Some(trigger_at);
+
+        let Some(first) = tz.timestamp_opt(trigger_at, 0).single() else {
+            return;
+        };
+        self.time_of_day = Some(first.time().format(TIME_OF_DAY_FORMAT).to_string());
+        match self.frequency {
+            Frequency::Weekly if self.weekdays.is_empty() => {
+                self.weekdays = vec![weekday_code(first.weekday()).to_string()];
+            }
+            Frequency::Monthly if self.month_day.is_none() => {
+                self.month_day = Some(first.day());
+            }
+            _ => {}
+        }
+    }
+
+    /// Short human description, e.g. "every 2 weeks on mon, fri".
+    pub fn describe(&self) -> String {
+        let unit = match self.frequency {
+            Frequency::Hourly => "hour",
+            Frequency::Daily => "day",
+            Frequency::Weekly => "week",
+            Frequency::Monthly => "month",
+        };
+
+        let mut text = if self.interval <= 1 {
+            format!("every {}", unit)
+        } else {
+            format!("every {} {}s", self.interval, unit)
+        };
+
+        if self.frequency == Frequency::Weekly && !self.weekdays.is_empty() {
+            text.push_str(&format!(" on {}", self.weekdays.join(", ")));
+        }
+        if let (Frequency::Monthly, Some(day)) = (self.frequency, self.month_day) {
+            text.push_str(&format!(" on day {}", day));
+        }
+        if let Some(count) = self.count {
+            text.push_str(&format!(", {} times", count));
+        }
+        text
+    }
+
+    fn matches_day(&self, date: NaiveDate, anchor: NaiveDate) -> bool {
+        match self.frequency {
+            Frequency::Hourly | Frequency::Daily => true,
+            Frequency::Weekly => {
+                if self.weekdays.is_empty() {
+                    date.weekday() == anchor.weekday()
+                } else {
+                    self.weekdays
+                        .iter()
+                        .filter_map(|day| parse_weekday_code(day))
+                        .any(|day| day == date.weekday())
+                }
+            }
+            Frequency::Monthly => {
+                let wanted = self.month_day.unwrap_or_else(|| anchor.day());
+                date.day() == wanted.min(days_in_month(date.year(), date.month()))
+            }
+        }
+    }
+}
+
+/// First occurrence at `time` that is strictly after `now`.
+pub fn first_occurrence(rule: &Recurrence, time: NaiveTime, now: i64) -> Option<i64> {
+    if rule.frequency == Frequency::Hourly {
+        return Some(now + 3600 * rule.interval.max(1) as i64);
+    }
+
+    let today = local_datetime(now)?.date_naive();
+    (0..MAX_SEARCH_DAYS)
+        .map(|offset| today + Duration::days(offset))
+        .filter(|date| rule.matches_day(*date, today))
+        .filter_map(|date| local_timestamp(date, time))
+        .find(|ts| *ts > now)
+}
+
+/// The occurrence that follows `previous`, honouring the interval but not `until`/`count`.
+pub fn next_occurrence(rule: &Recurrence, previous: i64) -> Option<i64> {
+    next_occurrence_in(&Local, rule, previous)
+}
+
+fn next_occurrence_in<Tz: TimeZone>(tz: &Tz, rule: &Recurrence, previous: i64) -> Option<i64> {
+    let interval = rule.interval.max(1) as i64;
+    let current = tz.timestamp_opt(previous, 0).single()?;
+    let date = current.date_naive();
+    let time = rule
+        .time_of_day
+        .as_deref()
+        .and_then(parse_time_of_day)
+        .unwrap_or_else(|| current.time());
+
+    match rule.frequency {
+        Frequency::Hourly => Some(previous + 3600 * interval),
+        Frequency::Daily => zoned_timestamp(tz, date + Duration::days(interval), time),
+        Frequency::Weekly => {
+            let week_start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+            (1..MAX_SEARCH_DAYS)
+                .map(|offset| date + Duration::days(offset))
+                .filter(|candidate| {
+                    let weeks = (*candidate - week_start).num_days() / 7;
+                    weeks % interval == 0 && rule.matches_day(*candidate, date)
+                })
+                .find_map(|candidate| zoned_timestamp(tz, candidate, time))
+        }
+        Frequency::Monthly => {
+            let wanted = rule.month_day.unwrap_or_else(|| date.day());
+            let months = date.year() as i64 * 12 + date.month0() as i64 + interval;
+            let year = (months / 12) as i32;
+            let month = (months % 12) as u32 + 1;
+            let day = wanted.min(days_in_month(year, month));
+            zoned_timestamp(tz, NaiveDate::from_ymd_opt(year, month, day)?, time)
+        }
+    }
+}
+
+/// Counts the occurrence scheduled at `previous` as fired and returns the
+/// updated rule with the next occurrence after `now`, or None when the series ends.
+pub fn advance(rule: &Recurrence, previous: i64, now: i64) -> Option<(i64, Recurrence)> {
+    let mut next_rule = rule.clone();
+    next_rule.occurrences = rule.occurrences.saturating_add(1);
+
+    if let Some(count) = rule.count {
+        if next_rule.occurrences >= count {
+            return None;
+        }
+    }
+
+    let mut next = next_occurrence(rule, previous)?;
+    while next <= now {
+        next = next_occurrence(rule, next)?;
+    }
+
+    if let Some(until) = rule.until {
+        if next > until {
+            return None;
+        }
+    }
+
+    next_rule.scheduled_at = Some(next);
+    Some((next, next_rule))
+}
+
+pub fn parse_weekday_code(value: &str) -> Option<Weekday> {
+    match value.trim().to_lowercase().as_str() {
+        "monday" | "mon" | "mo" => Some(Weekday::Mon),
+        "tuesday" | "tue" | "tues" | "tu" => Some(Weekday::Tue),
+        "wednesday" | "wed" | "we" => Some(Weekday::Wed),
+        "thursday" | "thu" | "thurs" | "th" => Some(Weekday::Thu),
+        "friday" | "fri" | "fr" => Some(Weekday::Fri),
+        "saturday" | "sat" | "sa" => Some(Weekday::Sat),
+        "sunday" | "sun" | "su" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+pub fn weekday_code(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "mon",
+        Weekday::Tue => "tue",
+        Weekday::Wed => "wed",
+        Weekday::Thu => "thu",
+        Weekday::Fri => "fri",
+        Weekday::Sat => "sat",
+        Weekday::Sun => "sun",
+    }
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|first| first.pred_opt())
+        .map(|last| last.day())
+        .unwrap_or(28)
+}
+
+fn parse_time_of_day(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value, TIME_OF_DAY_FORMAT).ok()
+}
+
+fn local_datetime(timestamp: i64) -> Option<chrono::DateTime<Local>> {
+    Local.timestamp_opt(timestamp, 0).single()
+}
+
+fn local_timestamp(date: NaiveDate, time: NaiveTime) -> Option<i64> {
+    zoned_timestamp(&Local, date, time)
+}
+
+/// Resolves a wall-clock time in `tz`. A time skipped by a DST jump resolves to
+/// the first valid minute after the gap, so the series keeps going.
+fn zoned_timestamp<Tz: TimeZone>(tz: &Tz, date: NaiveDate, time: NaiveTime) -> Option<i64> {
+    let wanted = NaiveDateTime::new(date, time);
+    (0..=MAX_DST_GAP_MINUTES)
+        .find_map(|minutes| {
+            tz.from_local_datetime(&(wanted + Duration::minutes(minutes)))
+                .earliest()
+        })
+        .map(|dt| dt.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, LocalResult, Offset, Utc};
+
+    /// UTC that springs forward to UTC+1 at 02:00 on 2030-03-10.
+    #[derive(Clone)]
+    struct SpringForward;
+
+    impl SpringForward {
+        fn switch() -> NaiveDateTime {
+            NaiveDate::from_ymd_opt(2030, 3, 10)
+                .unwrap()
+                .and_hms_opt(2, 0, 0)
+                .unwrap()
+        }
+    }
+
+    impl TimeZone for SpringForward {
+        type Offset = FixedOffset;
+
+        fn from_offset(_offset: &FixedOffset) -> Self {
+            SpringForward
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            let switch = Self::switch();
+            if *local < switch {
+                LocalResult::Single(Utc.fix())
+            } else if *local < switch + Duration::hours(1) {
+                LocalResult::None
+            } else {
+                LocalResult::Single(FixedOffset::east_opt(3600).unwrap())
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            if *utc < Self::switch() {
+                Utc.fix()
+            } else {
+                FixedOffset::east_opt(3600).unwrap()
+            }
+        }
+    }
+
+    fn at(tz: &SpringForward, y: i32, m: u32, d: u32, h: u32, min: u32) -> i64 {
+        tz.with_ymd_and_hms(y, m, d, h, min, 0)
+            .single()
+            .unwrap()
+            .timestamp()
+    }
+
+    #[test]
+    fn daily_series_survives_a_dst_gap() {
+        let tz = SpringForward;
+        let mut rule = Recurrence::new(Frequency::Daily);
+        rule.anchor_in(&tz, at(&tz, 2030, 3, 9, 2, 30));
+
+        let in_gap = next_occurrence_in(&tz, &rule, at(&tz, 2030, 3, 9, 2, 30));
+        assert_eq!(in_gap, Some(at(&tz, 2030, 3, 10, 3, 0)));
+
+        let after_gap = next_occurrence_in(&tz, &rule, in_gap.unwrap());
+        assert_eq!(after_gap, Some(at(&tz, 2030, 3, 11, 2, 30)));
+    }
+
+    #[test]
+    fn monthly_series_clamps_to_the_end_of_shorter_months() {
+        let tz = SpringForward;
+        let mut rule = Recurrence::new(Frequency::Monthly);
+        rule.month_day = Some(31);
+
+        let february = next_occurrence_in(&tz, &rule, at(&tz, 2030, 1, 31, 9, 0));
+        assert_eq!(february, Some(at(&tz, 2030, 2, 28, 9, 0)));
+
+        let march = next_occurrence_in(&tz, &rule, february.unwrap());
+        assert_eq!(march, Some(at(&tz, 2030, 3, 31, 9, 0)));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::reminder_recurrence::Recurrence;
 use rusqlite::{params, Connection};
 use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
@@ -11,12 +12,15 @@ pub struct Reminder {
     pub status: String,
     pub source: String,
     pub memory_id: Option<String>,
+    pub recurrence: Option<Recurrence>,
 }
 
 pub mod status {
     pub const PENDING: &str = "pending";
     pub const TRIGGERED: &str = "triggered";
     pub const SNOOZED: &str = "snoozed";
+    /// Came due while the app was closed and was dropped by the catch-up policy
+    pub const MISSED: &str = "missed";
 }
 
 pub fn create_reminder(
@@ -25,13 +29,15 @@ pub fn create_reminder(
     content: String,
     trigger_at: i64,
     memory_id: Option<String>,
+    recurrence: Option<&Recurrence>,
 ) -> Result<String, String> {
     let id = Uuid::new_v4().to_string();
     let now = current_timestamp();
+    let recurrence_json = recurrence.map(recurrence_to_column).transpose()?;
 
     conn.execute(
-        "INSERT INTO reminders (id, user_id, content, created_at, trigger_at, status, source, memory_id)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        "INSERT INTO reminders (id, user_id, content, created_at, trigger_at, status, source, memory_id, recurrence)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
         params![
             id,
             user_id,
@@ -40,7 +46,8 @@ pub fn create_reminder(
             trigger_at,
             status::PENDING,
             "user_request",
-            memory_id
+            memory_id,
+            recurrence_json
         ],
     )
     .map_err(|e| format!("Failed to create reminder: {}", e))?;
@@ -51,7 +58,7 @@ pub fn create_reminder(
 pub fn get_pending_reminders(conn: &Connection, user_id: &str) -> Result<Vec<Reminder>, String> {
     let mut stmt = conn
         .prepare(
-            "SELECT id, user_id, content, trigger_at, status, source, memory_id
+            "SELECT id, user_id, content, trigger_at, status, source, memory_id, recurrence
              FROM reminders
              WHERE user_id = ?1 AND status = ?2
              ORDER BY trigger_at ASC",
@@ -68,6 +75,7 @@ pub fn get_pending_reminders(conn: &Connection, user_id: &str) -> Result<Vec<Rem
                 status: row.get(4)?,
                 source: row.get(5)?,
                 memory_id: row.get(6)?,
+                recurrence: recurrence_from_column(row.get(7)?),
             })
         })
         .map_err(|e| format!("Failed to query reminders: {}", e))?
@@ -79,7 +87,7 @@ pub fn get_pending_reminders(conn: &Connection, user_id: &str) -> Result<Vec<Rem
 
 pub fn get_reminder(conn: &Connection, user_id: &str, reminder_id: &str) -> Result<Reminder, String> {
     conn.query_row(
-        "SELECT id, user_id, content, trigger_at, status, source, memory_id
+        "SELECT id, user_id, content, trigger_at, status, source, memory_id, recurrence
          FROM reminders
          WHERE id = ?1 AND user_id = ?2",
         params![reminder_id, user_id],
@@ -92,6 +100,7 @@ pub fn get_reminder(conn: &Connection, user_id: &str, reminder_id: &str) -> Resu
                 status: row.get(4)?,
                 source: row.get(5)?,
                 memory_id: row.get(6)?,
+                recurrence: recurrence_from_column(row.get(7)?),
             })
         },
     )
@@ -105,7 +114,7 @@ pub fn update_reminder_status(
     new_status: &str,
 ) -> Result<(), String> {
     match new_status {
-        status::PENDING | status::TRIGGERED | status::SNOOZED => {}
+        status::PENDING | status::TRIGGERED | status::SNOOZED | status::MISSED => {}
         _ => return Err(format!("Invalid status: {}", new_status)),
     }
 
@@ -136,6 +145,46 @@ pub fn snooze_reminder(
     Ok(())
 }
 
+/// Stores when an occurrence was delivered (None if it was dropped) and how late it was.
+pub fn record_reminder_fire(
+    conn: &Connection,
+    user_id: &str,
+    reminder_id: &str,
+    fired_at: Option<i64>,
+    lateness_seconds: i64,
+) -> Result<(), String> {
+    conn.execute(
+        "UPDATE reminders SET fired_at = ?1, lateness_seconds = ?2 WHERE id = ?3 AND user_id = ?4",
+        params![fired_at, lateness_seconds, reminder_id, user_id],
+    )
+    .map_err(|e| format!("Failed to record reminder fire time: {}", e))?;
+
+    Ok(())
+}
+
+/// Moves a recurring reminder on to its next occurrence and stores the updated rule.
+pub fn advance_recurring_reminder(
+    conn: &Connection,
+    user_id: &str,
+    reminder_id: &str,
+    next_trigger_at: i64,
+    recurrence: &Recurrence,
+) -> Result<(), String> {
+    conn.execute(
+        "UPDATE reminders SET trigger_at = ?1, status = ?2, recurrence = ?3 WHERE id = ?4 AND user_id = ?5",
+        params![
+            next_trigger_at,
+            status::PENDING,
+            recurrence_to_column(recurrence)?,
+            reminder_id,
+            user_id
+        ],
+    )
+    .map_err(|e| format!("Failed to advance recurring reminder: {}", e))?;
+
+    Ok(())
+}
+
 pub fn delete_reminder(conn: &Connection, user_id: &str, reminder_id: &str) -> Result<(), String> {
     conn.execute(
         "DELETE FROM reminders WHERE id = ?1 AND user_id = ?2",
@@ -146,6 +195,15 @@ pub fn delete_reminder(conn: &Connection, user_id: &str, reminder_id: &str) -> R
     Ok(())
 }
 
+pub fn recurrence_to_column(recurrence: &Recurrence) -> Result<String, String> {
+    serde_json::to_string(recurrence).map_err(|e| format!("Failed to serialize recurrence: {}", e))
+}
+
+/// Unreadable rules degrade to a one-off reminder rather than failing the whole query.
+pub fn recurrence_from_column(raw: Option<String>) -> Option<Recurrence> {
+    raw.and_then(|json| serde_json::from_str(&json).ok())
+}
+
 fn current_timestamp() -> i64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -1,5 +1,7 @@
-use crate::reminder_store::Reminder;
-use rusqlite::{params, Connection};
+use crate::reminder_recurrence::Recurrence;
+use crate::reminder_store::{self, Reminder};
+use crate::task_registry;
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
@@ -11,6 +13,9 @@ pub struct ScheduledTask {
     pub payload: String,
     pub execute_at: i64,
     pub status: String,
+    /// Failed runs so far; above zero while the task waits for a retry.
+    #[serde(default)]
+    pub attempts: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,20 +25,57 @@ pub struct ReminderTaskPayload {
     pub content: String,
     pub trigger_at: i64,
     pub memory_id: Option<String>,
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+}
+
+/// A task that used up its attempts and is parked until someone requeues it.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadLetterTask {
+    pub task_id: String,
+    pub task_type: String,
+    pub payload: String,
+    pub execute_at: i64,
+    pub attempts: i64,
+    pub last_error: Option<String>,
+    pub updated_at: Option<i64>,
+}
+
+/// What happened to a task after a failed run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureDisposition {
+    Retry { next_attempt_at: i64 },
+    DeadLettered,
 }
 
 pub mod status {
     pub const PENDING: &str = "pending";
     pub const COMPLETED: &str = "completed";
-    pub const FAILED: &str = "failed";
     pub const CANCELLED: &str = "cancelled";
+    pub const DEAD_LETTER: &str = "dead_letter";
+    /// Overdue reminder dropped by the missed-reminder policy
+    pub const SKIPPED: &str = "skipped";
 }
 
+/// First retry waits this long; each further attempt doubles it.
+const RETRY_BASE_DELAY_SECONDS: i64 = 30;
+const RETRY_MAX_DELAY_SECONDS: i64 = 3600;
+
 pub mod task_type {
     pub const REMINDER: &str = "reminder";
+    pub const GRAPH_REBUILD: &str = "graph_rebuild";
+    pub const MEMORY_DECAY: &str = "memory_decay";
+    pub const PLUGIN_SYNC: &str = "plugin_sync";
+    pub const DATA_EXPORT: &str = "data_export";
+
+    /// Types `schedule_background_task` accepts from the frontend. Reminder
+    /// tasks only come from reminders themselves.
+    pub const USER_SCHEDULABLE: &[&str] = &[GRAPH_REBUILD, MEMORY_DECAY, PLUGIN_SYNC, DATA_EXPORT];
 }
 
 pub fn register_task(conn: &Connection, task: ScheduledTask) -> Result<String, String> {
+    task_registry::validate_task_payload(&task.task_type, &task.payload)?;
+
     let now = current_timestamp();
     conn.execute(
         "INSERT OR REPLACE INTO background_tasks (task_id, task_type, payload, execute_at, status, created_at, updated_at)
@@ -59,6 +101,7 @@ pub fn schedule_reminder(conn: &Connection, reminder: &Reminder) -> Result<Strin
         content: reminder.content.clone(),
         trigger_at: reminder.trigger_at,
         memory_id: reminder.memory_id.clone(),
+        recurrence: reminder.recurrence.clone(),
     })
     .map_err(|e| format!("Failed to serialize reminder task payload: {}", e))?;
 
@@ -70,6 +113,7 @@ pub fn schedule_reminder(conn: &Connection, reminder: &Reminder) -> Result<Strin
             payload,
             execute_at: reminder.trigger_at,
             status: status::PENDING.to_string(),
+            attempts: 0,
         },
     )
 }
@@ -78,10 +122,10 @@ pub fn get_pending_tasks(conn: &Connection, limit: i32) -> Result<Vec<ScheduledT
     let now = current_timestamp();
     let mut stmt = conn
         .prepare(
-            "SELECT task_id, task_type, payload, execute_at, status
+            "SELECT task_id, task_type, payload, execute_at, status, COALESCE(attempts, 0)
              FROM background_tasks
-             WHERE status = ?1 AND execute_at <= ?2
-             ORDER BY execute_at ASC
+             WHERE status = ?1 AND COALESCE(next_attempt_at, execute_at) <= ?2
+             ORDER BY COALESCE(next_attempt_at, execute_at) ASC
              LIMIT ?3",
         )
         .map_err(|e| format!("Failed to prepare pending tasks query: {}", e))?;
@@ -94,6 +138,7 @@ pub fn get_pending_tasks(conn: &Connection, limit: i32) -> Result<Vec<ScheduledT
                 payload: row.get(2)?,
                 execute_at: row.get(3)?,
                 status: row.get(4)?,
+                attempts: row.get(5)?,
             })
         })
         .map_err(|e| format!("Failed to query pending tasks: {}", e))?;
@@ -106,8 +151,112 @@ pub fn mark_task_completed(conn: &Connection, task_id: &str) -> Result<(), Strin
     update_task_status(conn, task_id, status::COMPLETED)
 }
 
-pub fn mark_task_failed(conn: &Connection, task_id: &str) -> Result<(), String> {
-    update_task_status(conn, task_id, status::FAILED)
+/// Records a failed run. The task is retried with exponential backoff until
+/// `max_attempts` runs have failed, then moved to the dead-letter state.
+pub fn record_task_failure(
+    conn: &Connection,
+    task_id: &str,
+    error: &str,
+    max_attempts: u32,
+) -> Result<FailureDisposition, String> {
+    let previous_attempts: i64 = conn
+        .query_row(
+            "SELECT attempts FROM background_tasks WHERE task_id = ?1",
+            params![task_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to load task attempts: {}", e))?;
+
+    let attempts = previous_attempts + 1;
+    let now = current_timestamp();
+    let disposition = if attempts >= max_attempts.max(1) as i64 {
+        FailureDisposition::DeadLettered
+    } else {
+        FailureDisposition::Retry {
+            next_attempt_at: now + retry_delay_seconds(attempts),
+        }
+    };
+
+    let (next_status, next_attempt_at) = match disposition {
+        FailureDisposition::Retry { next_attempt_at } => (status::PENDING, Some(next_attempt_at)),
+        FailureDisposition::DeadLettered => (status::DEAD_LETTER, None),
+    };
+
+    conn.execute(
+        "UPDATE background_tasks
+         SET status = ?1, attempts = ?2, last_error = ?3, next_attempt_at = ?4, updated_at = ?5
+         WHERE task_id = ?6",
+        params![next_status, attempts, error, next_attempt_at, now, task_id],
+    )
+    .map_err(|e| format!("Failed to record task failure: {}", e))?;
+
+    Ok(disposition)
+}
+
+/// Delay before retry number `attempts` (1-based), capped at one hour.
+pub fn retry_delay_seconds(attempts: i64) -> i64 {
+    let exponent = (attempts - 1).clamp(0, 16) as u32;
+    (RETRY_BASE_DELAY_SECONDS * 2_i64.pow(exponent)).min(RETRY_MAX_DELAY_SECONDS)
+}
+
+/// Dead-lettered tasks whose payload belongs to `user_id`, most recent first.
+pub fn get_dead_letter_tasks(conn: &Connection, user_id: &str) -> Result<Vec<DeadLetterTask>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT task_id, task_type, payload, execute_at, attempts, last_error, updated_at
+             FROM background_tasks
+             WHERE status = ?1 AND json_extract(payload, '$.user_id') = ?2
+             ORDER BY updated_at DESC",
+        )
+        .map_err(|e| format!("Failed to prepare dead-letter query: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![status::DEAD_LETTER, user_id], |row| {
+            Ok(DeadLetterTask {
+                task_id: row.get(0)?,
+                task_type: row.get(1)?,
+                payload: row.get(2)?,
+                execute_at: row.get(3)?,
+                attempts: row.get(4)?,
+                last_error: row.get(5)?,
+                updated_at: row.get(6)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query dead-letter tasks: {}", e))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect dead-letter tasks: {}", e))
+}
+
+/// Puts dead-lettered tasks back in the queue with a fresh attempt budget.
+/// With no `task_id`, every dead-lettered task of the user is requeued.
+pub fn requeue_dead_letter_tasks(
+    conn: &Connection,
+    user_id: &str,
+    task_id: Option<&str>,
+) -> Result<usize, String> {
+    let requeued = conn
+        .execute(
+            "UPDATE background_tasks
+             SET status = ?1, attempts = 0, next_attempt_at = ?2, updated_at = ?2
+             WHERE status = ?3
+               AND json_extract(payload, '$.user_id') = ?4
+               AND (?5 IS NULL OR task_id = ?5)",
+            params![status::PENDING, current_timestamp(), status::DEAD_LETTER, user_id, task_id],
+        )
+        .map_err(|e| format!("Failed to requeue dead-letter tasks: {}", e))?;
+
+    if requeued == 0 {
+        if let Some(task_id) = task_id {
+            return Err(format!("Dead-lettered task {} not found", task_id));
+        }
+    }
+
+    Ok(requeued)
+}
+
+pub fn mark_task_skipped(conn: &Connection, task_id: &str) -> Result<(), String> {
+    update_task_status(conn, task_id, status::SKIPPED)
 }
 
 pub fn cancel_task(conn: &Connection, task_id: &str) -> Result<(), String> {
@@ -121,7 +270,7 @@ pub fn cancel_reminder_task(conn: &Connection, reminder_id: &str) -> Result<(),
 pub fn sync_reminder_tasks(conn: &Connection) -> Result<i64, String> {
     let mut stmt = conn
         .prepare(
-            "SELECT id, user_id, content, trigger_at, status, source, memory_id
+            "SELECT id, user_id, content, trigger_at, status, source, memory_id, recurrence
              FROM reminders
              WHERE status IN ('pending', 'snoozed')
              ORDER BY trigger_at ASC",
@@ -138,6 +287,7 @@ pub fn sync_reminder_tasks(conn: &Connection) -> Result<i64, String> {
                 status: row.get(4)?,
                 source: row.get(5)?,
                 memory_id: row.get(6)?,
+                recurrence: reminder_store::recurrence_from_column(row.get(7)?),
             })
         })
         .map_err(|e| format!("Failed to query reminders for sync: {}", e))?;
@@ -148,13 +298,30 @@ pub fn sync_reminder_tasks(conn: &Connection) -> Result<i64, String> {
 
     let mut scheduled = 0_i64;
     for reminder in reminders {
-        schedule_reminder(conn, &reminder)?;
-        scheduled += 1;
+        // Re-registering would reset the row, reviving dead letters and
+        // forgetting how many attempts a retrying task has used.
+        let existing: Option<(String, i64)> = conn
+            .query_row(
+                "SELECT status, COALESCE(attempts, 0) FROM background_tasks WHERE task_id = ?1",
+                params![reminder_task_id(&reminder.id)],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to load reminder task: {}", e))?;
+        let keep_existing = match &existing {
+            Some((task_status, _)) if task_status == status::DEAD_LETTER => true,
+            Some((task_status, attempts)) => task_status == status::PENDING && *attempts > 0,
+            None => false,
+        };
+        if !keep_existing {
+            schedule_reminder(conn, &reminder)?;
+            scheduled += 1;
+        }
 
-        if reminder.status == crate::reminder_store::status::SNOOZED {
+        if reminder.status == reminder_store::status::SNOOZED {
             conn.execute(
                 "UPDATE reminders SET status = ?1 WHERE id = ?2",
-                params![crate::reminder_store::status::PENDING, reminder.id],
+                params![reminder_store::status::PENDING, reminder.id],
             )
             .map_err(|e| format!("Failed to normalize snoozed reminder status: {}", e))?;
         }
@@ -179,7 +346,6 @@ pub fn reminder_task_id(reminder_id: &str) -> String {
     format!("reminder:{}", reminder_id)
 }
 
-#[allow(dead_code)]
 pub fn generate_task_id() -> String {
     Uuid::new_v4().to_string()
 }
@@ -189,4 +355,39 @@ fn current_timestamp() -> i64 {
         .duration_since(UNIX_EPOCH)
         .map(|d| d.as_secs() as i64)
         .unwrap_or(0)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn startup_sync_leaves_dead_letter_reminder_tasks_alone() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::database::initialize_database(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO users (id, email, password_hash, created_at) VALUES ('u1', 'a@b.c', 'x', 0)",
+            [],
+        )
+        .unwrap();
+        let reminder_id = reminder_store::create_reminder(&conn, "u1", "Water plants".to_string(), 1_000, None, None).unwrap();
+        let reminder = reminder_store::get_reminder(&conn, "u1", &reminder_id).unwrap();
+        schedule_reminder(&conn, &reminder).unwrap();
+        let task_id = reminder_task_id(&reminder.id);
+        let disposition = record_task_failure(&conn, &task_id, "notification failed", 1).unwrap();
+        assert_eq!(disposition, FailureDisposition::DeadLettered);
+
+        assert_eq!(sync_reminder_tasks(&conn).unwrap(), 0);
+
+        let (task_status, attempts, last_error): (String, i64, Option<String>) = conn
+            .query_row(
+                "SELECT status, attempts, last_error FROM background_tasks WHERE task_id = ?1",
+                params![task_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(task_status, status::DEAD_LETTER);
+        assert_eq!(attempts, 1);
+        assert_eq!(last_error.as_deref(), Some("notification failed"));
+    }
+}
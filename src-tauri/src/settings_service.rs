@@ -14,6 +14,60 @@ pub struct AppSettings {
     pub theme: String, // "dark" | "light" | "system"
     pub suggestions_enabled: bool,
     pub auto_start: bool,
+    /// Runs a background task may fail before it is dead-lettered
+    #[serde(default = "default_task_max_attempts")]
+    pub task_max_attempts: u32,
+    /// What to do with reminders that came due while the app was closed
+    #[serde(default)]
+    pub missed_reminder_policy: MissedReminderPolicy,
+    /// Reminders at most this late still fire normally under every policy
+    #[serde(default = "default_missed_reminder_grace_minutes")]
+    pub missed_reminder_grace_minutes: u32,
+    /// Backend used for chat and planning calls
+    #[serde(default)]
+    pub llm_provider: LlmProviderKind,
+    /// Model name; empty uses the provider's default
+    #[serde(default)]
+    pub llm_model: Option<String>,
+    /// Override for the provider's API base URL (e.g. a self-hosted server)
+    #[serde(default)]
+    pub llm_base_url: Option<String>,
+    /// Bearer token for an OpenAI-compatible server; OPENAI_API_KEY only goes to api.openai.com
+    #[serde(default)]
+    pub llm_api_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissedReminderPolicy {
+    /// Fire every overdue reminder individually
+    #[default]
+    FireAll,
+    /// Fire reminders inside the grace window, drop older ones as missed
+    GraceWindow,
+    /// Fire reminders inside the grace window, collapse older ones into one notification
+    Digest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LlmProviderKind {
+    #[default]
+    Gemini,
+    /// Any server exposing OpenAI's `/chat/completions`
+    OpenaiCompatible,
+    Ollama,
+}
+
+pub const DEFAULT_TASK_MAX_ATTEMPTS: u32 = 5;
+pub const DEFAULT_MISSED_REMINDER_GRACE_MINUTES: u32 = 15;
+
+fn default_task_max_attempts() -> u32 {
+    DEFAULT_TASK_MAX_ATTEMPTS
+}
+
+fn default_missed_reminder_grace_minutes() -> u32 {
+    DEFAULT_MISSED_REMINDER_GRACE_MINUTES
 }
 
 impl Default for AppSettings {
@@ -24,6 +78,13 @@ impl Default for AppSettings {
             theme: "dark".to_string(),
             suggestions_enabled: true,
             auto_start: false,
+            task_max_attempts: DEFAULT_TASK_MAX_ATTEMPTS,
+            missed_reminder_policy: MissedReminderPolicy::default(),
+            missed_reminder_grace_minutes: DEFAULT_MISSED_REMINDER_GRACE_MINUTES,
+            llm_provider: LlmProviderKind::default(),
+            llm_model: None,
+            llm_base_url: None,
+            llm_api_key: None,
         }
     }
 }
@@ -94,6 +155,7 @@ pub fn update_settings(
     }
     state.save()?;
     let settings = state.settings.lock().map_err(|e| e.to_string())?;
+    crate::ai::llm_provider::configure_from_settings(&settings);
     Ok(settings.clone())
 }
 
@@ -2,9 +2,10 @@ use std::sync::{Mutex, OnceLock};
 
 use chrono::TimeZone;
 use rusqlite::Connection;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 
-use crate::{command_history_service, reminder_store, Event, EventBus};
+use crate::permissions_service::UserPermissions;
+use crate::{command_history_service, reminder_store, Capability, Event, EventBus, PermissionManager};
 
 use super::suggestion_engine;
 use super::suggestion_types::{Suggestion, SuggestionContext};
@@ -24,7 +25,8 @@ pub fn maybe_run_suggestion_cycle(
     event_bus: &EventBus,
     app_handle: Option<&AppHandle>,
 ) -> Result<(), String> {
-    if suggestions_disabled() {
+    let permissions = current_permissions(app_handle);
+    if suggestions_disabled() || !permissions.allows(Capability::BackgroundSuggestions) {
         return Ok(());
     }
 
@@ -42,7 +44,7 @@ pub fn maybe_run_suggestion_cycle(
     let user_ids = collect_candidate_user_ids(conn)?;
 
     for user_id in user_ids {
-        let context = build_context(conn, &user_id)?;
+        let context = build_context(conn, &user_id, &permissions)?;
         let suggestions = suggestion_engine::evaluate_suggestions(&context);
 
         for suggestion in suggestions {
@@ -53,8 +55,20 @@ pub fn maybe_run_suggestion_cycle(
     Ok(())
 }
 
-fn build_context(conn: &Connection, user_id: &str) -> Result<SuggestionContext, String> {
-    let sys = crate::system::system_context_service::get_system_context();
+/// The live permissions when running in the app, the defaults otherwise.
+fn current_permissions(app_handle: Option<&AppHandle>) -> PermissionManager {
+    app_handle
+        .and_then(|app| app.try_state::<PermissionManager>())
+        .map(|state| state.inner().clone())
+        .unwrap_or_else(|| PermissionManager::from_user_permissions(&UserPermissions::default()))
+}
+
+fn build_context(
+    conn: &Connection,
+    user_id: &str,
+    permissions: &PermissionManager,
+) -> Result<SuggestionContext, String> {
+    let sys = crate::system::system_context_service::get_system_context(permissions);
     let now = current_timestamp();
 
     let reminders = reminder_store::get_pending_reminders(conn, user_id)?
@@ -105,7 +119,7 @@ fn collect_upcoming_classes(conn: &Connection, user_id: &str) -> Result<Vec<(Str
 
     let mut stmt = conn
         .prepare(
-            "SELECT content FROM memories WHERE user_id = ?1 AND content LIKE '%class of %' AND content LIKE ?2 ORDER BY created_at DESC LIMIT 50",
+            "SELECT content FROM memories WHERE user_id = ?1 AND deleted_at IS NULL AND content LIKE '%class of %' AND content LIKE ?2 ORDER BY created_at DESC LIMIT 50",
         )
         .map_err(|e| format!("Failed to prepare class memory query: {}", e))?;
 
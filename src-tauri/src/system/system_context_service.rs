@@ -15,6 +15,7 @@ pub struct SystemContext {
 struct CachedSystemContext {
     context: SystemContext,
     captured_at_ts: i64,
+    includes_apps: bool,
 }
 
 fn cache_state() -> &'static Mutex<Option<CachedSystemContext>> {
@@ -22,7 +23,9 @@ fn cache_state() -> &'static Mutex<Option<CachedSystemContext>> {
     CACHE.get_or_init(|| Mutex::new(None))
 }
 
-pub fn get_system_context() -> SystemContext {
+/// Snapshot of the machine state. Window and process names are only read when
+/// `permissions` allows access to running apps.
+pub fn get_system_context(permissions: &crate::PermissionManager) -> SystemContext {
     if is_disabled() {
         return SystemContext {
             active_application: "Unknown".to_string(),
@@ -34,17 +37,21 @@ pub fn get_system_context() -> SystemContext {
         };
     }
 
+    let includes_apps = permissions.allows(crate::Capability::RunningApps);
     let now_ts = Local::now().timestamp();
     if let Ok(guard) = cache_state().lock() {
         if let Some(cache) = guard.as_ref() {
-            if now_ts - cache.captured_at_ts <= 10 {
+            if now_ts - cache.captured_at_ts <= 10 && cache.includes_apps == includes_apps {
                 return cache.context.clone();
             }
         }
     }
 
-    let active_application = get_active_window();
-    let running_applications = get_running_apps();
+    let (active_application, running_applications) = if includes_apps {
+        (get_active_window(), get_running_apps())
+    } else {
+        ("Unknown".to_string(), Vec::new())
+    };
     let (battery_level, network_status) = get_system_state();
     let idle_seconds = get_idle_seconds();
 
@@ -61,6 +68,7 @@ pub fn get_system_context() -> SystemContext {
         *guard = Some(CachedSystemContext {
             context: context.clone(),
             captured_at_ts: now_ts,
+            includes_apps,
         });
     }
 
@@ -0,0 +1,164 @@
+use crate::scheduler::ScheduledTask;
+use crate::EventBus;
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+use tauri::AppHandle;
+
+/// Everything a handler may touch while running a task.
+pub struct TaskContext<'a> {
+    pub conn: &'a Connection,
+    pub event_bus: &'a EventBus,
+    pub app_handle: Option<&'a AppHandle>,
+}
+
+/// What a handler did with its task row.
+pub enum TaskOutcome {
+    Completed,
+    /// The handler re-registered the same task id for a later run; leave it pending.
+    Rescheduled,
+    /// Not allowed to run. Retrying cannot help, so the task is dead-lettered at once.
+    Refused(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[allow(dead_code)]
+pub enum FieldKind {
+    String,
+    Integer,
+    Boolean,
+    Object,
+    Array,
+}
+
+/// One top-level key of a task payload. Unknown keys are allowed.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PayloadField {
+    pub name: &'static str,
+    pub kind: FieldKind,
+    pub required: bool,
+}
+
+impl PayloadField {
+    pub const fn required(name: &'static str, kind: FieldKind) -> Self {
+        Self { name, kind, required: true }
+    }
+
+    pub const fn optional(name: &'static str, kind: FieldKind) -> Self {
+        Self { name, kind, required: false }
+    }
+}
+
+/// Executes one kind of background task. Modules and plugins register
+/// implementations with `register_handler`; the worker dispatches on `task_type`.
+pub trait TaskHandler: Send + Sync {
+    fn task_type(&self) -> &'static str;
+    /// Checked by `scheduler::register_task` before a task is queued.
+    fn payload_schema(&self) -> &'static [PayloadField];
+    fn execute(&self, ctx: &TaskContext<'_>, task: &ScheduledTask) -> Result<TaskOutcome, String>;
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskTypeInfo {
+    pub task_type: String,
+    pub payload_schema: Vec<PayloadField>,
+}
+
+fn handler_slot() -> &'static RwLock<HashMap<String, Arc<dyn TaskHandler>>> {
+    static HANDLERS: OnceLock<RwLock<HashMap<String, Arc<dyn TaskHandler>>>> = OnceLock::new();
+    HANDLERS.get_or_init(|| {
+        let mut handlers: HashMap<String, Arc<dyn TaskHandler>> = HashMap::new();
+        for handler in builtin_handlers() {
+            handlers.insert(handler.task_type().to_string(), handler);
+        }
+        RwLock::new(handlers)
+    })
+}
+
+fn builtin_handlers() -> Vec<Arc<dyn TaskHandler>> {
+    vec![
+        Arc::new(crate::worker::ReminderTaskHandler),
+        Arc::new(crate::maintenance_tasks::GraphRebuildHandler),
+        Arc::new(crate::maintenance_tasks::MemoryDecayHandler),
+        Arc::new(crate::maintenance_tasks::PluginSyncHandler),
+        Arc::new(crate::maintenance_tasks::DataExportHandler),
+    ]
+}
+
+/// Adds or replaces the handler for its task type.
+pub fn register_handler(handler: Arc<dyn TaskHandler>) {
+    let task_type = handler.task_type().to_string();
+    match handler_slot().write() {
+        Ok(mut guard) => guard.insert(task_type, handler),
+        Err(poisoned) => poisoned.into_inner().insert(task_type, handler),
+    };
+}
+
+pub fn handler_for(task_type: &str) -> Option<Arc<dyn TaskHandler>> {
+    match handler_slot().read() {
+        Ok(guard) => guard.get(task_type).cloned(),
+        Err(poisoned) => poisoned.into_inner().get(task_type).cloned(),
+    }
+}
+
+pub fn registered_task_types() -> Vec<TaskTypeInfo> {
+    let handlers = match handler_slot().read() {
+        Ok(guard) => guard.values().cloned().collect::<Vec<_>>(),
+        Err(poisoned) => poisoned.into_inner().values().cloned().collect::<Vec<_>>(),
+    };
+
+    let mut types = handlers
+        .iter()
+        .map(|handler| TaskTypeInfo {
+            task_type: handler.task_type().to_string(),
+            payload_schema: handler.payload_schema().to_vec(),
+        })
+        .collect::<Vec<_>>();
+    types.sort_by(|a, b| a.task_type.cmp(&b.task_type));
+    types
+}
+
+/// Rejects unknown task types and payloads that don't match the handler's schema.
+pub fn validate_task_payload(task_type: &str, payload: &str) -> Result<(), String> {
+    let handler = handler_for(task_type).ok_or_else(|| format!("Unsupported task type: {}", task_type))?;
+
+    let value: Value = serde_json::from_str(payload)
+        .map_err(|e| format!("Invalid payload for task type {}: {}", task_type, e))?;
+    let object = value
+        .as_object()
+        .ok_or_else(|| format!("Payload for task type {} must be a JSON object", task_type))?;
+
+    for field in handler.payload_schema() {
+        match object.get(field.name).filter(|value| !value.is_null()) {
+            None if field.required => {
+                return Err(format!(
+                    "Payload for task type {} is missing '{}'",
+                    task_type, field.name
+                ));
+            }
+            None => {}
+            Some(value) if !matches_kind(value, field.kind) => {
+                return Err(format!(
+                    "Payload field '{}' for task type {} must be {:?}",
+                    field.name, task_type, field.kind
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn matches_kind(value: &Value, kind: FieldKind) -> bool {
+    match kind {
+        FieldKind::String => value.is_string(),
+        FieldKind::Integer => value.is_i64() || value.is_u64(),
+        FieldKind::Boolean => value.is_boolean(),
+        FieldKind::Object => value.is_object(),
+        FieldKind::Array => value.is_array(),
+    }
+}
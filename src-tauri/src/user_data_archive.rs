@@ -0,0 +1,914 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+use crate::reminder_recurrence::Recurrence;
+use crate::reminder_store::{self, Reminder};
+
+/// Portable per-user archive: one JSON object per line, header first.
+/// Bump ARCHIVE_VERSION whenever a record shape changes incompatibly.
+pub const ARCHIVE_FORMAT: &str = "noddy-user-archive";
+pub const ARCHIVE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveHeader {
+    pub format: String,
+    pub version: u32,
+    pub schema_version: i64,
+    pub exported_at: i64,
+    pub source_user_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryRecord {
+    pub id: String,
+    pub content: String,
+    pub created_at: Option<i64>,
+    pub updated_at: Option<i64>,
+    pub importance: Option<f64>,
+    pub access_count: Option<i64>,
+    pub last_accessed_at: Option<i64>,
+    pub source: Option<String>,
+    pub tags: Option<String>,
+    pub metadata: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagRecord {
+    pub id: String,
+    pub tag: String,
+    pub color: Option<String>,
+    pub created_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagLinkRecord {
+    pub memory_id: String,
+    pub tag_id: String,
+    pub created_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdgeRecord {
+    pub source_memory_id: String,
+    pub target_memory_id: String,
+    pub relationship: Option<String>,
+    pub weight: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReminderRecord {
+    pub id: String,
+    pub content: String,
+    pub created_at: Option<i64>,
+    pub trigger_at: i64,
+    pub status: Option<String>,
+    pub source: Option<String>,
+    pub memory_id: Option<String>,
+    pub metadata: Option<String>,
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    #[serde(default)]
+    pub fired_at: Option<i64>,
+    #[serde(default)]
+    pub lateness_seconds: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessageRecord {
+    pub role: String,
+    pub content: String,
+    pub created_at: i64,
+    pub metadata: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandRecord {
+    pub command_text: String,
+    pub intent_name: Option<String>,
+    pub duration_ms: Option<i64>,
+    pub success: Option<i64>,
+    pub timestamp: i64,
+    pub status: Option<String>,
+    pub error_message: Option<String>,
+    pub metadata: Option<String>,
+}
+
+/// The user's allow/deny/ask choice for one capability.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyRecord {
+    pub capability: String,
+    pub policy: String,
+    pub updated_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum ArchiveRecord {
+    Header(ArchiveHeader),
+    Memory(MemoryRecord),
+    Tag(TagRecord),
+    TagLink(TagLinkRecord),
+    Edge(EdgeRecord),
+    Reminder(ReminderRecord),
+    ChatMessage(ChatMessageRecord),
+    Command(CommandRecord),
+    Policy(PolicyRecord),
+}
+
+/// Counts of what an import actually wrote. Records already present are skipped.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportSummary {
+    pub memories: i64,
+    pub tags: i64,
+    pub reminders: i64,
+    pub scheduled_reminders: i64,
+    pub chat_messages: i64,
+    pub commands: i64,
+    pub policies: i64,
+    pub edges_rebuilt: i64,
+    pub skipped: i64,
+}
+
+// ============================================================================
+// EXPORT
+// ============================================================================
+
+/// Serialize every record owned by `user_id` into a JSON-lines archive.
+pub fn export_user_data(conn: &Connection, user_id: &str) -> Result<String, String> {
+    let mut records = vec![ArchiveRecord::Header(ArchiveHeader {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        schema_version: crate::database::latest_schema_version(),
+        exported_at: current_timestamp(),
+        source_user_id: user_id.to_string(),
+    })];
+
+    records.extend(export_memories(conn, user_id)?.into_iter().map(ArchiveRecord::Memory));
+    records.extend(export_tags(conn, user_id)?.into_iter().map(ArchiveRecord::Tag));
+    records.extend(export_tag_links(conn, user_id)?.into_iter().map(ArchiveRecord::TagLink));
+    records.extend(export_edges(conn, user_id)?.into_iter().map(ArchiveRecord::Edge));
+    records.extend(export_reminders(conn, user_id)?.into_iter().map(ArchiveRecord::Reminder));
+    records.extend(export_chat_messages(conn, user_id)?.into_iter().map(ArchiveRecord::ChatMessage));
+    records.extend(export_commands(conn, user_id)?.into_iter().map(ArchiveRecord::Command));
+    records.extend(export_policies(conn, user_id)?.into_iter().map(ArchiveRecord::Policy));
+
+    let mut archive = String::new();
+    for record in records {
+        let line = serde_json::to_string(&record)
+            .map_err(|e| format!("Failed to serialize archive record: {}", e))?;
+        archive.push_str(&line);
+        archive.push('\n');
+    }
+
+    Ok(archive)
+}
+
+/// Where an archive goes inside `exports_dir`: `file_name` when given, else a
+/// timestamped default. Only bare file names are accepted, so a caller cannot
+/// write anywhere outside the exports folder.
+pub fn export_path(exports_dir: &Path, file_name: Option<&str>) -> Result<PathBuf, String> {
+    let file_name = match file_name.map(str::trim).filter(|name| !name.is_empty()) {
+        Some(name) => {
+            let plain = Path::new(name).file_name().and_then(|n| n.to_str()) == Some(name);
+            if !plain || name.starts_with('.') || name.contains(['/', '\\']) {
+                return Err(format!("Invalid export file name: {}", name));
+            }
+            name.to_string()
+        }
+        None => format!("noddy-export-{}.jsonl", current_timestamp()),
+    };
+
+    std::fs::create_dir_all(exports_dir).map_err(|e| format!("Failed to create exports directory: {}", e))?;
+    Ok(exports_dir.join(file_name))
+}
+
+fn export_memories(conn: &Connection, user_id: &str) -> Result<Vec<MemoryRecord>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT CAST(id AS TEXT), content, created_at, updated_at, importance, access_count,
+                    last_accessed_at, source, tags, metadata
+             FROM memories
+             WHERE user_id = ?1 AND content IS NOT NULL AND deleted_at IS NULL
+             ORDER BY created_at ASC",
+        )
+        .map_err(|e| format!("Failed to prepare memory export: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![user_id], |row| {
+            Ok(MemoryRecord {
+                id: row.get(0)?,
+                content: row.get(1)?,
+                created_at: row.get(2)?,
+                updated_at: row.get(3)?,
+                importance: row.get(4)?,
+                access_count: row.get(5)?,
+                last_accessed_at: row.get(6)?,
+                source: row.get(7)?,
+                tags: row.get(8)?,
+                metadata: row.get(9)?,
+            })
+        })
+        .map_err(|e| format!("Failed to export memories: {}", e))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to export memories: {}", e))
+}
+
+fn export_tags(conn: &Connection, user_id: &str) -> Result<Vec<TagRecord>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, tag, color, created_at
+             FROM memory_tags
+             WHERE user_id = ?1",
+        )
+        .map_err(|e| format!("Failed to prepare tag export: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![user_id], |row| {
+            Ok(TagRecord {
+                id: row.get(0)?,
+                tag: row.get(1)?,
+                color: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })
+        .map_err(|e| format!("Failed to export tags: {}", e))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to export tags: {}", e))
+}
+
+fn export_tag_links(conn: &Connection, user_id: &str) -> Result<Vec<TagLinkRecord>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT l.memory_id, l.tag_id, l.created_at
+             FROM memory_tag_links l
+             JOIN memories m ON CAST(m.id AS TEXT) = l.memory_id
+             JOIN memory_tags t ON t.id = l.tag_id
+             WHERE m.user_id = ?1 AND t.user_id = ?1",
+        )
+        .map_err(|e| format!("Failed to prepare tag link export: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![user_id], |row| {
+            Ok(TagLinkRecord {
+                memory_id: row.get(0)?,
+                tag_id: row.get(1)?,
+                created_at: row.get(2)?,
+            })
+        })
+        .map_err(|e| format!("Failed to export tag links: {}", e))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to export tag links: {}", e))
+}
+
+fn export_edges(conn: &Connection, user_id: &str) -> Result<Vec<EdgeRecord>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT source_memory_id, target_memory_id, relationship, weight
+             FROM memory_edges
+             WHERE user_id = ?1",
+        )
+        .map_err(|e| format!("Failed to prepare edge export: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![user_id], |row| {
+            Ok(EdgeRecord {
+                source_memory_id: row.get(0)?,
+                target_memory_id: row.get(1)?,
+                relationship: row.get(2)?,
+                weight: row.get(3)?,
+            })
+        })
+        .map_err(|e| format!("Failed to export edges: {}", e))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to export edges: {}", e))
+}
+
+fn export_reminders(conn: &Connection, user_id: &str) -> Result<Vec<ReminderRecord>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, content, created_at, trigger_at, status, source, memory_id, metadata, recurrence,
+                    fired_at, lateness_seconds
+             FROM reminders
+             WHERE user_id = ?1
+             ORDER BY trigger_at ASC",
+        )
+        .map_err(|e| format!("Failed to prepare reminder export: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![user_id], |row| {
+            Ok(ReminderRecord {
+                id: row.get(0)?,
+                content: row.get(1)?,
+                created_at: row.get(2)?,
+                trigger_at: row.get(3)?,
+                status: row.get(4)?,
+                source: row.get(5)?,
+                memory_id: row.get(6)?,
+                metadata: row.get(7)?,
+                recurrence: reminder_store::recurrence_from_column(row.get(8)?),
+                fired_at: row.get(9)?,
+                lateness_seconds: row.get(10)?,
+            })
+        })
+        .map_err(|e| format!("Failed to export reminders: {}", e))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to export reminders: {}", e))
+}
+
+fn export_chat_messages(conn: &Connection, user_id: &str) -> Result<Vec<ChatMessageRecord>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT role, content, created_at, metadata
+             FROM chat_messages
+             WHERE user_id = ?1
+             ORDER BY created_at ASC",
+        )
+        .map_err(|e| format!("Failed to prepare chat export: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![user_id], |row| {
+            Ok(ChatMessageRecord {
+                role: row.get(0)?,
+                content: row.get(1)?,
+                created_at: row.get(2)?,
+                metadata: row.get(3)?,
+            })
+        })
+        .map_err(|e| format!("Failed to export chat messages: {}", e))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to export chat messages: {}", e))
+}
+
+fn export_commands(conn: &Connection, user_id: &str) -> Result<Vec<CommandRecord>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT command_text, intent_name, duration_ms, success, timestamp, status, error_message, metadata
+             FROM command_history
+             WHERE user_id = ?1
+             ORDER BY timestamp ASC",
+        )
+        .map_err(|e| format!("Failed to prepare command history export: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![user_id], |row| {
+            Ok(CommandRecord {
+                command_text: row.get(0)?,
+                intent_name: row.get(1)?,
+                duration_ms: row.get(2)?,
+                success: row.get(3)?,
+                timestamp: row.get(4)?,
+                status: row.get(5)?,
+                error_message: row.get(6)?,
+                metadata: row.get(7)?,
+            })
+        })
+        .map_err(|e| format!("Failed to export command history: {}", e))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to export command history: {}", e))
+}
+
+fn export_policies(conn: &Connection, user_id: &str) -> Result<Vec<PolicyRecord>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT capability, policy, updated_at
+             FROM permission_policies
+             WHERE user_id = ?1
+             ORDER BY capability ASC",
+        )
+        .map_err(|e| format!("Failed to prepare policy export: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![user_id], |row| {
+            Ok(PolicyRecord {
+                capability: row.get(0)?,
+                policy: row.get(1)?,
+                updated_at: row.get(2)?,
+            })
+        })
+        .map_err(|e| format!("Failed to export policies: {}", e))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to export policies: {}", e))
+}
+
+// ============================================================================
+// IMPORT
+// ============================================================================
+
+/// Load an archive into `user_id`'s account. Every record gets a fresh ID and
+/// references are remapped; exact duplicates of existing rows are skipped so
+/// importing the same archive twice is harmless. Runs in one transaction.
+pub fn import_user_data(conn: &Connection, user_id: &str, archive: &str) -> Result<ImportSummary, String> {
+    let records = parse_archive(archive)?;
+
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Failed to start import: {}", e))?;
+
+    let mut summary = ImportSummary::default();
+    let mut memory_ids: HashMap<String, String> = HashMap::new();
+    let mut tag_ids: HashMap<String, String> = HashMap::new();
+    let mut imported_edges = Vec::new();
+    let uses_integer_id = crate::memory_store::memories_id_is_integer(&tx)?;
+
+    // Parents before children: memories and tags first so links can be remapped.
+    for record in &records {
+        match record {
+            ArchiveRecord::Memory(memory) => {
+                let (new_id, created) = import_memory(&tx, user_id, memory, uses_integer_id)?;
+                memory_ids.insert(memory.id.clone(), new_id);
+                if created {
+                    summary.memories += 1;
+                } else {
+                    summary.skipped += 1;
+                }
+            }
+            ArchiveRecord::Tag(tag) => {
+                let (new_id, created) = import_tag(&tx, user_id, tag)?;
+                tag_ids.insert(tag.id.clone(), new_id);
+                if created {
+                    summary.tags += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for record in records {
+        match record {
+            ArchiveRecord::TagLink(link) => {
+                if let (Some(memory_id), Some(tag_id)) =
+                    (memory_ids.get(&link.memory_id), tag_ids.get(&link.tag_id))
+                {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO memory_tag_links (memory_id, tag_id, created_at)
+                         VALUES (?1, ?2, ?3)",
+                        params![memory_id, tag_id, link.created_at],
+                    )
+                    .map_err(|e| format!("Failed to import tag link: {}", e))?;
+                }
+            }
+            ArchiveRecord::Edge(edge) => imported_edges.push(edge),
+            ArchiveRecord::Reminder(reminder) => {
+                let memory_id = reminder
+                    .memory_id
+                    .as_ref()
+                    .and_then(|id| memory_ids.get(id).cloned());
+                match import_reminder(&tx, user_id, &reminder, memory_id)? {
+                    Some(imported) => {
+                        summary.reminders += 1;
+                        if imported.status == reminder_store::status::PENDING
+                            || imported.status == reminder_store::status::SNOOZED
+                        {
+                            crate::scheduler::schedule_reminder(&tx, &imported)?;
+                            summary.scheduled_reminders += 1;
+                        }
+                    }
+                    None => summary.skipped += 1,
+                }
+            }
+            ArchiveRecord::ChatMessage(message) => {
+                if import_chat_message(&tx, user_id, &message)? {
+                    summary.chat_messages += 1;
+                } else {
+                    summary.skipped += 1;
+                }
+            }
+            ArchiveRecord::Command(command) => {
+                if import_command(&tx, user_id, &command)? {
+                    summary.commands += 1;
+                } else {
+                    summary.skipped += 1;
+                }
+            }
+            ArchiveRecord::Policy(policy) => {
+                if import_policy(&tx, user_id, &policy)? {
+                    summary.policies += 1;
+                } else {
+                    summary.skipped += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Keyword edges are derived data and get recomputed; anything else is restored as-is.
+    for edge in imported_edges {
+        let relationship = edge.relationship.unwrap_or_default();
+        if relationship.is_empty() || relationship == "keyword_similarity" {
+            continue;
+        }
+        if let (Some(source), Some(target)) = (
+            memory_ids.get(&edge.source_memory_id),
+            memory_ids.get(&edge.target_memory_id),
+        ) {
+            if source != target {
+                crate::memory_graph_repository::create_edge(
+                    &tx,
+                    user_id,
+                    source,
+                    target,
+                    &relationship,
+                    edge.weight.unwrap_or(0.5),
+                )?;
+            }
+        }
+    }
+
+    let (_, created_edges) = crate::memory_intelligence_service::rebuild_memory_links(&tx, user_id)?;
+    summary.edges_rebuilt = created_edges;
+
+    tx.commit()
+        .map_err(|e| format!("Failed to commit import: {}", e))?;
+
+    Ok(summary)
+}
+
+fn parse_archive(archive: &str) -> Result<Vec<ArchiveRecord>, String> {
+    let mut lines = archive.lines().filter(|line| !line.trim().is_empty());
+
+    let header_line = lines.next().ok_or("Archive is empty")?;
+    match serde_json::from_str::<ArchiveRecord>(header_line) {
+        Ok(ArchiveRecord::Header(header)) => {
+            if header.format != ARCHIVE_FORMAT {
+                return Err(format!("Unrecognized archive format: {}", header.format));
+            }
+            if header.version > ARCHIVE_VERSION {
+                return Err(format!(
+                    "Archive version {} is newer than this build supports (version {})",
+                    header.version, ARCHIVE_VERSION
+                ));
+            }
+        }
+        _ => return Err("Archive is missing its header line".to_string()),
+    }
+
+    lines
+        .enumerate()
+        .map(|(index, line)| {
+            serde_json::from_str::<ArchiveRecord>(line)
+                .map_err(|e| format!("Invalid archive record on line {}: {}", index + 2, e))
+        })
+        .collect()
+}
+
+fn import_memory(
+    conn: &Connection,
+    user_id: &str,
+    memory: &MemoryRecord,
+    uses_integer_id: bool,
+) -> Result<(String, bool), String> {
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT CAST(id AS TEXT) FROM memories WHERE user_id = ?1 AND content = ?2 AND deleted_at IS NULL LIMIT 1",
+            params![user_id, memory.content],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to check existing memory: {}", e))?;
+
+    if let Some(existing_id) = existing {
+        return Ok((existing_id, false));
+    }
+
+    let created_at = memory.created_at.unwrap_or_else(current_timestamp);
+    let updated_at = memory.updated_at.unwrap_or(created_at);
+    let source = memory.source.clone().unwrap_or_else(|| "import".to_string());
+
+    let new_id = if uses_integer_id {
+        conn.execute(
+            "INSERT INTO memories (user_id, content, created_at, updated_at, importance, access_count, last_accessed_at, source, tags, metadata)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                user_id,
+                memory.content,
+                created_at,
+                updated_at,
+                memory.importance.unwrap_or(0.5),
+                memory.access_count.unwrap_or(0),
+                memory.last_accessed_at,
+                source,
+                memory.tags,
+                memory.metadata
+            ],
+        )
+        .map_err(|e| format!("Failed to import memory: {}", e))?;
+        conn.last_insert_rowid().to_string()
+    } else {
+        let generated_id = Uuid::new_v4().to_string();
+        conn.execute(
+            "INSERT INTO memories (id, user_id, content, created_at, updated_at, importance, access_count, last_accessed_at, source, tags, metadata)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                generated_id,
+                user_id,
+                memory.content,
+                created_at,
+                updated_at,
+                memory.importance.unwrap_or(0.5),
+                memory.access_count.unwrap_or(0),
+                memory.last_accessed_at,
+                source,
+                memory.tags,
+                memory.metadata
+            ],
+        )
+        .map_err(|e| format!("Failed to import memory: {}", e))?;
+        generated_id
+    };
+
+    crate::embeddings::embedding_store::upsert_memory_embedding(conn, &new_id, &memory.content)?;
+    Ok((new_id, true))
+}
+
+/// Tags are matched by label so an import never creates a second "work" tag.
+fn import_tag(conn: &Connection, user_id: &str, tag: &TagRecord) -> Result<(String, bool), String> {
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT id FROM memory_tags WHERE user_id = ?1 AND tag = ?2",
+            params![user_id, tag.tag],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to check existing tag: {}", e))?;
+
+    if let Some(existing_id) = existing {
+        return Ok((existing_id, false));
+    }
+
+    let new_id = Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO memory_tags (id, user_id, tag, color, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![new_id, user_id, tag.tag, tag.color, tag.created_at.unwrap_or_else(current_timestamp)],
+    )
+    .map_err(|e| format!("Failed to import tag: {}", e))?;
+
+    Ok((new_id, true))
+}
+
+fn import_reminder(
+    conn: &Connection,
+    user_id: &str,
+    reminder: &ReminderRecord,
+    memory_id: Option<String>,
+) -> Result<Option<Reminder>, String> {
+    let exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM reminders WHERE user_id = ?1 AND content = ?2 AND trigger_at = ?3",
+            params![user_id, reminder.content, reminder.trigger_at],
+            |row| row.get::<_, i64>(0),
+        )
+        .map(|count| count > 0)
+        .map_err(|e| format!("Failed to check existing reminder: {}", e))?;
+
+    if exists {
+        return Ok(None);
+    }
+
+    let imported = Reminder {
+        id: Uuid::new_v4().to_string(),
+        user_id: user_id.to_string(),
+        content: reminder.content.clone(),
+        trigger_at: reminder.trigger_at,
+        status: reminder
+            .status
+            .clone()
+            .unwrap_or_else(|| reminder_store::status::PENDING.to_string()),
+        source: reminder.source.clone().unwrap_or_else(|| "import".to_string()),
+        memory_id,
+        recurrence: reminder.recurrence.clone(),
+    };
+    let recurrence_json = imported
+        .recurrence
+        .as_ref()
+        .map(reminder_store::recurrence_to_column)
+        .transpose()?;
+
+    conn.execute(
+        "INSERT INTO reminders (id, user_id, content, created_at, trigger_at, status, source, memory_id, metadata, recurrence,
+                                fired_at, lateness_seconds)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            imported.id,
+            imported.user_id,
+            imported.content,
+            reminder.created_at.unwrap_or_else(current_timestamp),
+            imported.trigger_at,
+            imported.status,
+            imported.source,
+            imported.memory_id,
+            reminder.metadata,
+            recurrence_json,
+            reminder.fired_at,
+            reminder.lateness_seconds
+        ],
+    )
+    .map_err(|e| format!("Failed to import reminder: {}", e))?;
+
+    Ok(Some(imported))
+}
+
+fn import_chat_message(conn: &Connection, user_id: &str, message: &ChatMessageRecord) -> Result<bool, String> {
+    if message.role != "user" && message.role != "assistant" {
+        return Ok(false);
+    }
+
+    let inserted = conn
+        .execute(
+            "INSERT INTO chat_messages (id, user_id, role, content, created_at, metadata)
+             SELECT ?1, ?2, ?3, ?4, ?5, ?6
+             WHERE NOT EXISTS (
+                SELECT 1 FROM chat_messages
+                WHERE user_id = ?2 AND role = ?3 AND content = ?4 AND created_at = ?5
+             )",
+            params![
+                Uuid::new_v4().to_string(),
+                user_id,
+                message.role,
+                message.content,
+                message.created_at,
+                message.metadata
+            ],
+        )
+        .map_err(|e| format!("Failed to import chat message: {}", e))?;
+
+    Ok(inserted > 0)
+}
+
+fn import_command(conn: &Connection, user_id: &str, command: &CommandRecord) -> Result<bool, String> {
+    let inserted = conn
+        .execute(
+            "INSERT INTO command_history
+             (id, user_id, command_text, intent_name, duration_ms, success, timestamp, status, error_message, metadata)
+             SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10
+             WHERE NOT EXISTS (
+                SELECT 1 FROM command_history
+                WHERE user_id = ?2 AND command_text = ?3 AND timestamp = ?7
+             )",
+            params![
+                Uuid::new_v4().to_string(),
+                user_id,
+                command.command_text,
+                command.intent_name,
+                command.duration_ms,
+                command.success.unwrap_or(1),
+                command.timestamp,
+                command.status.clone().unwrap_or_else(|| "completed".to_string()),
+                command.error_message,
+                command.metadata
+            ],
+        )
+        .map_err(|e| format!("Failed to import command history: {}", e))?;
+
+    Ok(inserted > 0)
+}
+
+/// Keeps a policy the importing user already chose; unknown capabilities are skipped.
+fn import_policy(conn: &Connection, user_id: &str, policy: &PolicyRecord) -> Result<bool, String> {
+    let Some(capability) = crate::Capability::parse(&policy.capability) else {
+        return Ok(false);
+    };
+    let exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM permission_policies WHERE user_id = ?1 AND capability = ?2",
+            params![user_id, capability.as_str()],
+            |row| row.get::<_, i64>(0),
+        )
+        .map(|count| count > 0)
+        .map_err(|e| format!("Failed to check existing policy: {}", e))?;
+
+    if exists {
+        return Ok(false);
+    }
+
+    crate::permission_policy_store::set_policy(conn, user_id, capability, &policy.policy)?;
+    Ok(true)
+}
+
+fn current_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory_tag_service;
+    use crate::permission_policy_store;
+    use crate::reminder_recurrence::Frequency;
+    use crate::Capability;
+
+    fn database_with_user(user_id: &str) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::database::initialize_database(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO users (id, email, password_hash, created_at) VALUES (?1, ?2, 'hash', 0)",
+            params![user_id, format!("{}@example.com", user_id)],
+        )
+        .unwrap();
+        conn
+    }
+
+    fn memory_contents(conn: &Connection, user_id: &str) -> Vec<(String, String)> {
+        let mut memories = crate::memory_store::get_memories(conn, user_id, 100, 0)
+            .unwrap()
+            .into_iter()
+            .map(|memory| (memory.content, memory.id))
+            .collect::<Vec<_>>();
+        memories.sort();
+        memories
+    }
+
+    #[test]
+    fn export_paths_stay_inside_the_exports_folder() {
+        let exports_dir = std::env::temp_dir().join(format!("noddy-exports-{}", Uuid::new_v4()));
+
+        assert_eq!(
+            export_path(&exports_dir, Some("backup.jsonl")).unwrap(),
+            exports_dir.join("backup.jsonl")
+        );
+        assert!(export_path(&exports_dir, None).unwrap().starts_with(&exports_dir));
+        for name in ["../backup.jsonl", "/etc/passwd", "sub/backup.jsonl", "..\\backup.jsonl", ".bashrc"] {
+            assert!(export_path(&exports_dir, Some(name)).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn exported_archive_round_trips_into_another_account() {
+        let source = database_with_user("alice");
+        let dentist = crate::memory_store::create_memory(
+            &source,
+            "alice",
+            "Dentist is Dr. Lee".to_string(),
+            Some(vec!["health".to_string()]),
+        )
+        .unwrap()
+        .id;
+        crate::memory_store::create_memory(&source, "alice", "Gym at 7 on weekdays".to_string(), None).unwrap();
+        let health = memory_tag_service::get_all_tags(&source, "alice").unwrap().remove(0);
+        memory_tag_service::update_tag_color(&source, "alice", &health.id, Some("#22aa55".to_string())).unwrap();
+        let weekly = Recurrence::new(Frequency::Weekly);
+        reminder_store::create_reminder(
+            &source,
+            "alice",
+            "Book the dentist".to_string(),
+            4_102_444_800,
+            Some(dentist.clone()),
+            Some(&weekly),
+        )
+        .unwrap();
+        permission_policy_store::set_policy(&source, "alice", Capability::WebSearch, "deny").unwrap();
+
+        let archive = export_user_data(&source, "alice").unwrap();
+        let target = database_with_user("bob");
+        let summary = import_user_data(&target, "bob", &archive).unwrap();
+
+        assert_eq!((summary.memories, summary.reminders, summary.policies), (2, 1, 1));
+        let exported = memory_contents(&source, "alice");
+        let imported = memory_contents(&target, "bob");
+        assert_eq!(
+            imported.iter().map(|(content, _)| content).collect::<Vec<_>>(),
+            exported.iter().map(|(content, _)| content).collect::<Vec<_>>()
+        );
+        assert!(imported.iter().all(|(_, id)| exported.iter().all(|(_, old)| old != id)));
+        let orphans: i64 = target
+            .query_row("SELECT COUNT(*) FROM memories WHERE user_id IS NOT 'bob'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(orphans, 0);
+
+        let imported_dentist = &imported
+            .iter()
+            .find(|(content, _)| content == "Dentist is Dr. Lee")
+            .unwrap()
+            .1;
+        let tags = memory_tag_service::get_memory_tags(&target, "bob", imported_dentist).unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!((tags[0].tag.as_str(), tags[0].color.as_deref()), ("health", Some("#22aa55")));
+        assert_ne!(tags[0].id, health.id);
+
+        let reminders = reminder_store::get_pending_reminders(&target, "bob").unwrap();
+        assert_eq!(reminders.len(), 1);
+        assert_eq!(reminders[0].content, "Book the dentist");
+        assert_eq!(reminders[0].trigger_at, 4_102_444_800);
+        assert_eq!(reminders[0].memory_id.as_ref(), Some(imported_dentist));
+        assert_eq!(reminders[0].recurrence.as_ref(), Some(&weekly));
+
+        assert_eq!(
+            permission_policy_store::get_policy(&target, "bob", Capability::WebSearch).unwrap(),
+            "deny"
+        );
+    }
+}
@@ -1,14 +1,26 @@
+use crate::reminder_recurrence;
 use crate::reminder_store;
-use crate::scheduler::{self, ReminderTaskPayload, ScheduledTask};
+use crate::scheduler::{self, FailureDisposition, ReminderTaskPayload, ScheduledTask};
+use crate::settings_service::{self, MissedReminderPolicy, SettingsState};
+use crate::task_registry::{self, FieldKind, PayloadField, TaskContext, TaskHandler, TaskOutcome};
 use crate::{Event, EventBus};
 use rusqlite::Connection;
 use std::path::PathBuf;
-use std::time::Duration;
-use tauri::{AppHandle, Emitter};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
 
 const WORKER_LOOP_INTERVAL: Duration = Duration::from_secs(10);
 const PENDING_TASK_BATCH_SIZE: i32 = 50;
 
+const REMINDER_PAYLOAD_SCHEMA: &[PayloadField] = &[
+    PayloadField::required("reminder_id", FieldKind::String),
+    PayloadField::required("user_id", FieldKind::String),
+    PayloadField::required("content", FieldKind::String),
+    PayloadField::required("trigger_at", FieldKind::Integer),
+    PayloadField::optional("memory_id", FieldKind::String),
+    PayloadField::optional("recurrence", FieldKind::Object),
+];
+
 pub fn start_worker_loop(db_path: PathBuf, event_bus: EventBus, app_handle: AppHandle) {
     std::thread::spawn(move || {
         let conn = match Connection::open(&db_path) {
@@ -43,11 +55,17 @@ pub fn run_pending_tasks_once(
     app_handle: Option<&AppHandle>,
 ) -> Result<usize, String> {
     let tasks = scheduler::get_pending_tasks(conn, PENDING_TASK_BATCH_SIZE)?;
-    let mut executed = 0_usize;
+    let (policy, grace_minutes) = configured_catch_up(app_handle);
+    let (tasks, overdue) = split_overdue_reminders(tasks, policy, grace_minutes, current_timestamp());
+    let mut executed = catch_up_overdue_reminders(conn, overdue, policy, event_bus, app_handle);
 
+    // One failing task must not hold back the rest of the batch
     for task in tasks {
-        execute_task(conn, task, event_bus, app_handle)?;
-        executed += 1;
+        let task_id = task.task_id.clone();
+        match execute_task(conn, task, event_bus, app_handle) {
+            Ok(()) => executed += 1,
+            Err(error) => eprintln!("⚠️  Task {} failed: {}", task_id, error),
+        }
     }
 
     Ok(executed)
@@ -59,24 +77,210 @@ pub fn execute_task(
     event_bus: &EventBus,
     app_handle: Option<&AppHandle>,
 ) -> Result<(), String> {
-    let execution = match task.task_type.as_str() {
-        scheduler::task_type::REMINDER => execute_reminder_task(conn, &task, event_bus, app_handle),
-        _ => Err(format!("Unsupported task type: {}", task.task_type)),
+    let execution = match task_registry::handler_for(&task.task_type) {
+        Some(handler) => handler.execute(
+            &TaskContext {
+                conn,
+                event_bus,
+                app_handle,
+            },
+            &task,
+        ),
+        None => Err(format!("Unsupported task type: {}", task.task_type)),
     };
 
     match execution {
-        Ok(()) => {
-            scheduler::mark_task_completed(conn, &task.task_id)?;
+        Ok(TaskOutcome::Refused(reason)) => {
+            scheduler::record_task_failure(conn, &task.task_id, &reason, 1)?;
+            emit_dead_letter_event(app_handle, &task, &reason);
+            Err(reason)
+        }
+        Ok(outcome) => {
+            if let TaskOutcome::Completed = outcome {
+                scheduler::mark_task_completed(conn, &task.task_id)?;
+            }
             emit_events_for_completed_tasks(event_bus, app_handle, &task)?;
             Ok(())
         }
         Err(error) => {
-            scheduler::mark_task_failed(conn, &task.task_id)?;
+            let max_attempts = configured_max_attempts(app_handle);
+            let disposition = scheduler::record_task_failure(conn, &task.task_id, &error, max_attempts)?;
+            if disposition == FailureDisposition::DeadLettered {
+                emit_dead_letter_event(app_handle, &task, &error);
+            }
             Err(error)
         }
     }
 }
 
+fn configured_catch_up(app_handle: Option<&AppHandle>) -> (MissedReminderPolicy, u32) {
+    let configured = app_handle
+        .and_then(|app| app.try_state::<SettingsState>())
+        .and_then(|state| {
+            let settings = state.settings.lock().ok()?;
+            Some((settings.missed_reminder_policy, settings.missed_reminder_grace_minutes))
+        });
+
+    configured.unwrap_or((
+        MissedReminderPolicy::default(),
+        settings_service::DEFAULT_MISSED_REMINDER_GRACE_MINUTES,
+    ))
+}
+
+/// A reminder task that is further past due than the grace window allows.
+struct OverdueReminder {
+    task: ScheduledTask,
+    payload: ReminderTaskPayload,
+    lateness_seconds: i64,
+}
+
+/// Pulls reminders that are later than the grace window out of the batch.
+/// Under `FireAll` (or with an unreadable payload) they stay in the normal path,
+/// and so do retries, which are late only because an earlier run failed.
+fn split_overdue_reminders(
+    tasks: Vec<ScheduledTask>,
+    policy: MissedReminderPolicy,
+    grace_minutes: u32,
+    now: i64,
+) -> (Vec<ScheduledTask>, Vec<OverdueReminder>) {
+    if policy == MissedReminderPolicy::FireAll {
+        return (tasks, Vec::new());
+    }
+
+    let grace_seconds = grace_minutes as i64 * 60;
+    let mut due = Vec::new();
+    let mut overdue = Vec::new();
+
+    for task in tasks {
+        if task.task_type == scheduler::task_type::REMINDER && task.attempts == 0 {
+            if let Ok(payload) = serde_json::from_str::<ReminderTaskPayload>(&task.payload) {
+                let lateness_seconds = now - payload.trigger_at;
+                if lateness_seconds > grace_seconds {
+                    overdue.push(OverdueReminder {
+                        task,
+                        payload,
+                        lateness_seconds,
+                    });
+                    continue;
+                }
+            }
+        }
+        due.push(task);
+    }
+
+    (due, overdue)
+}
+
+/// Applies the digest or grace-window policy to overdue reminders.
+/// Returns how many were handled; failures go through the normal retry path.
+fn catch_up_overdue_reminders(
+    conn: &Connection,
+    overdue: Vec<OverdueReminder>,
+    policy: MissedReminderPolicy,
+    event_bus: &EventBus,
+    app_handle: Option<&AppHandle>,
+) -> usize {
+    let now = current_timestamp();
+    let deliver = policy == MissedReminderPolicy::Digest;
+    let mut handled = Vec::new();
+
+    for reminder in overdue {
+        let (fired_at, final_status) = if deliver {
+            (Some(now), reminder_store::status::TRIGGERED)
+        } else {
+            (None, reminder_store::status::MISSED)
+        };
+
+        let result = finish_reminder_occurrence(
+            conn,
+            &reminder.payload,
+            fired_at,
+            reminder.lateness_seconds,
+            final_status,
+        )
+        .and_then(|next_trigger_at| match next_trigger_at {
+            Some(_) => Ok(next_trigger_at),
+            None if deliver => scheduler::mark_task_completed(conn, &reminder.task.task_id).map(|_| None),
+            None => scheduler::mark_task_skipped(conn, &reminder.task.task_id).map(|_| None),
+        });
+
+        match result {
+            Ok(next_trigger_at) => handled.push((reminder, next_trigger_at)),
+            Err(error) => {
+                eprintln!("⚠️  Task {} failed: {}", reminder.task.task_id, error);
+                let max_attempts = configured_max_attempts(app_handle);
+                if let Ok(FailureDisposition::DeadLettered) =
+                    scheduler::record_task_failure(conn, &reminder.task.task_id, &error, max_attempts)
+                {
+                    emit_dead_letter_event(app_handle, &reminder.task, &error);
+                }
+            }
+        }
+    }
+
+    if handled.is_empty() {
+        return 0;
+    }
+
+    let entries = handled
+        .iter()
+        .map(|(reminder, next_trigger_at)| {
+            serde_json::json!({
+                "id": reminder.payload.reminder_id,
+                "content": reminder.payload.content,
+                "user_id": reminder.payload.user_id,
+                "trigger_at": reminder.payload.trigger_at,
+                "lateness_seconds": reminder.lateness_seconds,
+                "next_trigger_at": next_trigger_at,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if deliver {
+        if let Some(app) = app_handle {
+            let _ = app.emit("reminder_digest", serde_json::json!({ "reminders": entries }));
+        }
+        let summary = handled
+            .iter()
+            .map(|(reminder, _)| reminder.payload.content.as_str())
+            .collect::<Vec<_>>()
+            .join("; ");
+        event_bus.emit(&Event::ReminderTriggered(format!(
+            "{} missed reminder(s): {}",
+            handled.len(),
+            summary
+        )));
+    } else if let Some(app) = app_handle {
+        let _ = app.emit("reminders_missed", serde_json::json!({ "reminders": entries }));
+    }
+
+    handled.len()
+}
+
+fn configured_max_attempts(app_handle: Option<&AppHandle>) -> u32 {
+    let configured = app_handle
+        .and_then(|app| app.try_state::<SettingsState>())
+        .and_then(|state| {
+            let settings = state.settings.lock().ok()?;
+            Some(settings.task_max_attempts)
+        });
+
+    configured.unwrap_or(settings_service::DEFAULT_TASK_MAX_ATTEMPTS).max(1)
+}
+
+fn emit_dead_letter_event(app_handle: Option<&AppHandle>, task: &ScheduledTask, error: &str) {
+    if let Some(app) = app_handle {
+        let _ = app.emit(
+            "background_task_dead_lettered",
+            serde_json::json!({
+                "task_id": task.task_id,
+                "task_type": task.task_type,
+                "error": error,
+            }),
+        );
+    }
+}
+
 pub fn emit_events_for_completed_tasks(
     event_bus: &EventBus,
     app_handle: Option<&AppHandle>,
@@ -101,19 +305,37 @@ pub fn emit_events_for_completed_tasks(
     Ok(())
 }
 
+pub struct ReminderTaskHandler;
+
+impl TaskHandler for ReminderTaskHandler {
+    fn task_type(&self) -> &'static str {
+        scheduler::task_type::REMINDER
+    }
+
+    fn payload_schema(&self) -> &'static [PayloadField] {
+        REMINDER_PAYLOAD_SCHEMA
+    }
+
+    fn execute(&self, ctx: &TaskContext<'_>, task: &ScheduledTask) -> Result<TaskOutcome, String> {
+        execute_reminder_task(ctx.conn, task, ctx.event_bus, ctx.app_handle)
+    }
+}
+
 fn execute_reminder_task(
     conn: &Connection,
     task: &ScheduledTask,
     event_bus: &EventBus,
     app_handle: Option<&AppHandle>,
-) -> Result<(), String> {
+) -> Result<TaskOutcome, String> {
     let payload: ReminderTaskPayload = serde_json::from_str(&task.payload)
         .map_err(|e| format!("Failed to deserialize reminder task payload: {}", e))?;
 
-    reminder_store::update_reminder_status(
+    let fired_at = current_timestamp();
+    let next_trigger_at = finish_reminder_occurrence(
         conn,
-        &payload.user_id,
-        &payload.reminder_id,
+        &payload,
+        Some(fired_at),
+        (fired_at - payload.trigger_at).max(0),
         reminder_store::status::TRIGGERED,
     )?;
 
@@ -124,10 +346,124 @@ fn execute_reminder_task(
                 "id": payload.reminder_id,
                 "content": payload.content,
                 "user_id": payload.user_id,
+                "next_trigger_at": next_trigger_at,
             }),
         );
     }
 
     event_bus.emit(&Event::ReminderTriggered(payload.content));
-    Ok(())
-}
\ No newline at end of file
+
+    Ok(match next_trigger_at {
+        Some(_) => TaskOutcome::Rescheduled,
+        None => TaskOutcome::Completed,
+    })
+}
+
+/// Records the fire time of the current occurrence, then either schedules the
+/// next one (recurring reminders) or moves the reminder to `final_status`.
+fn finish_reminder_occurrence(
+    conn: &Connection,
+    payload: &ReminderTaskPayload,
+    fired_at: Option<i64>,
+    lateness_seconds: i64,
+    final_status: &str,
+) -> Result<Option<i64>, String> {
+    reminder_store::record_reminder_fire(
+        conn,
+        &payload.user_id,
+        &payload.reminder_id,
+        fired_at,
+        lateness_seconds,
+    )?;
+
+    let next_trigger_at = match &payload.recurrence {
+        Some(rule) => schedule_next_occurrence(conn, payload, rule)?,
+        None => None,
+    };
+
+    if next_trigger_at.is_none() {
+        reminder_store::update_reminder_status(conn, &payload.user_id, &payload.reminder_id, final_status)?;
+    }
+
+    Ok(next_trigger_at)
+}
+
+/// Registers the following occurrence of a recurring reminder.
+/// Returns None once the series has run out (count or until reached).
+fn schedule_next_occurrence(
+    conn: &Connection,
+    payload: &ReminderTaskPayload,
+    rule: &reminder_recurrence::Recurrence,
+) -> Result<Option<i64>, String> {
+    let previous = rule.scheduled_at.unwrap_or(payload.trigger_at);
+    let now = current_timestamp();
+
+    let Some((next_trigger_at, next_rule)) = reminder_recurrence::advance(rule, previous, now) else {
+        return Ok(None);
+    };
+
+    reminder_store::advance_recurring_reminder(
+        conn,
+        &payload.user_id,
+        &payload.reminder_id,
+        next_trigger_at,
+        &next_rule,
+    )?;
+
+    let reminder = reminder_store::get_reminder(conn, &payload.user_id, &payload.reminder_id)?;
+    scheduler::schedule_reminder(conn, &reminder)?;
+
+    Ok(Some(next_trigger_at))
+}
+
+fn current_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reminder_task(id: &str, trigger_at: i64, attempts: i64) -> ScheduledTask {
+        let payload = ReminderTaskPayload {
+            reminder_id: id.to_string(),
+            user_id: "u1".to_string(),
+            content: "Water plants".to_string(),
+            trigger_at,
+            memory_id: None,
+            recurrence: None,
+        };
+        ScheduledTask {
+            task_id: scheduler::reminder_task_id(id),
+            task_type: scheduler::task_type::REMINDER.to_string(),
+            payload: serde_json::to_string(&payload).unwrap(),
+            execute_at: trigger_at,
+            status: scheduler::status::PENDING.to_string(),
+            attempts,
+        }
+    }
+
+    #[test]
+    fn only_first_runs_past_the_grace_window_count_as_missed() {
+        let now = 100_000;
+        let tasks = vec![
+            reminder_task("on-time", now - 60, 0),
+            reminder_task("missed", now - 3600, 0),
+            reminder_task("retrying", now - 3600, 2),
+        ];
+
+        let (due, overdue) = split_overdue_reminders(tasks, MissedReminderPolicy::Digest, 15, now);
+
+        let due_ids = due.iter().map(|task| task.task_id.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            due_ids,
+            vec![scheduler::reminder_task_id("on-time"), scheduler::reminder_task_id("retrying")]
+        );
+        assert_eq!(overdue.len(), 1);
+        assert_eq!(overdue[0].payload.reminder_id, "missed");
+        assert_eq!(overdue[0].lateness_seconds, 3600);
+    }
+}
@@ -81,6 +81,7 @@ interface Integration {
 interface TestCommandResponse {
   success: boolean;
   message: string;
+  requires_confirmation?: boolean;
   data?: any;
   duration?: number;
   timestamp: string;
@@ -284,6 +285,7 @@ function App() {
 
     let unlistenReminderFired: (() => void) | undefined;
     let unlistenReminderScheduled: (() => void) | undefined;
+    let unlistenReminderDigest: (() => void) | undefined;
 
     const setup = async () => {
       unlistenReminderFired = await listen("reminder_fired", async (event: any) => {
@@ -314,6 +316,30 @@ function App() {
         }
       });
 
+      // Reminders that came due while the app was closed arrive as one digest.
+      unlistenReminderDigest = await listen("reminder_digest", async (event: any) => {
+        const missed = (event?.payload?.reminders || []).filter(
+          (reminder: any) => !reminder?.user_id || reminder.user_id === user.id
+        );
+        if (missed.length === 0) {
+          return;
+        }
+
+        const accessToken = await getAccessToken();
+        const remindersData = await fetchReminders(accessToken);
+        setReminders(remindersData);
+        setPendingReminderNavigation(true);
+
+        try {
+          await sendNotification({
+            title: missed.length === 1 ? "Missed reminder" : `${missed.length} missed reminders`,
+            body: missed.map((reminder: any) => reminder.content).join("\n"),
+          });
+        } catch (notifError) {
+          console.warn("Failed to send notification:", notifError);
+        }
+      });
+
       unlistenReminderScheduled = await listen("reminder_scheduled", async (event: any) => {
         const accessToken = await getAccessToken();
         if (event?.payload?.user_id && event.payload.user_id !== user.id) {
@@ -329,6 +355,7 @@ function App() {
     return () => {
       if (unlistenReminderFired) unlistenReminderFired();
       if (unlistenReminderScheduled) unlistenReminderScheduled();
+      if (unlistenReminderDigest) unlistenReminderDigest();
     };
   }, [getAccessToken, user?.id]);
 
@@ -1496,10 +1523,25 @@ function TestCommandsView({
 
     try {
       const intentJson = parseTestCommand(commandInput);
-      const result = await invokeAuthed<TestCommandResponse>("execute_action", {
+      let result = await invokeAuthed<TestCommandResponse>("execute_action", {
         intentJson,
       });
 
+      // Destructive actions come back parked; data[0] is the pending plan id
+      if (result.requires_confirmation && Array.isArray(result.data) && result.data[0]) {
+        const approve = window.confirm(result.message);
+        const progress = await invokeAuthed<{ status: string; message: string }>("confirm_plan_step", {
+          planId: result.data[0],
+          approve,
+        });
+        result = {
+          ...result,
+          success: progress.status === "completed",
+          message: progress.message,
+          requires_confirmation: false,
+        };
+      }
+
       const duration = Math.round(performance.now() - startTime);
       const newResult: TestCommandResult = {
         id: Date.now().toString(),
@@ -5,6 +5,7 @@ interface AuthUser {
   id: string;
   email: string;
   created_at: number;
+  is_admin: boolean;
 }
 
 interface TokenBundle {
@@ -27,6 +28,7 @@ interface AuthContextValue {
   login: (email: string, password: string) => Promise<void>;
   signup: (email: string, password: string) => Promise<void>;
   logout: () => Promise<void>;
+  changePassword: (currentPassword: string, newPassword: string) => Promise<void>;
   getAccessToken: () => Promise<string>;
 }
 
@@ -146,6 +148,20 @@ export function AuthProvider({ children }: { children: ReactNode }) {
     }
   };
 
+  // Every session is revoked by the backend; keep this device signed in with the new one.
+  const changePassword = async (currentPassword: string, newPassword: string) => {
+    if (!session) {
+      throw new Error("Not authenticated");
+    }
+    const active = await refreshIfNeeded(session);
+    const changed = await invoke<{ tokens: TokenBundle }>("change_password", {
+      accessToken: active.tokens.access_token,
+      currentPassword,
+      newPassword,
+    });
+    applyAuthPayload({ user: active.user, tokens: changed.tokens });
+  };
+
   const getAccessToken = async (): Promise<string> => {
     if (!session) {
       throw new Error("Not authenticated");
@@ -161,6 +177,7 @@ export function AuthProvider({ children }: { children: ReactNode }) {
       login,
       signup,
       logout,
+      changePassword,
       getAccessToken,
     }),
     [session, loading],
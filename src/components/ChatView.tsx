@@ -11,6 +11,9 @@ interface Message {
   content: string;
   timestamp: Date;
   reminderId?: string;
+  pendingPlanId?: string;
+  choices?: PlanChoice[];
+  permission?: string | null;
 }
 
 interface PersistedMessage {
@@ -33,6 +36,43 @@ interface Suggestion {
 interface ActionResponse {
   success: boolean;
   message: string;
+  requires_confirmation: boolean;
+  data?: string[] | null;
+}
+
+interface PlanChoice {
+  label: string;
+  intent: string;
+}
+
+interface ConfirmationRequest {
+  plan_id: string;
+  step_index: number;
+  total_steps: number;
+  intent: string;
+  summary: string;
+  choices: PlanChoice[];
+  permission?: string | null;
+}
+
+type GrantScope = "once" | "session" | "always";
+
+const GRANT_OPTIONS: { scope: GrantScope; label: string }[] = [
+  { scope: "once", label: "Allow once" },
+  { scope: "session", label: "Allow this session" },
+  { scope: "always", label: "Always allow" },
+];
+
+interface PlanProgress {
+  status:
+    | "completed"
+    | "failed"
+    | "cancelled"
+    | "confirmation_required"
+    | "choice_required"
+    | "permission_required";
+  message: string;
+  confirmation?: ConfirmationRequest | null;
 }
 
 export function ChatView() {
@@ -142,38 +182,88 @@ export function ChatView() {
     setInputMessage("");
     setIsLoading(true);
 
+    // Streamed tokens for this reply are tagged with its id
+    const messageId = crypto.randomUUID();
+    const unlistenTokens = await listen<{ message_id: string; token: string }>(
+      "chat_token",
+      (event) => {
+        if (event.payload.message_id !== messageId) return;
+        setMessages((prev) => {
+          if (prev.some((message) => message.id === messageId)) {
+            return prev.map((message) =>
+              message.id === messageId
+                ? { ...message, content: message.content + event.payload.token }
+                : message
+            );
+          }
+          return [
+            ...prev,
+            {
+              id: messageId,
+              role: "assistant",
+              content: event.payload.token,
+              timestamp: new Date(),
+            },
+          ];
+        });
+      }
+    );
+
+    // A plan step waiting for approval shows Confirm/Cancel (or the memories
+    // to pick from) on this reply
+    let pendingPlanId: string | undefined;
+    let choices: PlanChoice[] | undefined;
+    let permission: string | null | undefined;
+    const unlistenConfirmation = await listen<ConfirmationRequest>(
+      "plan_confirmation_required",
+      (event) => {
+        pendingPlanId = event.payload.plan_id;
+        choices = event.payload.choices;
+        permission = event.payload.permission;
+      }
+    );
+
+    const upsertAssistantMessage = (content: string) => {
+      setMessages((prev) => {
+        if (prev.some((message) => message.id === messageId)) {
+          return prev.map((message) =>
+            message.id === messageId
+              ? { ...message, content, pendingPlanId, choices, permission }
+              : message
+          );
+        }
+        return [
+          ...prev,
+          {
+            id: messageId,
+            role: "assistant",
+            content,
+            timestamp: new Date(),
+            pendingPlanId,
+            choices,
+            permission,
+          },
+        ];
+      });
+    };
+
     try {
       const accessToken = await getAccessToken();
 
-      // Call Tauri backend
+      // Call Tauri backend; the final text replaces whatever was streamed
       const response = await invoke<string>("chat_with_ai", {
         message: trimmedMessage,
+        messageId,
         accessToken,
       });
 
-      // Create assistant message
-      const assistantMessage: Message = {
-        id: `assistant-${Date.now()}`,
-        role: "assistant",
-        content: response,
-        timestamp: new Date(),
-      };
-
-      // Add assistant message to chat
-      setMessages((prev) => [...prev, assistantMessage]);
+      upsertAssistantMessage(response);
     } catch (error) {
       console.error("Error sending message:", error);
-      
-      // Create error message
-      const errorMessage: Message = {
-        id: `error-${Date.now()}`,
-        role: "assistant",
-        content: `Error: ${error}`,
-        timestamp: new Date(),
-      };
-
-      setMessages((prev) => [...prev, errorMessage]);
+      upsertAssistantMessage(`Error: ${error}`);
     } finally {
+      unlistenTokens();
+      unlistenConfirmation();
       setIsLoading(false);
       inputRef.current?.focus();
     }
@@ -211,6 +301,72 @@ export function ChatView() {
     }
   };
 
+  const handlePlanConfirmation = async (
+    planId: string,
+    approve: boolean,
+    choice?: number,
+    grant?: GrantScope
+  ) => {
+    setMessages((prev) =>
+      prev.map((message) =>
+        message.pendingPlanId === planId
+          ? { ...message, pendingPlanId: undefined, choices: undefined, permission: undefined }
+          : message
+      )
+    );
+
+    try {
+      const accessToken = await getAccessToken();
+      const progress = await invoke<PlanProgress>("confirm_plan_step", {
+        accessToken,
+        planId,
+        approve,
+        choice,
+        grant,
+      });
+      setMessages((prev) => [
+        ...prev,
+        {
+          id: `plan-${planId}-${Date.now()}`,
+          role: "assistant",
+          content: progress.message,
+          timestamp: new Date(),
+          pendingPlanId: progress.confirmation?.plan_id,
+          choices: progress.confirmation?.choices,
+          permission: progress.confirmation?.permission,
+        },
+      ]);
+    } catch (error) {
+      console.error("Failed to confirm plan step:", error);
+      setMessages((prev) => [
+        ...prev,
+        {
+          id: `plan-${planId}-${Date.now()}`,
+          role: "assistant",
+          content: `Error: ${error}`,
+          timestamp: new Date(),
+        },
+      ]);
+    }
+  };
+
+  const handleUndo = async () => {
+    try {
+      const accessToken = await getAccessToken();
+      const summary = await invoke<string>("undo_last_action", { accessToken });
+      setMessages((prev) => [
+        ...prev,
+        { id: `undo-${Date.now()}`, role: "assistant", content: summary, timestamp: new Date() },
+      ]);
+    } catch (error) {
+      console.error("Failed to undo last action:", error);
+      setMessages((prev) => [
+        ...prev,
+        { id: `undo-${Date.now()}`, role: "assistant", content: `Error: ${error}`, timestamp: new Date() },
+      ]);
+    }
+  };
+
   const handleExecuteSuggestion = async (suggestion: Suggestion) => {
     if (!suggestion.action_intent) {
       return;
@@ -233,6 +389,7 @@ export function ChatView() {
         role: "assistant",
         content: result.message,
         timestamp: new Date(),
+        pendingPlanId: result.requires_confirmation ? result.data?.[0] : undefined,
       };
       setMessages((prev) => [...prev, assistantMessage]);
       setSuggestions((prev) => prev.filter((s) => s.id !== suggestion.id));
@@ -423,6 +580,79 @@ export function ChatView() {
                     </button>
                   </div>
                 )}
+                {message.pendingPlanId && (
+                  <div style={{ display: "flex", flexWrap: "wrap", gap: "8px", marginTop: "10px" }}>
+                    {message.permission ? (
+                      GRANT_OPTIONS.map((option) => (
+                        <button
+                          key={option.scope}
+                          onClick={() =>
+                            handlePlanConfirmation(message.pendingPlanId!, true, undefined, option.scope)
+                          }
+                          className="btn btn-primary"
+                          style={{ padding: "4px 10px", fontSize: "12px" }}
+                        >
+                          {option.label}
+                        </button>
+                      ))
+                    ) : message.choices && message.choices.length > 0 ? (
+                      message.choices.map((choice, choiceIndex) => (
+                        <button
+                          key={choiceIndex}
+                          onClick={() => handlePlanConfirmation(message.pendingPlanId!, true, choiceIndex)}
+                          className="btn btn-primary"
+                          style={{ padding: "4px 10px", fontSize: "12px" }}
+                        >
+                          {choiceIndex + 1}. {choice.label}
+                        </button>
+                      ))
+                    ) : (
+                      <button
+                        onClick={() => handlePlanConfirmation(message.pendingPlanId!, true)}
+                        className="btn btn-primary"
+                        style={{ padding: "4px 10px", fontSize: "12px" }}
+                      >
+                        Confirm
+                      </button>
+                    )}
+                    <button
+                      onClick={() => handlePlanConfirmation(message.pendingPlanId!, false)}
+                      style={{
+                        border: "1px solid var(--border-medium)",
+                        borderRadius: "8px",
+                        padding: "4px 10px",
+                        fontSize: "12px",
+                        background: "transparent",
+                        color: "var(--text-primary)",
+                        cursor: "pointer",
+                      }}
+                    >
+                      {message.permission ? "Deny" : "Cancel"}
+                    </button>
+                  </div>
+                )}
+                {message.role === "assistant" &&
+                  index === messages.length - 1 &&
+                  index > 0 &&
+                  !message.reminderId &&
+                  !message.pendingPlanId &&
+                  !isLoading && (
+                    <div style={{ marginTop: "8px" }}>
+                      <button
+                        onClick={handleUndo}
+                        style={{
+                          border: "none",
+                          padding: 0,
+                          fontSize: "12px",
+                          background: "transparent",
+                          color: "var(--text-secondary)",
+                          cursor: "pointer",
+                        }}
+                      >
+                        Undo
+                      </button>
+                    </div>
+                  )}
               </div>
 
               {message.role === "user" && (
@@ -15,6 +15,14 @@ interface CommandRecord {
   duration: number;
   status: string;
   error_message?: string;
+  result?: ToolOutcome;
+}
+
+interface ToolOutcome {
+  status: "success" | "no_results" | "needs_input";
+  message: string;
+  data?: unknown;
+  follow_ups?: { label: string; intent: string; parameters: unknown }[];
 }
 
 const FILTER_PILLS: { label: string; value: FilterStatus }[] = [
@@ -52,6 +60,7 @@ export function CommandHistoryView() {
             duration: cmd.duration || 0,
             status: cmd.status || "completed",
             error_message: cmd.error_message ?? undefined,
+            result: cmd.result ?? undefined,
           }))
         );
       }
@@ -254,6 +263,19 @@ export function CommandHistoryView() {
                         </span>
                       </div>
 
+                      {/* Tool result message */}
+                      {record.result?.message && (
+                        <div
+                          style={{
+                            marginTop: "8px",
+                            fontSize: "12px",
+                            color: "var(--text-secondary)",
+                          }}
+                        >
+                          {record.result.message}
+                        </div>
+                      )}
+
                       {/* Error message (failed commands only) */}
                       {record.error_message && (
                         <div
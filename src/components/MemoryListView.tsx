@@ -1,4 +1,4 @@
-import { useState, useEffect, useMemo } from "react";
+import { useState, useEffect, useMemo, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { motion, AnimatePresence } from "framer-motion";
@@ -14,6 +14,7 @@ interface Memory {
   importance?: number;
   source?: string;
   tags?: string[];
+  snippet?: string;
 }
 
 interface MemoryListViewProps {
@@ -31,6 +32,17 @@ const FILTER_PILLS: { label: string; value: FilterTag }[] = [
   { label: "Reminders", value: "reminders" },
 ];
 
+// Snippets come back with matched terms wrapped in <mark> tags
+function renderSnippet(snippet: string) {
+  return snippet.split(/(<mark>.*?<\/mark>)/g).map((part, index) =>
+    part.startsWith("<mark>") ? (
+      <mark key={index}>{part.slice(6, -7)}</mark>
+    ) : (
+      <span key={index}>{part}</span>
+    )
+  );
+}
+
 export function MemoryListView({ onSelectMemory }: MemoryListViewProps) {
   const { getAccessToken } = useAuth();
   const [memories, setMemories] = useState<Memory[]>([]);
@@ -40,6 +52,7 @@ export function MemoryListView({ onSelectMemory }: MemoryListViewProps) {
   const [selectedMemory, setSelectedMemory] = useState<Memory | null>(null);
   const [detailPanelOpen, setDetailPanelOpen] = useState(false);
   const [debouncedSearch, setDebouncedSearch] = useState("");
+  const searchRef = useRef("");
 
   // Debounced search
   useEffect(() => {
@@ -47,10 +60,11 @@ export function MemoryListView({ onSelectMemory }: MemoryListViewProps) {
     return () => clearTimeout(timer);
   }, [searchQuery]);
 
-  // Fetch memories on mount
+  // Fetch memories on mount and whenever the search settles
   useEffect(() => {
+    searchRef.current = debouncedSearch.trim();
     fetchMemories();
-  }, []);
+  }, [debouncedSearch]);
 
   useEffect(() => {
     let unlisten: (() => void) | undefined;
@@ -71,7 +85,9 @@ export function MemoryListView({ onSelectMemory }: MemoryListViewProps) {
     setIsLoading(true);
     try {
       const accessToken = await getAccessToken();
-      const data = await invoke<any>("get_memories", { limit: 100, accessToken });
+      const query = searchRef.current || undefined;
+      const data = await invoke<any>("get_memories", { limit: 100, accessToken, query });
+      if (query !== (searchRef.current || undefined)) return;
       if (Array.isArray(data)) {
         const formattedMemories = data.map((m: any) => ({
           id: m.id || Math.random().toString(),
@@ -80,6 +96,7 @@ export function MemoryListView({ onSelectMemory }: MemoryListViewProps) {
           importance: m.importance || 0.5,
           source: m.source || "user_input",
           tags: m.tags ? (Array.isArray(m.tags) ? m.tags : []) : [],
+          snippet: m.snippet || undefined,
         }));
         setMemories(formattedMemories);
       }
@@ -90,14 +107,9 @@ export function MemoryListView({ onSelectMemory }: MemoryListViewProps) {
     }
   };
 
-  // Filter memories by search and category
+  // Search is ranked by the backend; only the category filter runs here
   const filteredMemories = useMemo(() => {
     return memories.filter((memory) => {
-      // Search filter
-      const matchesSearch = memory.content
-        .toLowerCase()
-        .includes(debouncedSearch.toLowerCase());
-
       // Category filter
       let matchesCategory = true;
       if (activeFilter !== "all") {
@@ -107,9 +119,9 @@ export function MemoryListView({ onSelectMemory }: MemoryListViewProps) {
         );
       }
 
-      return matchesSearch && matchesCategory;
+      return matchesCategory;
     });
-  }, [memories, debouncedSearch, activeFilter]);
+  }, [memories, activeFilter]);
 
   const handleSelectMemory = (memory: Memory) => {
     setSelectedMemory(memory);
@@ -263,7 +275,7 @@ export function MemoryListView({ onSelectMemory }: MemoryListViewProps) {
                 >
                   {/* Memory Text */}
                   <p style={{ fontSize: "14px", color: "var(--text-primary)", marginBottom: "16px", lineHeight: "1.6", display: "-webkit-box", WebkitLineClamp: 3, WebkitBoxOrient: "vertical", overflow: "hidden" }}>
-                    {memory.content || "[empty memory]"}
+                    {memory.snippet ? renderSnippet(memory.snippet) : memory.content || "[empty memory]"}
                   </p>
 
                   {/* Tags */}
@@ -88,7 +88,7 @@ const PERMISSION_ITEMS: {
   {
     key: "launch_apps",
     label: "Launch Applications",
-    description: "Allow Noddy to open apps on your behalf when you ask it to.",
+    description: "Allow Noddy to open and close apps on your behalf when you ask it to.",
     icon: <Terminal size={18} />,
     defaultValue: false,
   },
@@ -1,6 +1,7 @@
 import { useState, useEffect, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { motion } from "framer-motion";
+import { useAuth } from "../auth/AuthContext";
 import {
   Settings,
   Shield,
@@ -17,6 +18,7 @@ import {
   CheckCircle2,
   AlertCircle,
   RefreshCw,
+  KeyRound,
 } from "lucide-react";
 
 // ─── Types ────────────────────────────────────────────────────────────────────
@@ -27,6 +29,12 @@ interface AppSettings {
   theme: string;
   suggestions_enabled: boolean;
   auto_start: boolean;
+  missed_reminder_policy: "fire_all" | "grace_window" | "digest";
+  missed_reminder_grace_minutes: number;
+  llm_provider: "gemini" | "openai_compatible" | "ollama";
+  llm_model: string | null;
+  llm_base_url: string | null;
+  llm_api_key: string | null;
 }
 
 interface UserPermissions {
@@ -37,6 +45,15 @@ interface UserPermissions {
   background_suggestions: boolean;
 }
 
+interface SessionInfo {
+  id: string;
+  device_label: string | null;
+  created_at: number;
+  last_used_at: number | null;
+  expires_at: number;
+  current: boolean;
+}
+
 interface SettingsPageProps {
   onNavigate: (view: string) => void;
   accessToken: string;
@@ -48,6 +65,7 @@ interface SettingsPageProps {
 const SECTIONS = [
   { id: "general", label: "General", icon: Settings },
   { id: "permissions", label: "Permissions", icon: Shield },
+  { id: "account", label: "Account", icon: KeyRound },
   { id: "ai", label: "AI & Assistant", icon: Sparkles },
   { id: "notifications", label: "Notifications", icon: Bell },
   { id: "plugins", label: "Plugins", icon: Zap },
@@ -88,13 +106,31 @@ const PERMISSION_ITEMS: {
   {
     key: "launch_apps",
     label: "Launch Applications",
-    description: "Open apps on your behalf when asked.",
+    description: "Open and close apps on your behalf when asked.",
     icon: <Terminal size={16} />,
   },
 ];
 
 // ─── Small shared components ──────────────────────────────────────────────────
 
+interface CapabilityPolicy {
+  capability: string;
+  policy: "allow" | "deny" | "ask";
+  updated_at: number | null;
+}
+
+const CAPABILITY_LABELS: Record<string, string> = {
+  open_app: "Open apps",
+  kill_process: "Close apps",
+  web_search: "Web search",
+  memory_read: "Read memories",
+  memory_write: "Change memories",
+  reminder_schedule: "Schedule reminders",
+  plugin_access: "Run plugins",
+  running_apps: "See running apps",
+  background_suggestions: "Background suggestions",
+};
+
 function ToggleSwitch({
   checked,
   onChange,
@@ -278,9 +314,14 @@ export function SettingsPage({ onNavigate, accessToken, onRestartOnboarding }: S
   const [activeSection, setActiveSection] = useState("general");
   const [settings, setSettings] = useState<AppSettings | null>(null);
   const [permissions, setPermissions] = useState<UserPermissions | null>(null);
+  const [policies, setPolicies] = useState<CapabilityPolicy[]>([]);
   const [toast, setToast] = useState<{ message: string; type: "success" | "error" } | null>(null);
   const [saving, setSaving] = useState(false);
   const [rebuildingGraph, setRebuildingGraph] = useState(false);
+  const [sessions, setSessions] = useState<SessionInfo[]>([]);
+  const [currentPassword, setCurrentPassword] = useState("");
+  const [newPassword, setNewPassword] = useState("");
+  const { changePassword, getAccessToken, logout } = useAuth();
 
   useEffect(() => {
     invoke<AppSettings>("get_settings")
@@ -292,6 +333,12 @@ export function SettingsPage({ onNavigate, accessToken, onRestartOnboarding }: S
       .catch((e) => console.error("Failed to load permissions:", e));
   }, []);
 
+  useEffect(() => {
+    invoke<CapabilityPolicy[]>("get_permission_policies", { accessToken })
+      .then(setPolicies)
+      .catch((e) => console.error("Failed to load permission policies:", e));
+  }, [accessToken]);
+
   const showToast = useCallback(
     (message: string, type: "success" | "error") => {
       setToast({ message, type });
@@ -338,6 +385,74 @@ export function SettingsPage({ onNavigate, accessToken, onRestartOnboarding }: S
     [showToast]
   );
 
+  const updatePolicy = useCallback(
+    async (capability: string, policy: CapabilityPolicy["policy"]) => {
+      try {
+        const updated = await invoke<CapabilityPolicy[]>("set_permission_policy", {
+          accessToken,
+          capability,
+          policy,
+        });
+        setPolicies(updated);
+        showToast("Policy updated", "success");
+      } catch (e) {
+        showToast("Failed to update policy", "error");
+        console.error(e);
+      }
+    },
+    [accessToken, showToast]
+  );
+
+  // Session calls use a fresh token: a password change replaces this device's session.
+  const loadSessions = useCallback(async () => {
+    try {
+      const token = await getAccessToken();
+      setSessions(await invoke<SessionInfo[]>("list_sessions", { accessToken: token }));
+    } catch (e) {
+      console.error("Failed to load sessions:", e);
+    }
+  }, [getAccessToken]);
+
+  useEffect(() => {
+    if (activeSection === "account") void loadSessions();
+  }, [activeSection, loadSessions]);
+
+  const handleChangePassword = useCallback(async () => {
+    try {
+      await changePassword(currentPassword, newPassword);
+      setCurrentPassword("");
+      setNewPassword("");
+      showToast("Password changed. Other devices were signed out.", "success");
+      await loadSessions();
+    } catch (e) {
+      showToast(String(e), "error");
+    }
+  }, [changePassword, currentPassword, newPassword, showToast, loadSessions]);
+
+  const handleRevokeSession = useCallback(
+    async (sessionId: string) => {
+      try {
+        await invoke("revoke_session", { accessToken: await getAccessToken(), sessionId });
+        showToast("Device signed out", "success");
+        await loadSessions();
+      } catch (e) {
+        showToast("Failed to sign out device", "error");
+        console.error(e);
+      }
+    },
+    [getAccessToken, showToast, loadSessions]
+  );
+
+  const handleRevokeAll = useCallback(async () => {
+    try {
+      await invoke("revoke_all_sessions", { accessToken: await getAccessToken() });
+      await logout();
+    } catch (e) {
+      showToast("Failed to sign out everywhere", "error");
+      console.error(e);
+    }
+  }, [getAccessToken, logout, showToast]);
+
   const handleRebuildGraph = useCallback(async () => {
     setRebuildingGraph(true);
     try {
@@ -441,6 +556,98 @@ export function SettingsPage({ onNavigate, accessToken, onRestartOnboarding }: S
                 />
               </SettingRow>
             ))}
+            <p
+              style={{
+                fontSize: "13px",
+                color: "var(--text-secondary)",
+                margin: "20px 0 8px",
+                lineHeight: 1.5,
+              }}
+            >
+              Per-account policy for chat requests. "Ask" pauses the request until you approve it.
+            </p>
+            {policies.map((entry) => (
+              <SettingRow key={entry.capability} label={CAPABILITY_LABELS[entry.capability] ?? entry.capability}>
+                <select
+                  value={entry.policy}
+                  onChange={(e) =>
+                    void updatePolicy(entry.capability, e.target.value as CapabilityPolicy["policy"])
+                  }
+                  className="search-input"
+                  style={{ fontSize: "13px", padding: "6px 10px", width: "110px" }}
+                >
+                  <option value="allow">Allow</option>
+                  <option value="ask">Ask</option>
+                  <option value="deny">Deny</option>
+                </select>
+              </SettingRow>
+            ))}
+          </SectionCard>
+        );
+
+      case "account":
+        return (
+          <SectionCard title="Account" icon={KeyRound} delay={0.05}>
+            <SettingRow label="Current Password">
+              <input
+                type="password"
+                value={currentPassword}
+                onChange={(e) => setCurrentPassword(e.target.value)}
+                className="search-input"
+                style={{ fontSize: "13px", padding: "6px 10px", width: "200px" }}
+              />
+            </SettingRow>
+            <SettingRow label="New Password" description="At least 8 characters. Signs out every other device.">
+              <input
+                type="password"
+                value={newPassword}
+                onChange={(e) => setNewPassword(e.target.value)}
+                className="search-input"
+                style={{ fontSize: "13px", padding: "6px 10px", width: "200px" }}
+              />
+            </SettingRow>
+            <div style={{ display: "flex", justifyContent: "flex-end", padding: "10px 0" }}>
+              <motion.button
+                className="btn btn-secondary"
+                onClick={() => void handleChangePassword()}
+                disabled={!currentPassword || !newPassword}
+                whileHover={{ scale: 1.02 }}
+                whileTap={{ scale: 0.98 }}
+                style={{ fontSize: "12px", padding: "6px 12px" }}
+              >
+                Change Password
+              </motion.button>
+            </div>
+            {sessions.map((entry) => (
+              <SettingRow
+                key={entry.id}
+                label={`${entry.device_label ?? "Unknown device"}${entry.current ? " (this device)" : ""}`}
+                description={`Last active ${new Date((entry.last_used_at ?? entry.created_at) * 1000).toLocaleString()}`}
+              >
+                {!entry.current && (
+                  <motion.button
+                    className="btn btn-secondary"
+                    onClick={() => void handleRevokeSession(entry.id)}
+                    whileHover={{ scale: 1.02 }}
+                    whileTap={{ scale: 0.98 }}
+                    style={{ fontSize: "12px", padding: "6px 12px" }}
+                  >
+                    Sign Out
+                  </motion.button>
+                )}
+              </SettingRow>
+            ))}
+            <div style={{ display: "flex", justifyContent: "flex-end", paddingTop: "12px" }}>
+              <motion.button
+                className="btn btn-secondary"
+                onClick={() => void handleRevokeAll()}
+                whileHover={{ scale: 1.02 }}
+                whileTap={{ scale: 0.98 }}
+                style={{ fontSize: "12px", padding: "6px 12px" }}
+              >
+                Sign Out Everywhere
+              </motion.button>
+            </div>
           </SectionCard>
         );
 
@@ -457,25 +664,80 @@ export function SettingsPage({ onNavigate, accessToken, onRestartOnboarding }: S
                 disabled={saving}
               />
             </SettingRow>
-            <SettingRow label="AI Model" description="The language model powering Noddy.">
-              <span
-                className="badge badge-primary"
-                style={{ fontSize: "12px" }}
+            <SettingRow label="AI Provider" description="The backend that answers chat and planning requests.">
+              <select
+                value={settings.llm_provider}
+                onChange={(e) =>
+                  void updateSetting("llm_provider", e.target.value as AppSettings["llm_provider"])
+                }
+                className="search-input"
+                style={{ fontSize: "13px", padding: "6px 10px", width: "170px" }}
+                disabled={saving}
               >
-                Gemini Flash
-              </span>
+                <option value="gemini">Gemini</option>
+                <option value="openai_compatible">OpenAI-compatible</option>
+                <option value="ollama">Ollama</option>
+              </select>
             </SettingRow>
-            <SettingRow
-              label="API Key"
-              description="Set via GEMINI_API_KEY environment variable in your .env file."
-            >
-              <div style={{ display: "flex", alignItems: "center", gap: "6px" }}>
-                <Eye size={14} color="var(--text-muted)" />
-                <span style={{ fontSize: "12px", color: "var(--text-muted)", fontFamily: "monospace" }}>
-                  ••••••••
-                </span>
-              </div>
+            <SettingRow label="Model" description="Leave empty to use the provider's default model.">
+              <input
+                key={`model-${settings.llm_provider}`}
+                defaultValue={settings.llm_model ?? ""}
+                onBlur={(e) => {
+                  const value = e.target.value.trim() || null;
+                  if (value !== settings.llm_model) void updateSetting("llm_model", value);
+                }}
+                className="search-input"
+                style={{ fontSize: "13px", padding: "6px 10px", width: "170px" }}
+                placeholder="Default"
+                disabled={saving}
+              />
             </SettingRow>
+            <SettingRow label="Base URL" description="Override the API endpoint, e.g. a local LM Studio or Ollama server.">
+              <input
+                key={`base-url-${settings.llm_provider}`}
+                defaultValue={settings.llm_base_url ?? ""}
+                onBlur={(e) => {
+                  const value = e.target.value.trim() || null;
+                  if (value !== settings.llm_base_url) void updateSetting("llm_base_url", value);
+                }}
+                className="search-input"
+                style={{ fontSize: "13px", padding: "6px 10px", width: "170px" }}
+                placeholder="Default"
+                disabled={saving}
+              />
+            </SettingRow>
+            {settings.llm_provider === "openai_compatible" ? (
+              <SettingRow
+                label="API Key"
+                description="Sent to the server above. Leave empty to use OPENAI_API_KEY, which only goes to api.openai.com."
+              >
+                <input
+                  type="password"
+                  defaultValue={settings.llm_api_key ?? ""}
+                  onBlur={(e) => {
+                    const value = e.target.value.trim() || null;
+                    if (value !== settings.llm_api_key) void updateSetting("llm_api_key", value);
+                  }}
+                  className="search-input"
+                  style={{ fontSize: "13px", padding: "6px 10px", width: "170px" }}
+                  placeholder="Not set"
+                  disabled={saving}
+                />
+              </SettingRow>
+            ) : (
+              <SettingRow
+                label="API Key"
+                description="Set GEMINI_API_KEY in your .env file. Ollama needs none."
+              >
+                <div style={{ display: "flex", alignItems: "center", gap: "6px" }}>
+                  <Eye size={14} color="var(--text-muted)" />
+                  <span style={{ fontSize: "12px", color: "var(--text-muted)", fontFamily: "monospace" }}>
+                    ••••••••
+                  </span>
+                </div>
+              </SettingRow>
+            )}
             <SettingRow label="Background Suggestions" description="Allow Noddy to generate context in the background.">
               <ToggleSwitch
                 checked={permissions.background_suggestions}
@@ -488,18 +750,48 @@ export function SettingsPage({ onNavigate, accessToken, onRestartOnboarding }: S
       case "notifications":
         return (
           <SectionCard title="Notifications" icon={Bell} delay={0.05}>
-            <div
-              style={{
-                padding: "24px 0",
-                textAlign: "center",
-                color: "var(--text-secondary)",
-              }}
+            <SettingRow
+              label="Missed Reminders"
+              description="What to do with reminders that came due while Noddy was closed."
             >
-              <Bell size={28} style={{ opacity: 0.4, marginBottom: "10px" }} />
-              <p style={{ fontSize: "13px" }}>
-                Notification preferences coming soon. Reminders always notify when due.
-              </p>
-            </div>
+              <select
+                value={settings.missed_reminder_policy}
+                onChange={(e) =>
+                  void updateSetting(
+                    "missed_reminder_policy",
+                    e.target.value as AppSettings["missed_reminder_policy"]
+                  )
+                }
+                className="search-input"
+                style={{ fontSize: "13px", padding: "6px 10px", width: "170px" }}
+                disabled={saving}
+              >
+                <option value="fire_all">Fire all</option>
+                <option value="grace_window">Skip older ones</option>
+                <option value="digest">Summarize older ones</option>
+              </select>
+            </SettingRow>
+            {settings.missed_reminder_policy !== "fire_all" && (
+              <SettingRow
+                label="Grace Window"
+                description="Minutes late a reminder can be and still fire normally."
+              >
+                <input
+                  type="number"
+                  min={0}
+                  key={`grace-${settings.missed_reminder_grace_minutes}`}
+                  defaultValue={settings.missed_reminder_grace_minutes}
+                  onBlur={(e) => {
+                    const value = Math.max(0, Math.floor(Number(e.target.value) || 0));
+                    if (value !== settings.missed_reminder_grace_minutes)
+                      void updateSetting("missed_reminder_grace_minutes", value);
+                  }}
+                  className="search-input"
+                  style={{ fontSize: "13px", padding: "6px 10px", width: "170px" }}
+                  disabled={saving}
+                />
+              </SettingRow>
+            )}
           </SectionCard>
         );
 